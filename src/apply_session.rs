@@ -0,0 +1,245 @@
+//! Back-annotation of a router's session onto the design it was routed from.
+
+use std::collections::HashMap;
+
+use crate::{
+    diagnostics::{Diagnostic, ScopePath},
+    dsn_error::{DsnError, DsnErrorKind},
+    dsn_struct::{DsnStruct, Pin2, Wire, Wiring, WiringVia},
+    session_struct::{Routes, SessionPlacement, SessionStruct, WasIs},
+};
+
+fn session_error(kind: DsnErrorKind, segments: &[&str]) -> DsnError {
+    let mut path = vec!["session".to_string()];
+    path.extend(segments.iter().map(|segment| segment.to_string()));
+    DsnError::new(kind, ScopePath::from_segments(path))
+}
+
+fn dangling(kind: &str, name: &str, segments: &[&str]) -> DsnError {
+    session_error(
+        DsnErrorKind::DanglingReference {
+            kind: kind.to_string(),
+            name: name.to_string(),
+        },
+        segments,
+    )
+}
+
+fn pin_name(pin: &Pin2) -> String {
+    format!("{}-{}", pin.component_name, pin.pin_id)
+}
+
+fn has_pin(dsn: &DsnStruct, pin: &Pin2) -> bool {
+    dsn.placement.components.iter().any(|component| {
+        component
+            .instances
+            .iter()
+            .any(|instance| instance.reference == pin.component_name)
+            && dsn
+                .library
+                .images
+                .get(&component.name)
+                .is_some_and(|image| image.pins.contains_key(&pin.pin_id))
+    })
+}
+
+/// Moves every net connection from the `was` pin of a swap to its `is` pin.
+/// All swaps apply at once, so a pair of entries exchanging two pins swaps
+/// them rather than moving both connections onto one pin.
+fn apply_was_is(dsn: &mut DsnStruct, was_is: &WasIs) -> Result<(), DsnError> {
+    let mut moves: HashMap<(&str, &str), &Pin2> = HashMap::new();
+    for swap in &was_is.pin_swaps {
+        for pin in [&swap.was, &swap.is] {
+            if !has_pin(dsn, pin) {
+                return Err(dangling("pin", &pin_name(pin), &["was_is"]));
+            }
+        }
+        let key = (swap.was.component_name.as_str(), swap.was.pin_id.as_str());
+        if moves.insert(key, &swap.is).is_some() {
+            return Err(session_error(
+                DsnErrorKind::DuplicateName {
+                    kind: "was pin".to_string(),
+                    name: pin_name(&swap.was),
+                },
+                &["was_is"],
+            ));
+        }
+    }
+
+    for net in &mut dsn.network.nets {
+        for pin in &mut net.pins {
+            if let Some(is) = moves.get(&(pin.component_name.as_str(), pin.pin_id.as_str())) {
+                *pin = (*is).clone();
+            }
+        }
+    }
+
+    // a swap that is not a permutation leaves a pin on two nets
+    let mut nets_of_pins: HashMap<String, &str> = HashMap::new();
+    for net in &dsn.network.nets {
+        for pin in &net.pins {
+            if let Some(other) = nets_of_pins.insert(pin_name(pin), &net.name) {
+                return Err(session_error(
+                    DsnErrorKind::AlreadyConnected {
+                        pin: pin_name(pin),
+                        net: other.to_string(),
+                    },
+                    &["was_is"],
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Moves, rotates, flips and re-images the placed components as the session
+/// placed them. Components the session does not list stay where they are.
+fn apply_placement(dsn: &mut DsnStruct, placement: &SessionPlacement) -> Result<(), DsnError> {
+    for component in &placement.components {
+        let segment = ScopePath::segment("component", &component.name);
+        if !dsn.library.images.contains_key(&component.name) {
+            return Err(dangling("image", &component.name, &["placement", &segment]));
+        }
+        for place in &component.instances {
+            let instance = dsn
+                .placement
+                .components
+                .iter()
+                .flat_map(|component| &component.instances)
+                .find(|instance| instance.reference == place.reference)
+                .ok_or_else(|| dangling("component", &place.reference, &["placement", &segment]))?;
+            let flipped = instance.side != place.side;
+            dsn.swap_image(&place.reference, &component.name)?;
+            dsn.move_component(&place.reference, place.position)?;
+            dsn.rotate_component(&place.reference, place.rotation)?;
+            if flipped {
+                dsn.flip_component(&place.reference)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the wires and vias of the wiring with the copper of
+/// `network_out`. The padstacks of new vias are taken from `library_out`
+/// unless the library has them. Scopes the session parser kept verbatim,
+/// such as polygon wires, are in session units and cannot be taken over;
+/// each gives a warning in `warnings`.
+fn apply_routes(
+    dsn: &mut DsnStruct,
+    routes: &Routes,
+    warnings: &mut Vec<Diagnostic>,
+) -> Result<(), DsnError> {
+    let mut wires: Vec<Wire> = Vec::new();
+    let mut vias: Vec<WiringVia> = Vec::new();
+    for net in &routes.network_out {
+        let segment = ScopePath::segment("net", &net.name);
+        if !dsn
+            .network
+            .nets
+            .iter()
+            .any(|dsn_net| dsn_net.name == net.name)
+        {
+            return Err(dangling("net", &net.name, &["routes", "network_out"]));
+        }
+        for scope in &net.unknown {
+            let items = scope.expr.as_list().map(Vec::as_slice).unwrap_or_default();
+            let what = match items {
+                [keyword, shape, ..] if keyword.as_atom().is_some_and(|k| k == "wire") => {
+                    let shape = shape.as_list().map(Vec::as_slice).unwrap_or_default();
+                    let atom = |index: usize| {
+                        shape
+                            .get(index)
+                            .and_then(|x| x.as_atom())
+                            .map_or("?", |atom| atom.as_str())
+                    };
+                    format!("{} wire on {} not taken over", atom(0), atom(1))
+                }
+                _ => format!("{} not taken over", ScopePath::segment_for(items)),
+            };
+            warnings.push(Diagnostic {
+                location: ScopePath::from_segments(vec![
+                    "session".to_string(),
+                    "routes".to_string(),
+                    "network_out".to_string(),
+                    segment.clone(),
+                ]),
+                message: what,
+            });
+        }
+        for wire in &net.wires {
+            wires.push(Wire {
+                net: Some(net.name.clone()),
+                ..wire.clone()
+            });
+        }
+        for via in &net.vias {
+            if !dsn.library.pad_stacks.contains_key(&via.padstack_name) {
+                let padstack = routes
+                    .library_out
+                    .iter()
+                    .find(|padstack| padstack.name == via.padstack_name)
+                    .ok_or_else(|| {
+                        dangling(
+                            "padstack",
+                            &via.padstack_name,
+                            &["routes", "network_out", &segment],
+                        )
+                    })?;
+                dsn.library
+                    .pad_stacks
+                    .insert(padstack.name.clone(), padstack.clone());
+            }
+            vias.push(WiringVia {
+                net: Some(net.name.clone()),
+                ..via.clone()
+            });
+        }
+    }
+    // copper the model does not hold stays as the design had it
+    let unknown = dsn
+        .wiring
+        .take()
+        .map(|wiring| wiring.unknown)
+        .unwrap_or_default();
+    dsn.wiring = Some(Wiring {
+        wires,
+        vias,
+        unknown,
+    });
+    Ok(())
+}
+
+/// Merges a session into the design it was routed from, giving a routed
+/// design that can be analysed or exported again.
+///
+/// Pin swaps recorded in `was_is` are carried into the nets of the design so
+/// that the netlist matches the routed copper. The session placement updates
+/// the placed components, and the wires and vias of `network_out` replace
+/// the wires and vias of the design's `wiring`: a router reports all copper
+/// of the board, including the wires it was given. Only path wires and vias
+/// are taken over. Scopes of the wiring the parser kept verbatim stay, and
+/// anything in `network_out` that is not taken over, such as a polygon
+/// wire, is returned as a warning.
+///
+/// The session is checked against the design as it is applied; if it refers
+/// to a component, image, net or padstack the design does not have, the
+/// design is left unchanged.
+pub fn apply_session(
+    dsn: &mut DsnStruct,
+    session: &SessionStruct,
+) -> Result<Vec<Diagnostic>, DsnError> {
+    let mut applied = dsn.clone();
+    let mut warnings = Vec::new();
+    if let Some(was_is) = &session.was_is {
+        apply_was_is(&mut applied, was_is)?;
+    }
+    if let Some(placement) = &session.placement {
+        apply_placement(&mut applied, placement)?;
+    }
+    if let Some(routes) = &session.routes {
+        apply_routes(&mut applied, routes, &mut warnings)?;
+    }
+    *dsn = applied;
+    Ok(warnings)
+}
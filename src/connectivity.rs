@@ -0,0 +1,207 @@
+//! Which pads of each net the routed copper actually connects.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use crate::{
+    apply_session::apply_session,
+    copper::{CopperItem, CopperSource, close_pairs, design_copper},
+    dsn_error::DsnError,
+    dsn_index::DsnIndex,
+    dsn_struct::DsnStruct,
+    session_struct::SessionStruct,
+    units::Length,
+};
+
+/// Union-find over item indices.
+struct DisjointSets {
+    parents: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(len: usize) -> DisjointSets {
+        DisjointSets {
+            parents: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, mut item: usize) -> usize {
+        while self.parents[item] != item {
+            self.parents[item] = self.parents[self.parents[item]];
+            item = self.parents[item];
+        }
+        item
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parents[b] = a;
+        }
+    }
+}
+
+/// Groups `items` into electrically connected sets: copper touching on the
+/// same layer is connected, and so are the layers of one pad or via.
+fn connect(items: &[CopperItem]) -> DisjointSets {
+    let mut sets = DisjointSets::new(items.len());
+    let mut first_of_source: HashMap<&CopperSource, usize> = HashMap::new();
+    for (index, item) in items.iter().enumerate() {
+        match first_of_source.get(&item.source) {
+            Some(&first) => sets.union(first, index),
+            None => {
+                first_of_source.insert(&item.source, index);
+            }
+        }
+    }
+    for (a, b) in close_pairs(items, Length::ZERO) {
+        if items[a].shape.gap(&items[b].shape).0 == Length::ZERO {
+            sets.union(a, b);
+        }
+    }
+    sets
+}
+
+/// How far the routing of one net got.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetCompletion {
+    pub net: String,
+    /// Pins of the net, such as `U1-5`, grouped by the copper connecting
+    /// them. The largest group comes first; a fully routed net has one.
+    pub groups: Vec<Vec<String>>,
+}
+
+impl NetCompletion {
+    pub fn pin_count(&self) -> usize {
+        self.groups.iter().map(Vec::len).sum()
+    }
+
+    /// Pins left as islands, cut off from the largest group.
+    pub fn islands(&self) -> &[Vec<String>] {
+        self.groups.get(1..).unwrap_or(&[])
+    }
+
+    /// Connections still needed to join all groups.
+    pub fn unrouted(&self) -> usize {
+        self.groups.len().saturating_sub(1)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.unrouted() == 0
+    }
+
+    /// Share of the connections of the net that are routed, in percent. A
+    /// net of `n` pins needs `n - 1` connections.
+    pub fn completion(&self) -> f64 {
+        let needed = self.pin_count().saturating_sub(1);
+        if needed == 0 {
+            return 100.0;
+        }
+        (needed - self.unrouted()) as f64 / needed as f64 * 100.0
+    }
+}
+
+impl fmt::Display for NetCompletion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "net {}: {:.0}% routed, {} of {} connections open",
+            self.net,
+            self.completion(),
+            self.unrouted(),
+            self.pin_count().saturating_sub(1)
+        )?;
+        for island in self.islands() {
+            write!(f, "\n  island: {}", island.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks which pins of every net the pads, wires and vias of the design
+/// connect. Pins with no pad copper count as islands of their own.
+pub fn route_completeness(dsn: &DsnStruct) -> Result<Vec<NetCompletion>, Vec<DsnError>> {
+    let index = DsnIndex::new(dsn)?;
+    let items = design_copper(&index).map_err(|error| vec![error])?;
+    let mut sets = connect(&items);
+
+    let mut pad_items: HashMap<(&str, &str), usize> = HashMap::new();
+    for (item_index, item) in items.iter().enumerate() {
+        if let CopperSource::Pad { reference, pin_id } = &item.source {
+            pad_items.entry((reference, pin_id)).or_insert(item_index);
+        }
+    }
+
+    let mut completions = Vec::new();
+    for net in &dsn.network.nets {
+        let mut groups: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+        for (pin_index, pin) in net.pins.iter().enumerate() {
+            let key = (pin.component_name.as_str(), pin.pin_id.as_str());
+            // a pin without copper is alone; keep it apart from every set
+            let group = match pad_items.get(&key) {
+                Some(&item_index) => sets.find(item_index),
+                None => items.len() + pin_index,
+            };
+            groups
+                .entry(group)
+                .or_default()
+                .push(format!("{}-{}", pin.component_name, pin.pin_id));
+        }
+        let mut groups: Vec<Vec<String>> = groups.into_values().collect();
+        groups.sort_by_key(|group| std::cmp::Reverse(group.len()));
+        completions.push(NetCompletion {
+            net: net.name.clone(),
+            groups,
+        });
+    }
+    Ok(completions)
+}
+
+/// [`route_completeness`] of the design once `session` has been applied.
+pub fn session_completeness(
+    dsn: &DsnStruct,
+    session: &SessionStruct,
+) -> Result<Vec<NetCompletion>, Vec<DsnError>> {
+    let mut routed = dsn.clone();
+    apply_session(&mut routed, session).map_err(|error| vec![error])?;
+    route_completeness(&routed)
+}
+
+/// Share of all connections of the board that are routed, in percent.
+pub fn board_completion(completions: &[NetCompletion]) -> f64 {
+    let needed: usize = completions
+        .iter()
+        .map(|completion| completion.pin_count().saturating_sub(1))
+        .sum();
+    let unrouted: usize = completions.iter().map(NetCompletion::unrouted).sum();
+    if needed == 0 {
+        return 100.0;
+    }
+    (needed - unrouted) as f64 / needed as f64 * 100.0
+}
+
+/// One line per net with the islands of incomplete nets below it, and the
+/// board totals at the end.
+pub fn completeness_report(completions: &[NetCompletion]) -> String {
+    let mut report: String = completions
+        .iter()
+        .map(|completion| format!("{}\n", completion))
+        .collect();
+    let needed: usize = completions
+        .iter()
+        .map(|completion| completion.pin_count().saturating_sub(1))
+        .sum();
+    let unrouted: usize = completions.iter().map(NetCompletion::unrouted).sum();
+    let complete = completions
+        .iter()
+        .filter(|completion| completion.is_complete())
+        .count();
+    report.push_str(&format!(
+        "{} of {} nets complete, {} of {} connections open\n",
+        complete,
+        completions.len(),
+        unrouted,
+        needed
+    ));
+    report
+}
@@ -0,0 +1,485 @@
+//! Copper on the board as plain geometry: pads, wire segments and vias, each
+//! on one layer and tagged with its net and the object it came from.
+
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+
+use crate::{
+    diagnostics::ScopePath,
+    dsn_error::{DsnError, DsnErrorKind},
+    dsn_index::DsnIndex,
+    dsn_struct::{DsnStruct, Layer, PadStack, Shape, Side, Wiring},
+    geometry::Point,
+    units::Length,
+};
+
+/// A board coordinate in floating point nanometres, for the distance maths.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Vec2 {
+    x: f64,
+    y: f64,
+}
+
+impl Vec2 {
+    fn from_point(point: Point) -> Vec2 {
+        Vec2 {
+            x: point.x.nm() as f64,
+            y: point.y.nm() as f64,
+        }
+    }
+
+    fn to_point(self) -> Point {
+        Point::new(
+            Length::from_nm(self.x.round() as i64),
+            Length::from_nm(self.y.round() as i64),
+        )
+    }
+
+    fn dot(self, other: Vec2) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    fn cross(self, other: Vec2) -> f64 {
+        self.x * other.y - self.y * other.x
+    }
+
+    fn length(self) -> f64 {
+        self.dot(self).sqrt()
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+    fn add(self, rhs: Vec2) -> Vec2 {
+        Vec2 {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+        }
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+    fn sub(self, rhs: Vec2) -> Vec2 {
+        Vec2 {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+        }
+    }
+}
+
+impl Mul<f64> for Vec2 {
+    type Output = Vec2;
+    fn mul(self, rhs: f64) -> Vec2 {
+        Vec2 {
+            x: self.x * rhs,
+            y: self.y * rhs,
+        }
+    }
+}
+
+fn closest_on_segment(point: Vec2, start: Vec2, end: Vec2) -> Vec2 {
+    let direction = end - start;
+    let length_squared = direction.dot(direction);
+    if length_squared == 0.0 {
+        return start;
+    }
+    let t = ((point - start).dot(direction) / length_squared).clamp(0.0, 1.0);
+    start + direction * t
+}
+
+fn segment_intersection(a: (Vec2, Vec2), b: (Vec2, Vec2)) -> Option<Vec2> {
+    let r = a.1 - a.0;
+    let s = b.1 - b.0;
+    let denominator = r.cross(s);
+    // parallel segments that overlap are found by the endpoint distances
+    if denominator == 0.0 {
+        return None;
+    }
+    let t = (b.0 - a.0).cross(s) / denominator;
+    let u = (b.0 - a.0).cross(r) / denominator;
+    ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)).then(|| a.0 + r * t)
+}
+
+/// Distance between two segments and the closest point on each.
+fn segments_closest(a: (Vec2, Vec2), b: (Vec2, Vec2)) -> (f64, Vec2, Vec2) {
+    if let Some(point) = segment_intersection(a, b) {
+        return (0.0, point, point);
+    }
+    [
+        (a.0, closest_on_segment(a.0, b.0, b.1)),
+        (a.1, closest_on_segment(a.1, b.0, b.1)),
+        (closest_on_segment(b.0, a.0, a.1), b.0),
+        (closest_on_segment(b.1, a.0, a.1), b.1),
+    ]
+    .into_iter()
+    .map(|(on_a, on_b)| ((on_b - on_a).length(), on_a, on_b))
+    .min_by(|x, y| x.0.total_cmp(&y.0))
+    .unwrap_or((0.0, a.0, b.0))
+}
+
+/// Even-odd test; points on the outline may go either way.
+fn polygon_contains(vertices: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+    let mut previous = match vertices.last() {
+        Some(last) => *last,
+        None => return false,
+    };
+    for &vertex in vertices {
+        if (vertex.y > point.y) != (previous.y > point.y) {
+            let x =
+                vertex.x + (point.y - vertex.y) / (previous.y - vertex.y) * (previous.x - vertex.x);
+            if point.x < x {
+                inside = !inside;
+            }
+        }
+        previous = vertex;
+    }
+    inside
+}
+
+/// The outline of a piece of copper.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CopperShape {
+    /// A track segment with round ends, or a round pad when both ends
+    /// coincide.
+    Segment {
+        start: Point,
+        end: Point,
+        width: Length,
+    },
+    /// A filled polygon whose outline is drawn with a round aperture of
+    /// `aperture_width`.
+    Polygon {
+        vertices: Vec<Point>,
+        aperture_width: Length,
+    },
+}
+
+impl CopperShape {
+    pub fn circle(center: Point, diameter: Length) -> CopperShape {
+        CopperShape::Segment {
+            start: center,
+            end: center,
+            width: diameter,
+        }
+    }
+
+    /// Centre lines of the shape, and the area it fills when it is a polygon.
+    fn core(&self) -> (Vec<(Vec2, Vec2)>, Option<Vec<Vec2>>, f64) {
+        match self {
+            CopperShape::Segment { start, end, width } => (
+                vec![(Vec2::from_point(*start), Vec2::from_point(*end))],
+                None,
+                width.nm() as f64 / 2.0,
+            ),
+            CopperShape::Polygon {
+                vertices,
+                aperture_width,
+            } => {
+                let mut area: Vec<Vec2> = vertices.iter().copied().map(Vec2::from_point).collect();
+                // a closing vertex repeating the first adds nothing
+                if area.len() > 1 && area.first() == area.last() {
+                    area.pop();
+                }
+                let edges = (0..area.len())
+                    .map(|i| (area[i], area[(i + 1) % area.len()]))
+                    .collect();
+                (edges, Some(area), aperture_width.nm() as f64 / 2.0)
+            }
+        }
+    }
+
+    /// Copper-to-copper distance to `other`, zero where the two touch or
+    /// overlap, and a point between them where that distance is measured.
+    pub fn gap(&self, other: &CopperShape) -> (Length, Point) {
+        let (edges, area, radius) = self.core();
+        let (other_edges, other_area, other_radius) = other.core();
+
+        // one shape lying entirely inside the other polygon
+        if let (Some(area), Some(edge)) = (&area, other_edges.first())
+            && polygon_contains(area, edge.0)
+        {
+            return (Length::ZERO, edge.0.to_point());
+        }
+        if let (Some(other_area), Some(edge)) = (&other_area, edges.first())
+            && polygon_contains(other_area, edge.0)
+        {
+            return (Length::ZERO, edge.0.to_point());
+        }
+
+        let Some((distance, on_self, on_other)) = edges
+            .iter()
+            .flat_map(|edge| {
+                other_edges
+                    .iter()
+                    .map(|other_edge| segments_closest(*edge, *other_edge))
+            })
+            .min_by(|x, y| x.0.total_cmp(&y.0))
+        else {
+            return (Length::ZERO, Point::ORIGIN);
+        };
+        let gap = (distance - radius - other_radius).max(0.0);
+        // midway between the two copper surfaces
+        let direction = if distance > 0.0 {
+            (on_other - on_self) * (1.0 / distance)
+        } else {
+            Vec2 { x: 0.0, y: 0.0 }
+        };
+        let location = (on_self + on_other) * 0.5 + direction * ((radius - other_radius) / 2.0);
+        (Length::from_nm(gap.round() as i64), location.to_point())
+    }
+
+    /// Whether `point` lies on the shape or its outline.
+    pub fn contains(&self, point: Point) -> bool {
+        self.gap(&CopperShape::circle(point, Length::ZERO)).0 == Length::ZERO
+    }
+
+    /// Lower left and upper right corner of the box around the copper.
+    pub fn bounds(&self) -> (Point, Point) {
+        let (points, half_width): (&[Point], Length) = match self {
+            CopperShape::Segment { start, end, width } => {
+                let min = Point::new(start.x.min(end.x), start.y.min(end.y));
+                let max = Point::new(start.x.max(end.x), start.y.max(end.y));
+                let half = Point::new(*width / 2, *width / 2);
+                return (min - half, max + half);
+            }
+            CopperShape::Polygon {
+                vertices,
+                aperture_width,
+            } => (vertices, *aperture_width / 2),
+        };
+        let first = points.first().copied().unwrap_or(Point::ORIGIN);
+        let (min, max) = points.iter().fold((first, first), |(min, max), point| {
+            (
+                Point::new(min.x.min(point.x), min.y.min(point.y)),
+                Point::new(max.x.max(point.x), max.y.max(point.y)),
+            )
+        });
+        let half = Point::new(half_width, half_width);
+        (min - half, max + half)
+    }
+}
+
+/// The design object a piece of copper belongs to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CopperSource {
+    Pad {
+        reference: String,
+        pin_id: String,
+    },
+    /// Index into the wires of the wiring.
+    Wire(usize),
+    /// Index into the vias of the wiring.
+    Via(usize),
+}
+
+impl fmt::Display for CopperSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CopperSource::Pad { reference, pin_id } => write!(f, "pad {}-{}", reference, pin_id),
+            CopperSource::Wire(index) => write!(f, "wire #{}", index),
+            CopperSource::Via(index) => write!(f, "via #{}", index),
+        }
+    }
+}
+
+/// A piece of copper on one layer. Pads and vias with copper on several
+/// layers give one item per layer, all with the same source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CopperItem {
+    pub layer: String,
+    pub net: Option<String>,
+    pub shape: CopperShape,
+    pub source: CopperSource,
+}
+
+/// Names of the structure layers a shape on `layer` has copper on: `signal`
+/// stands for every signal layer, any other name for itself.
+pub fn covered_layers<'a>(layer: &'a str, layers: &'a [Layer]) -> Vec<&'a str> {
+    if layer == "signal" && !layers.is_empty() {
+        layers.iter().map(|layer| layer.name.as_str()).collect()
+    } else {
+        vec![layer]
+    }
+}
+
+/// Layer a pad shape ends up on once its component is placed on `side`: the
+/// layer stack is mirrored for parts on the back.
+fn placed_layer(layer: &str, side: Side, layers: &[Layer]) -> String {
+    let index = layers.iter().position(|l| l.name == layer);
+    match (side, index) {
+        (Side::Back, Some(index)) => layers[layers.len() - 1 - index].name.clone(),
+        _ => layer.to_string(),
+    }
+}
+
+/// Outline of `shape` with every point mapped to the board by `place`.
+pub fn shape_outline(shape: &Shape, place: impl Fn(Point) -> Point) -> CopperShape {
+    match shape {
+        Shape::Circle { diameter, center } => CopperShape::circle(place(*center), *diameter),
+        Shape::Rect {
+            x_min,
+            y_min,
+            x_max,
+            y_max,
+        } => CopperShape::Polygon {
+            vertices: [
+                (*x_min, *y_min),
+                (*x_max, *y_min),
+                (*x_max, *y_max),
+                (*x_min, *y_max),
+            ]
+            .into_iter()
+            .map(|(x, y)| place(Point::new(x, y)))
+            .collect(),
+            aperture_width: Length::ZERO,
+        },
+        Shape::Polygon {
+            aperture_width,
+            vertices,
+        } => CopperShape::Polygon {
+            vertices: vertices.iter().map(|vertex| place(*vertex)).collect(),
+            aperture_width: *aperture_width,
+        },
+    }
+}
+
+/// Shapes of `padstack` with every point mapped to the board by `place`,
+/// one per layer the shape covers.
+fn padstack_shapes<'a>(
+    padstack: &'a PadStack,
+    layers: &'a [Layer],
+    place: impl Fn(Point) -> Point,
+) -> Vec<(&'a str, CopperShape)> {
+    padstack
+        .shapes
+        .iter()
+        .flat_map(|layer_shape| {
+            let shape = shape_outline(&layer_shape.shape, &place);
+            covered_layers(&layer_shape.layer, layers)
+                .into_iter()
+                .map(move |layer| (layer, shape.clone()))
+        })
+        .collect()
+}
+
+/// Copper of the pads of every placed component.
+pub fn pad_copper(index: &DsnIndex) -> Vec<CopperItem> {
+    let dsn = index.dsn();
+    let mut items = Vec::new();
+    for component in &dsn.placement.components {
+        for instance in &component.instances {
+            let Some(image) = index.image(&instance.reference) else {
+                continue;
+            };
+            let mut pins: Vec<_> = image.pins.values().collect();
+            pins.sort_by(|a, b| (a.pin_id.len(), &a.pin_id).cmp(&(b.pin_id.len(), &b.pin_id)));
+            for pin in pins {
+                let Some(padstack) = dsn.library.pad_stacks.get(&pin.pad_stack_name) else {
+                    continue;
+                };
+                let transform = instance.transform();
+                let place =
+                    |point: Point| transform.apply(point.rotated(pin.rotation) + pin.position);
+                let net = index
+                    .net(&instance.reference, &pin.pin_id)
+                    .map(|net| net.name.clone());
+                for (layer, shape) in padstack_shapes(padstack, &dsn.structure.layers, place) {
+                    items.push(CopperItem {
+                        layer: placed_layer(layer, instance.side, &dsn.structure.layers),
+                        net: net.clone(),
+                        shape,
+                        source: CopperSource::Pad {
+                            reference: instance.reference.clone(),
+                            pin_id: pin.pin_id.clone(),
+                        },
+                    });
+                }
+            }
+        }
+    }
+    items
+}
+
+/// Copper of the wires and vias of `wiring`. Via padstacks are looked up in
+/// the library of `dsn`.
+pub fn wiring_copper(dsn: &DsnStruct, wiring: &Wiring) -> Result<Vec<CopperItem>, DsnError> {
+    let mut items = Vec::new();
+    for (index, wire) in wiring.wires.iter().enumerate() {
+        let segments: Vec<(Point, Point)> = match wire.points.as_slice() {
+            [point] => vec![(*point, *point)],
+            points => points.windows(2).map(|pair| (pair[0], pair[1])).collect(),
+        };
+        for (start, end) in segments {
+            items.push(CopperItem {
+                layer: wire.layer.clone(),
+                net: wire.net.clone(),
+                shape: CopperShape::Segment {
+                    start,
+                    end,
+                    width: wire.width,
+                },
+                source: CopperSource::Wire(index),
+            });
+        }
+    }
+    for (index, via) in wiring.vias.iter().enumerate() {
+        let padstack = dsn
+            .library
+            .pad_stacks
+            .get(&via.padstack_name)
+            .ok_or_else(|| {
+                DsnError::new(
+                    DsnErrorKind::DanglingReference {
+                        kind: "padstack".to_string(),
+                        name: via.padstack_name.clone(),
+                    },
+                    ScopePath::from_segments(vec!["pcb".to_string(), "wiring".to_string()]),
+                )
+            })?;
+        let place = |point| point + via.position;
+        for (layer, shape) in padstack_shapes(padstack, &dsn.structure.layers, place) {
+            items.push(CopperItem {
+                layer: layer.to_string(),
+                net: via.net.clone(),
+                shape,
+                source: CopperSource::Via(index),
+            });
+        }
+    }
+    Ok(items)
+}
+
+/// Copper of the pads and, if the design has been routed, of its wiring.
+pub fn design_copper(index: &DsnIndex) -> Result<Vec<CopperItem>, DsnError> {
+    let mut items = pad_copper(index);
+    if let Some(wiring) = &index.dsn().wiring {
+        items.extend(wiring_copper(index.dsn(), wiring)?);
+    }
+    Ok(items)
+}
+
+/// Pairs of items on the same layer whose boxes come within `margin` of each
+/// other: the candidates for touching or for violating a clearance.
+pub(crate) fn close_pairs(items: &[CopperItem], margin: Length) -> Vec<(usize, usize)> {
+    let bounds: Vec<(Point, Point)> = items.iter().map(|item| item.shape.bounds()).collect();
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by_key(|&i| bounds[i].0.x);
+
+    let mut pairs = Vec::new();
+    for (position, &i) in order.iter().enumerate() {
+        let (min, max) = bounds[i];
+        for &j in &order[position + 1..] {
+            let (other_min, other_max) = bounds[j];
+            if other_min.x > max.x + margin {
+                break;
+            }
+            let overlaps_y = other_min.y <= max.y + margin && min.y <= other_max.y + margin;
+            if overlaps_y && items[i].layer == items[j].layer {
+                pairs.push((i.min(j), i.max(j)));
+            }
+        }
+    }
+    pairs
+}
@@ -0,0 +1,441 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::{
+    dsn_struct::{ComponentInst, DsnStruct, Rule, Side},
+    geometry::Point,
+};
+
+/// One semantic difference between two versions of a design.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    ComponentAdded {
+        reference: String,
+        image: String,
+    },
+    ComponentRemoved {
+        reference: String,
+        image: String,
+    },
+    ComponentMoved {
+        reference: String,
+        from: Point,
+        to: Point,
+    },
+    ComponentRotated {
+        reference: String,
+        from: f64,
+        to: f64,
+    },
+    ComponentFlipped {
+        reference: String,
+        to: Side,
+    },
+    ComponentImageChanged {
+        reference: String,
+        from: String,
+        to: String,
+    },
+    NetAdded {
+        name: String,
+    },
+    NetRemoved {
+        name: String,
+    },
+    /// Pins are written as references such as `U1-5`.
+    NetPinsChanged {
+        name: String,
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    NetClassChanged {
+        net: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    ClassAdded {
+        name: String,
+    },
+    ClassRemoved {
+        name: String,
+    },
+    ClassRuleChanged {
+        name: String,
+        from: Option<Rule>,
+        to: Option<Rule>,
+    },
+    /// A `class_class` is identified by its set of classes, listed sorted.
+    ClassClassAdded {
+        classes: Vec<String>,
+    },
+    ClassClassRemoved {
+        classes: Vec<String>,
+    },
+    ClassClassRuleChanged {
+        classes: Vec<String>,
+        from: Rule,
+        to: Rule,
+    },
+}
+
+fn describe_point(point: Point) -> String {
+    let (x, y) = point.as_mm();
+    format!("({}mm, {}mm)", x, y)
+}
+
+fn describe_side(side: Side) -> &'static str {
+    match side {
+        Side::Front => "front",
+        Side::Back => "back",
+    }
+}
+
+/// Short description of a rule, e.g. `width 0.2mm, clearance 0.2mm`.
+fn describe_rule(rule: Option<&Rule>) -> String {
+    let Some(rule) = rule else {
+        return "no rule".to_string();
+    };
+    let mut parts = Vec::new();
+    if let Some(width) = rule.width {
+        parts.push(format!("width {}", width));
+    }
+    for clearance in &rule.clearances {
+        if clearance.types.is_empty() {
+            parts.push(format!("clearance {}", clearance.value));
+        } else {
+            parts.push(format!(
+                "clearance {} ({})",
+                clearance.value,
+                clearance.types.join(" ")
+            ));
+        }
+    }
+    if parts.is_empty() {
+        "empty rule".to_string()
+    } else {
+        parts.join(", ")
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::ComponentAdded { reference, image } => {
+                write!(f, "component {} added ({})", reference, image)
+            }
+            Change::ComponentRemoved { reference, image } => {
+                write!(f, "component {} removed ({})", reference, image)
+            }
+            Change::ComponentMoved {
+                reference,
+                from,
+                to,
+            } => write!(
+                f,
+                "component {} moved from {} to {}",
+                reference,
+                describe_point(*from),
+                describe_point(*to)
+            ),
+            Change::ComponentRotated {
+                reference,
+                from,
+                to,
+            } => write!(
+                f,
+                "component {} rotated from {}° to {}°",
+                reference, from, to
+            ),
+            Change::ComponentFlipped { reference, to } => {
+                write!(
+                    f,
+                    "component {} flipped to the {} side",
+                    reference,
+                    describe_side(*to)
+                )
+            }
+            Change::ComponentImageChanged {
+                reference,
+                from,
+                to,
+            } => write!(
+                f,
+                "component {} changed image from {} to {}",
+                reference, from, to
+            ),
+            Change::NetAdded { name } => write!(f, "net {} added", name),
+            Change::NetRemoved { name } => write!(f, "net {} removed", name),
+            Change::NetPinsChanged {
+                name,
+                added,
+                removed,
+            } => {
+                write!(f, "net {} pins changed:", name)?;
+                if !added.is_empty() {
+                    write!(f, " +{}", added.join(" +"))?;
+                }
+                if !removed.is_empty() {
+                    write!(f, " -{}", removed.join(" -"))?;
+                }
+                Ok(())
+            }
+            Change::NetClassChanged { net, from, to } => write!(
+                f,
+                "net {} moved from class {} to class {}",
+                net,
+                from.as_deref().unwrap_or("<none>"),
+                to.as_deref().unwrap_or("<none>")
+            ),
+            Change::ClassAdded { name } => write!(f, "class {} added", name),
+            Change::ClassRemoved { name } => write!(f, "class {} removed", name),
+            Change::ClassRuleChanged { name, from, to } => write!(
+                f,
+                "class {} rule changed from {} to {}",
+                name,
+                describe_rule(from.as_ref()),
+                describe_rule(to.as_ref())
+            ),
+            Change::ClassClassAdded { classes } => {
+                write!(f, "class_class {} added", classes.join(" "))
+            }
+            Change::ClassClassRemoved { classes } => {
+                write!(f, "class_class {} removed", classes.join(" "))
+            }
+            Change::ClassClassRuleChanged { classes, from, to } => write!(
+                f,
+                "class_class {} rule changed from {} to {}",
+                classes.join(" "),
+                describe_rule(Some(from)),
+                describe_rule(Some(to))
+            ),
+        }
+    }
+}
+
+/// Whether two rotations in degrees point the same way: 0 and 360 do.
+fn same_angle(a: f64, b: f64) -> bool {
+    let difference = (a - b).rem_euclid(360.0);
+    difference < 1e-9 || 360.0 - difference < 1e-9
+}
+
+/// Placed components by reference, with the image they were placed from.
+fn instances(dsn: &DsnStruct) -> BTreeMap<&str, (&ComponentInst, &str)> {
+    dsn.placement
+        .components
+        .iter()
+        .flat_map(|component| {
+            component.instances.iter().map(move |instance| {
+                (
+                    instance.reference.as_str(),
+                    (instance, component.name.as_str()),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Net class of every net listed in a class.
+fn classes_of_nets(dsn: &DsnStruct) -> BTreeMap<&str, &str> {
+    dsn.network
+        .netclasses
+        .values()
+        .flat_map(|netclass| {
+            netclass
+                .net_names
+                .iter()
+                .map(|net| (net.as_str(), netclass.net_class_name.as_str()))
+        })
+        .collect()
+}
+
+fn diff_components(old: &DsnStruct, new: &DsnStruct, changes: &mut Vec<Change>) {
+    let old_instances = instances(old);
+    let new_instances = instances(new);
+    for (reference, (instance, image)) in &old_instances {
+        if !new_instances.contains_key(reference) {
+            changes.push(Change::ComponentRemoved {
+                reference: reference.to_string(),
+                image: image.to_string(),
+            });
+            continue;
+        }
+        let (new_instance, new_image) = new_instances[reference];
+        if *image != new_image {
+            changes.push(Change::ComponentImageChanged {
+                reference: reference.to_string(),
+                from: image.to_string(),
+                to: new_image.to_string(),
+            });
+        }
+        if instance.position != new_instance.position {
+            changes.push(Change::ComponentMoved {
+                reference: reference.to_string(),
+                from: instance.position,
+                to: new_instance.position,
+            });
+        }
+        if !same_angle(instance.rotation, new_instance.rotation) {
+            changes.push(Change::ComponentRotated {
+                reference: reference.to_string(),
+                from: instance.rotation,
+                to: new_instance.rotation,
+            });
+        }
+        if instance.side != new_instance.side {
+            changes.push(Change::ComponentFlipped {
+                reference: reference.to_string(),
+                to: new_instance.side,
+            });
+        }
+    }
+    for (reference, (_, image)) in &new_instances {
+        if !old_instances.contains_key(reference) {
+            changes.push(Change::ComponentAdded {
+                reference: reference.to_string(),
+                image: image.to_string(),
+            });
+        }
+    }
+}
+
+fn diff_nets(old: &DsnStruct, new: &DsnStruct, changes: &mut Vec<Change>) {
+    let pins = |dsn: &DsnStruct| -> BTreeMap<String, BTreeSet<String>> {
+        dsn.network
+            .nets
+            .iter()
+            .map(|net| {
+                let pins = net
+                    .pins
+                    .iter()
+                    .map(|pin| format!("{}-{}", pin.component_name, pin.pin_id))
+                    .collect();
+                (net.name.clone(), pins)
+            })
+            .collect()
+    };
+    let old_nets = pins(old);
+    let new_nets = pins(new);
+    for (name, old_pins) in &old_nets {
+        let Some(new_pins) = new_nets.get(name) else {
+            changes.push(Change::NetRemoved { name: name.clone() });
+            continue;
+        };
+        let added: Vec<String> = new_pins.difference(old_pins).cloned().collect();
+        let removed: Vec<String> = old_pins.difference(new_pins).cloned().collect();
+        if !added.is_empty() || !removed.is_empty() {
+            changes.push(Change::NetPinsChanged {
+                name: name.clone(),
+                added,
+                removed,
+            });
+        }
+    }
+    for name in new_nets.keys() {
+        if !old_nets.contains_key(name) {
+            changes.push(Change::NetAdded { name: name.clone() });
+        }
+    }
+
+    // class membership of nets present in both versions
+    let old_classes = classes_of_nets(old);
+    let new_classes = classes_of_nets(new);
+    for name in old_nets.keys().filter(|name| new_nets.contains_key(*name)) {
+        let from = old_classes.get(name.as_str()).copied();
+        let to = new_classes.get(name.as_str()).copied();
+        if from != to {
+            changes.push(Change::NetClassChanged {
+                net: name.clone(),
+                from: from.map(str::to_string),
+                to: to.map(str::to_string),
+            });
+        }
+    }
+}
+
+fn diff_classes(old: &DsnStruct, new: &DsnStruct, changes: &mut Vec<Change>) {
+    let old_classes: BTreeSet<&String> = old.network.netclasses.keys().collect();
+    let new_classes: BTreeSet<&String> = new.network.netclasses.keys().collect();
+    for name in &old_classes {
+        let Some(new_class) = new.network.netclasses.get(*name) else {
+            changes.push(Change::ClassRemoved {
+                name: name.to_string(),
+            });
+            continue;
+        };
+        let old_class = &old.network.netclasses[*name];
+        if old_class.rule != new_class.rule {
+            changes.push(Change::ClassRuleChanged {
+                name: name.to_string(),
+                from: old_class.rule.clone(),
+                to: new_class.rule.clone(),
+            });
+        }
+    }
+    for name in new_classes.difference(&old_classes) {
+        changes.push(Change::ClassAdded {
+            name: name.to_string(),
+        });
+    }
+}
+
+fn diff_class_classes(old: &DsnStruct, new: &DsnStruct, changes: &mut Vec<Change>) {
+    let rules = |dsn: &DsnStruct| -> BTreeMap<Vec<String>, Rule> {
+        dsn.network
+            .class_classes
+            .iter()
+            .map(|class_class| {
+                let mut classes = class_class.classes.clone();
+                classes.sort();
+                (classes, class_class.rule.clone())
+            })
+            .collect()
+    };
+    let old_rules = rules(old);
+    let new_rules = rules(new);
+    for (classes, old_rule) in &old_rules {
+        match new_rules.get(classes) {
+            None => changes.push(Change::ClassClassRemoved {
+                classes: classes.clone(),
+            }),
+            Some(new_rule) if new_rule != old_rule => {
+                changes.push(Change::ClassClassRuleChanged {
+                    classes: classes.clone(),
+                    from: old_rule.clone(),
+                    to: new_rule.clone(),
+                });
+            }
+            Some(_) => {}
+        }
+    }
+    for classes in new_rules.keys() {
+        if !old_rules.contains_key(classes) {
+            changes.push(Change::ClassClassAdded {
+                classes: classes.clone(),
+            });
+        }
+    }
+}
+
+/// Lists what changed from `old` to `new`: placed components, net pin lists,
+/// net class membership, class rules and class_class rules. Formatting,
+/// ordering and other textual differences of the files are not reported, nor
+/// are rotations that differ by whole turns.
+pub fn diff_designs(old: &DsnStruct, new: &DsnStruct) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_components(old, new, &mut changes);
+    diff_nets(old, new, &mut changes);
+    diff_classes(old, new, &mut changes);
+    diff_class_classes(old, new, &mut changes);
+    changes
+}
+
+/// Human-readable report with one line per change.
+pub fn diff_report(changes: &[Change]) -> String {
+    if changes.is_empty() {
+        return "no changes\n".to_string();
+    }
+    changes
+        .iter()
+        .map(|change| format!("{}\n", change))
+        .collect()
+}
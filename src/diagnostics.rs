@@ -0,0 +1,78 @@
+use std::fmt;
+
+use crate::s_expr::SExpr;
+
+/// Location of a scope inside a DSN file, e.g. `pcb > library > image "LED_D5.0mm" > pin 2`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScopePath(Vec<String>);
+
+impl ScopePath {
+    pub fn new() -> ScopePath {
+        ScopePath(Vec::new())
+    }
+
+    pub fn from_segments(segments: Vec<String>) -> ScopePath {
+        ScopePath(segments)
+    }
+
+    pub fn segments(&self) -> &[String] {
+        &self.0
+    }
+
+    pub fn push(&mut self, segment: String) {
+        self.0.push(segment);
+    }
+
+    pub fn pop(&mut self) {
+        self.0.pop();
+    }
+
+    pub fn child(&self, segment: String) -> ScopePath {
+        let mut path = self.clone();
+        path.push(segment);
+        path
+    }
+
+    /// Describes a scope by its keyword and, if present, its name: `image "LED_D5.0mm"`.
+    pub fn segment_for(scope: &[SExpr]) -> String {
+        let keyword = scope
+            .first()
+            .and_then(|x| x.as_atom())
+            .map(|s| s.as_str())
+            .unwrap_or("?");
+        match scope.get(1).and_then(|x| x.as_atom()) {
+            Some(name) => ScopePath::segment(keyword, name),
+            None => keyword.to_string(),
+        }
+    }
+
+    /// Segment for a named scope, quoting the name unless it is plain
+    /// alphanumeric. An empty name is quoted too, as `keepout ""`.
+    pub fn segment(keyword: &str, name: &str) -> String {
+        if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric()) {
+            format!("{} {}", keyword, name)
+        } else {
+            format!("{} {:?}", keyword, name)
+        }
+    }
+}
+
+impl fmt::Display for ScopePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.join(" > "))
+    }
+}
+
+/// Something the lenient parser, or [`apply_session`](crate::apply_session::apply_session),
+/// skipped instead of failing on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub location: ScopePath,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "warning: {}: {}", self.location, self.message)
+    }
+}
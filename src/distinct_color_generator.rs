@@ -16,7 +16,7 @@ impl DistinctColorGenerator {
     pub fn new() -> Self {
         Self {
             index: 0,
-            golden_ratio_conjugate: 0.61803398875,
+            golden_ratio_conjugate: 0.618_034,
             saturation: 0.8, // vivid but not pure
             value: 0.75,     // avoid both black and white
         }
@@ -40,6 +40,12 @@ impl DistinctColorGenerator {
     }
 }
 
+impl Default for DistinctColorGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Iterator for DistinctColorGenerator {
     type Item = ColorFloat3;
 
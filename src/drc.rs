@@ -0,0 +1,327 @@
+//! Design rule check of the routed copper against the rules of the design.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{
+    apply_session::apply_session,
+    copper::{
+        CopperItem, CopperShape, CopperSource, close_pairs, covered_layers, design_copper,
+        shape_outline,
+    },
+    dsn_error::DsnError,
+    dsn_index::DsnIndex,
+    dsn_struct::{Boundary, DsnStruct, KeepoutKind, Rule},
+    geometry::Point,
+    rules::{RuleObject, RuleResolver},
+    session_struct::SessionStruct,
+    units::Length,
+};
+
+/// One place where the routed board breaks a rule.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    /// A wire narrower than the width its net requires.
+    TraceWidth {
+        wire: usize,
+        net: String,
+        width: Length,
+        minimum: Length,
+        location: Point,
+    },
+    /// Copper of different nets closer than the clearance between them.
+    Clearance {
+        objects: (CopperSource, CopperSource),
+        layer: String,
+        gap: Length,
+        minimum: Length,
+        location: Point,
+    },
+    /// A via whose centre lies outside the board outline.
+    ViaOutsideBoard { via: usize, location: Point },
+    /// A wire or via inside a keepout that forbids it.
+    CopperInKeepout {
+        object: CopperSource,
+        keepout: Option<String>,
+        layer: String,
+        location: Point,
+    },
+    /// Copper reaching into a cutout of the board.
+    CopperInCutout {
+        object: CopperSource,
+        layer: String,
+        location: Point,
+    },
+}
+
+fn describe_point(point: Point) -> String {
+    format!("({}, {})", point.x, point.y)
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::TraceWidth {
+                wire,
+                net,
+                width,
+                minimum,
+                location,
+            } => write!(
+                f,
+                "wire #{} of net {} at {} is {} wide, minimum {}",
+                wire,
+                net,
+                describe_point(*location),
+                width,
+                minimum
+            ),
+            Violation::Clearance {
+                objects,
+                layer,
+                gap,
+                minimum,
+                location,
+            } => write!(
+                f,
+                "{} and {} on {} at {} are {} apart, minimum {}",
+                objects.0,
+                objects.1,
+                layer,
+                describe_point(*location),
+                gap,
+                minimum
+            ),
+            Violation::ViaOutsideBoard { via, location } => write!(
+                f,
+                "via #{} at {} is outside the board",
+                via,
+                describe_point(*location)
+            ),
+            Violation::CopperInKeepout {
+                object,
+                keepout,
+                layer,
+                location,
+            } => write!(
+                f,
+                "{} on {} at {} is inside keepout {}",
+                object,
+                layer,
+                describe_point(*location),
+                keepout.as_deref().unwrap_or("<unnamed>")
+            ),
+            Violation::CopperInCutout {
+                object,
+                layer,
+                location,
+            } => write!(
+                f,
+                "{} on {} at {} reaches into a board cutout",
+                object,
+                layer,
+                describe_point(*location)
+            ),
+        }
+    }
+}
+
+/// Largest clearance any rule of the design asks for: copper further apart
+/// than this cannot violate a clearance.
+fn max_clearance(dsn: &DsnStruct) -> Length {
+    let network = &dsn.network;
+    let rules = dsn
+        .structure
+        .rule
+        .iter()
+        .chain(
+            network
+                .netclasses
+                .values()
+                .filter_map(|class| class.rule.as_ref()),
+        )
+        .chain(
+            network
+                .class_classes
+                .iter()
+                .map(|class_class| &class_class.rule),
+        )
+        .chain(network.nets.iter().filter_map(|net| net.rule.as_ref()));
+    rules
+        .flat_map(|rule: &Rule| rule.clearances.iter().map(|clearance| clearance.value))
+        .max()
+        .unwrap_or(Length::ZERO)
+}
+
+fn rule_object<'a>(item: &'a CopperItem, rules: &RuleResolver<'a>) -> RuleObject<'a> {
+    match &item.source {
+        CopperSource::Pad { reference, pin_id } => rules.pin_object(reference, pin_id),
+        CopperSource::Wire(_) | CopperSource::Via(_) => RuleObject {
+            net: item.net.as_deref(),
+            clearance_class: None,
+        },
+    }
+}
+
+fn check_widths(dsn: &DsnStruct, rules: &RuleResolver, violations: &mut Vec<Violation>) {
+    let Some(wiring) = &dsn.wiring else {
+        return;
+    };
+    for (index, wire) in wiring.wires.iter().enumerate() {
+        let Some(net) = &wire.net else {
+            continue;
+        };
+        let minimum = rules.width(Some(net));
+        if wire.width < minimum {
+            violations.push(Violation::TraceWidth {
+                wire: index,
+                net: net.clone(),
+                width: wire.width,
+                minimum,
+                location: wire.points.first().copied().unwrap_or(Point::ORIGIN),
+            });
+        }
+    }
+}
+
+/// Pad-to-pad spacing is set by the footprints and their placement, not by
+/// the routing, so only pairs with a wire or via are checked.
+fn check_clearances(
+    dsn: &DsnStruct,
+    items: &[CopperItem],
+    rules: &RuleResolver,
+    violations: &mut Vec<Violation>,
+) {
+    let mut worst: HashMap<(&CopperSource, &CopperSource, &str), Violation> = HashMap::new();
+    let mut order = Vec::new();
+    for (a, b) in close_pairs(items, max_clearance(dsn)) {
+        let (item, other) = (&items[a], &items[b]);
+        let both_pads = matches!(item.source, CopperSource::Pad { .. })
+            && matches!(other.source, CopperSource::Pad { .. });
+        if both_pads || item.source == other.source {
+            continue;
+        }
+        if item.net.is_some() && item.net == other.net {
+            continue;
+        }
+        let minimum = rules.clearance(rule_object(item, rules), rule_object(other, rules));
+        let (gap, location) = item.shape.gap(&other.shape);
+        if gap >= minimum {
+            continue;
+        }
+        let key = (&item.source, &other.source, item.layer.as_str());
+        let violation = Violation::Clearance {
+            objects: (item.source.clone(), other.source.clone()),
+            layer: item.layer.clone(),
+            gap,
+            minimum,
+            location,
+        };
+        match worst.get(&key) {
+            None => {
+                order.push(key);
+                worst.insert(key, violation);
+            }
+            Some(Violation::Clearance { gap: worst_gap, .. }) if gap < *worst_gap => {
+                worst.insert(key, violation);
+            }
+            Some(_) => {}
+        }
+    }
+    violations.extend(order.iter().filter_map(|key| worst.remove(key)));
+}
+
+fn check_board(dsn: &DsnStruct, items: &[CopperItem], violations: &mut Vec<Violation>) {
+    let outline = |boundary: &Boundary| CopperShape::Polygon {
+        vertices: boundary.points.clone(),
+        aperture_width: Length::ZERO,
+    };
+    // a `signal` boundary only limits the routing area
+    let mut boundaries = dsn
+        .structure
+        .boundaries
+        .iter()
+        .filter(|boundary| boundary.layer == "pcb");
+    if let (Some(board), Some(wiring)) = (boundaries.next(), &dsn.wiring) {
+        let board = outline(board);
+        for (index, via) in wiring.vias.iter().enumerate() {
+            if !board.contains(via.position) {
+                violations.push(Violation::ViaOutsideBoard {
+                    via: index,
+                    location: via.position,
+                });
+            }
+        }
+    }
+
+    // every `pcb` boundary after the outline cuts a hole into the board
+    let cutouts: Vec<CopperShape> = boundaries.map(outline).collect();
+    for item in items {
+        for cutout in &cutouts {
+            let (gap, location) = item.shape.gap(cutout);
+            if gap == Length::ZERO {
+                violations.push(Violation::CopperInCutout {
+                    object: item.source.clone(),
+                    layer: item.layer.clone(),
+                    location,
+                });
+                break;
+            }
+        }
+    }
+}
+
+fn check_keepouts(dsn: &DsnStruct, items: &[CopperItem], violations: &mut Vec<Violation>) {
+    for keepout in &dsn.structure.keepouts {
+        let shape = shape_outline(&keepout.shape.shape, |point| point);
+        for item in items {
+            let forbidden = match (&keepout.kind, &item.source) {
+                (_, CopperSource::Pad { .. }) => false,
+                (KeepoutKind::Keepout, _) => true,
+                (KeepoutKind::ViaKeepout, source) => matches!(source, CopperSource::Via(_)),
+                (KeepoutKind::WireKeepout, source) => matches!(source, CopperSource::Wire(_)),
+            };
+            let on_layer = covered_layers(&keepout.shape.layer, &dsn.structure.layers)
+                .contains(&item.layer.as_str());
+            if !forbidden || !on_layer {
+                continue;
+            }
+            let (gap, location) = item.shape.gap(&shape);
+            if gap == Length::ZERO {
+                violations.push(Violation::CopperInKeepout {
+                    object: item.source.clone(),
+                    keepout: keepout.name.clone(),
+                    layer: item.layer.clone(),
+                    location,
+                });
+            }
+        }
+    }
+}
+
+/// Checks the wiring of the design against its rules: wire widths against
+/// the width of their net, spacing between copper of different nets against
+/// the clearance the rules ask for, vias outside the board outline, wires
+/// and vias inside keepouts, and copper reaching into board cutouts.
+pub fn check_design(dsn: &DsnStruct) -> Result<Vec<Violation>, Vec<DsnError>> {
+    let index = DsnIndex::new(dsn)?;
+    let rules = RuleResolver::new(&index);
+    let items = design_copper(&index).map_err(|error| vec![error])?;
+
+    let mut violations = Vec::new();
+    check_widths(dsn, &rules, &mut violations);
+    check_clearances(dsn, &items, &rules, &mut violations);
+    check_board(dsn, &items, &mut violations);
+    check_keepouts(dsn, &items, &mut violations);
+    Ok(violations)
+}
+
+/// [`check_design`] of the design once `session` has been applied.
+pub fn check_session(
+    dsn: &DsnStruct,
+    session: &SessionStruct,
+) -> Result<Vec<Violation>, Vec<DsnError>> {
+    let mut routed = dsn.clone();
+    apply_session(&mut routed, session).map_err(|error| vec![error])?;
+    check_design(&routed)
+}
@@ -0,0 +1,81 @@
+use std::fmt;
+
+use crate::diagnostics::ScopePath;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DsnErrorKind {
+    /// A required item or scope is absent.
+    MissingField { field: String },
+    /// A scope starts with a different keyword than the one expected.
+    UnexpectedKeyword { expected: String, found: String },
+    /// A keyword the parser does not know. Strict mode fails on it only
+    /// for pad shapes; lenient mode reports it as a warning.
+    UnknownKeyword { keyword: String },
+    /// An atom was found where a list was expected, or the other way round.
+    UnexpectedExpr { expected: String, found: String },
+    /// An atom that should hold a number does not parse as one.
+    InvalidNumber { field: String, value: String },
+    /// An atom holds a value outside of the allowed set.
+    InvalidValue { field: String, value: String },
+    /// A name refers to something that is not defined in the design.
+    DanglingReference { kind: String, name: String },
+    /// A name that has to be unique is already taken.
+    DuplicateName { kind: String, name: String },
+    /// A pin can belong to one net only.
+    AlreadyConnected { pin: String, net: String },
+}
+
+impl fmt::Display for DsnErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DsnErrorKind::MissingField { field } => write!(f, "missing {}", field),
+            DsnErrorKind::UnexpectedKeyword { expected, found } => {
+                write!(f, "expected '{}', found '{}'", expected, found)
+            }
+            DsnErrorKind::UnknownKeyword { keyword } => write!(f, "unknown keyword '{}'", keyword),
+            DsnErrorKind::UnexpectedExpr { expected, found } => {
+                write!(f, "expected {}, found {}", expected, found)
+            }
+            DsnErrorKind::InvalidNumber { field, value } => {
+                write!(f, "invalid number for {}: '{}'", field, value)
+            }
+            DsnErrorKind::InvalidValue { field, value } => {
+                write!(f, "invalid value for {}: '{}'", field, value)
+            }
+            DsnErrorKind::DanglingReference { kind, name } => {
+                write!(f, "reference to undefined {} '{}'", kind, name)
+            }
+            DsnErrorKind::DuplicateName { kind, name } => {
+                write!(f, "{} '{}' is already defined", kind, name)
+            }
+            DsnErrorKind::AlreadyConnected { pin, net } => {
+                write!(f, "pin '{}' is already on net '{}'", pin, net)
+            }
+        }
+    }
+}
+
+/// An error together with the scope it occurred in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DsnError {
+    pub kind: DsnErrorKind,
+    pub context: ScopePath,
+}
+
+impl DsnError {
+    pub fn new(kind: DsnErrorKind, context: ScopePath) -> DsnError {
+        DsnError { kind, context }
+    }
+}
+
+impl fmt::Display for DsnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.context.segments().is_empty() {
+            write!(f, "{}", self.kind)
+        } else {
+            write!(f, "{}: {}", self.context, self.kind)
+        }
+    }
+}
+
+impl std::error::Error for DsnError {}
@@ -0,0 +1,237 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    diagnostics::{Diagnostic, ScopePath},
+    dsn_error::{DsnError, DsnErrorKind},
+    dsn_struct::{ComponentInst, DsnStruct, Image, Net, Netclass, PadStack, Pin},
+    via::Via,
+};
+
+/// Lookup tables over a [`DsnStruct`], built once so that resolving a pin,
+/// its net or its net class does not scan the whole design.
+///
+/// Building the index checks every reference between scopes; a design with
+/// dangling references is rejected with one error per reference. Problems
+/// the index can work around are kept as [`warnings`](DsnIndex::warnings).
+pub struct DsnIndex<'a> {
+    dsn: &'a DsnStruct,
+    instances: HashMap<&'a str, (&'a ComponentInst, &'a Image)>,
+    pin_nets: HashMap<&'a str, HashMap<&'a str, &'a Net>>,
+    nets: HashMap<&'a str, &'a Net>,
+    net_classes: HashMap<&'a str, &'a Netclass>,
+    vias: HashMap<&'a str, Via>,
+    warnings: Vec<Diagnostic>,
+}
+
+fn dangling(kind: &str, name: String, path: Vec<String>) -> DsnError {
+    DsnError::new(
+        DsnErrorKind::DanglingReference {
+            kind: kind.to_string(),
+            name,
+        },
+        ScopePath::from_segments(path),
+    )
+}
+
+impl<'a> DsnIndex<'a> {
+    pub fn new(dsn: &'a DsnStruct) -> Result<DsnIndex<'a>, Vec<DsnError>> {
+        let mut errors = Vec::new();
+        let library = &dsn.library;
+
+        for image in library.images.values() {
+            for pin in image.pins.values() {
+                if !library.pad_stacks.contains_key(&pin.pad_stack_name) {
+                    errors.push(dangling(
+                        "padstack",
+                        pin.pad_stack_name.clone(),
+                        vec![
+                            "pcb".to_string(),
+                            "library".to_string(),
+                            ScopePath::segment("image", &image.name),
+                            format!("pin {}", pin.pin_id),
+                        ],
+                    ));
+                }
+            }
+        }
+
+        let mut instances = HashMap::new();
+        for component in &dsn.placement.components {
+            let Some(image) = library.images.get(&component.name) else {
+                errors.push(dangling(
+                    "image",
+                    component.name.clone(),
+                    vec![
+                        "pcb".to_string(),
+                        "placement".to_string(),
+                        ScopePath::segment("component", &component.name),
+                    ],
+                ));
+                continue;
+            };
+            for instance in &component.instances {
+                instances.insert(instance.reference.as_str(), (instance, image));
+            }
+        }
+
+        let mut pin_nets: HashMap<&str, HashMap<&str, &Net>> = HashMap::new();
+        for net in &dsn.network.nets {
+            let path = || {
+                vec![
+                    "pcb".to_string(),
+                    "network".to_string(),
+                    ScopePath::segment("net", &net.name),
+                ]
+            };
+            for pin in &net.pins {
+                match instances.get(pin.component_name.as_str()) {
+                    None => {
+                        errors.push(dangling("component", pin.component_name.clone(), path()));
+                        continue;
+                    }
+                    Some((_, image)) if !image.pins.contains_key(&pin.pin_id) => {
+                        let name = format!("{}-{}", pin.component_name, pin.pin_id);
+                        errors.push(dangling("pin", name, path()));
+                        continue;
+                    }
+                    Some(_) => {}
+                }
+                pin_nets
+                    .entry(pin.component_name.as_str())
+                    .or_default()
+                    .insert(pin.pin_id.as_str(), net);
+            }
+        }
+
+        let nets: HashMap<&str, &Net> = dsn
+            .network
+            .nets
+            .iter()
+            .map(|net| (net.name.as_str(), net))
+            .collect();
+        let mut net_classes = HashMap::new();
+        for netclass in dsn.network.netclasses.values() {
+            let path = || {
+                vec![
+                    "pcb".to_string(),
+                    "network".to_string(),
+                    ScopePath::segment("class", &netclass.net_class_name),
+                ]
+            };
+            for net_name in &netclass.net_names {
+                if !nets.contains_key(net_name.as_str()) {
+                    errors.push(dangling("net", net_name.clone(), path()));
+                    continue;
+                }
+                net_classes.insert(net_name.as_str(), netclass);
+            }
+        }
+
+        // vias offered in the structure and used by the net classes
+        let structure_vias = dsn.structure.vias.iter().map(|via_name| {
+            let path = vec![
+                "pcb".to_string(),
+                "structure".to_string(),
+                "via".to_string(),
+            ];
+            (via_name, path)
+        });
+        let class_vias = dsn
+            .network
+            .netclasses
+            .values()
+            .filter(|netclass| !netclass.via_name.is_empty())
+            .map(|netclass| {
+                let path = vec![
+                    "pcb".to_string(),
+                    "network".to_string(),
+                    ScopePath::segment("class", &netclass.net_class_name),
+                ];
+                (&netclass.via_name, path)
+            });
+        let mut vias: HashMap<&str, Via> = HashMap::new();
+        let mut warnings = Vec::new();
+        let mut invalid_vias: HashSet<&str> = HashSet::new();
+        for (via_name, path) in structure_vias.chain(class_vias) {
+            if vias.contains_key(via_name.as_str()) || invalid_vias.contains(via_name.as_str()) {
+                continue;
+            }
+            let Some(padstack) = library.pad_stacks.get(via_name) else {
+                errors.push(dangling("padstack", via_name.clone(), path));
+                invalid_vias.insert(via_name);
+                continue;
+            };
+            match Via::from_padstack(padstack, &dsn.structure.layers, &mut warnings) {
+                Ok(via) => {
+                    vias.insert(via_name, via);
+                }
+                Err(error) => {
+                    errors.push(error);
+                    invalid_vias.insert(via_name);
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(DsnIndex {
+            dsn,
+            instances,
+            pin_nets,
+            nets,
+            net_classes,
+            vias,
+            warnings,
+        })
+    }
+
+    pub fn dsn(&self) -> &'a DsnStruct {
+        self.dsn
+    }
+
+    /// Component instance placed with the given reference, e.g. `U1`.
+    pub fn instance(&self, reference: &str) -> Option<&'a ComponentInst> {
+        self.instances.get(reference).map(|(instance, _)| *instance)
+    }
+
+    /// Library image the instance with the given reference was placed from.
+    pub fn image(&self, reference: &str) -> Option<&'a Image> {
+        self.instances.get(reference).map(|(_, image)| *image)
+    }
+
+    pub fn pin(&self, reference: &str, pin_id: &str) -> Option<&'a Pin> {
+        self.image(reference)?.pins.get(pin_id)
+    }
+
+    /// Padstack used by a pin of a placed component.
+    pub fn padstack(&self, reference: &str, pin_id: &str) -> Option<&'a PadStack> {
+        let pin = self.pin(reference, pin_id)?;
+        self.dsn.library.pad_stacks.get(&pin.pad_stack_name)
+    }
+
+    /// Net the pin is connected to, if any.
+    pub fn net(&self, reference: &str, pin_id: &str) -> Option<&'a Net> {
+        self.pin_nets.get(reference)?.get(pin_id).copied()
+    }
+
+    pub fn net_by_name(&self, net_name: &str) -> Option<&'a Net> {
+        self.nets.get(net_name).copied()
+    }
+
+    /// Net class the net belongs to, if any.
+    pub fn net_class(&self, net_name: &str) -> Option<&'a Netclass> {
+        self.net_classes.get(net_name).copied()
+    }
+
+    /// What building the index found odd but could work around, such as a
+    /// via name that disagrees with its padstack.
+    pub fn warnings(&self) -> &[Diagnostic] {
+        &self.warnings
+    }
+
+    /// Via resolved from a padstack listed in the structure or used by a net class.
+    pub fn via(&self, name: &str) -> Option<&Via> {
+        self.vias.get(name)
+    }
+}
@@ -1,101 +1,386 @@
 use std::collections::HashMap;
 
+use crate::{
+    geometry::{Point, Transform},
+    s_expr::SExpr,
+    units::{Length, Unit},
+};
 
+/// A scope the parser does not model, kept verbatim so writing the design
+/// back out does not drop it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownScope{
+    /// Index of the scope among the items of its parent scope.
+    pub position: usize,
+    pub expr: SExpr,
+}
+
+/// The `(parser ...)` scope: which tool wrote the file and how it quotes strings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParserHeader{
+    pub string_quote: Option<char>,
+    pub space_in_quoted_tokens: bool,
+    pub host_cad: Option<String>,
+    pub host_version: Option<String>,
+    pub unknown: Vec<UnknownScope>,
+}
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Resolution{
-    pub unit: String,
+    pub unit: Unit,
     pub value: f64,
 }
 
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Layer{
     pub name: String,
+    pub unknown: Vec<UnknownScope>,
+}
+
+/// A `(boundary (path <layer> <width> x y ...))`. The `pcb` boundary is the
+/// board outline; one on `signal` limits where the router may place copper.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Boundary{
+    pub layer: String,
+    pub width: Length,
+    pub points: Vec<Point>,
+}
+
+/// One `(clearance <value> [(type <type> ...)])` item of a rule. Untyped
+/// clearances apply between any two objects, typed ones only between the
+/// named object or clearance class pairs, e.g. `smd_smd`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clearance{
+    pub value: Length,
+    pub types: Vec<String>,
+}
+
+/// A `(rule ...)` scope.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule{
+    pub width: Option<Length>,
+    pub clearances: Vec<Clearance>,
+    pub unknown: Vec<UnknownScope>,
+}
+
+impl Rule{
+    /// The untyped clearance of the rule, if it has one.
+    pub fn default_clearance(&self) -> Option<Length> {
+        self.clearances.iter().find(|clearance| clearance.types.is_empty()).map(|clearance| clearance.value)
+    }
+
+    /// The clearance typed with `clearance_type`, if the rule has one.
+    pub fn typed_clearance(&self, clearance_type: &str) -> Option<Length> {
+        self.clearances
+            .iter()
+            .find(|clearance| clearance.types.iter().any(|t| t == clearance_type))
+            .map(|clearance| clearance.value)
+    }
+}
+
+/// The `(control ...)` scope of the structure. Switches absent from the file stay `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Control{
+    pub off_grid: Option<bool>,
+    pub via_at_smd: Option<bool>,
+    pub unknown: Vec<UnknownScope>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GridKind{
+    Via,
+    Wire,
+    ViaKeepout,
+    Place,
+    Snap,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GridDirection{
+    X,
+    Y,
+}
+
+/// A `(grid <kind> <step> ...)` scope.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid{
+    pub kind: GridKind,
+    pub step: Length,
+    pub direction: Option<GridDirection>,
+    pub offset: Option<Length>,
+    pub unknown: Vec<UnknownScope>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreferredDirection{
+    Horizontal,
+    Vertical,
+}
+
+/// Per-layer part of the autoroute settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerRule{
+    pub layer: String,
+    pub active: Option<bool>,
+    pub preferred_direction: Option<PreferredDirection>,
+    pub preferred_direction_trace_costs: Option<f64>,
+    pub against_preferred_direction_trace_costs: Option<f64>,
+    pub unknown: Vec<UnknownScope>,
+}
+
+/// The `(autoroute_settings ...)` scope written by Freerouting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutorouteSettings{
+    pub fanout: Option<bool>,
+    pub autoroute: Option<bool>,
+    pub postroute: Option<bool>,
+    pub vias: Option<bool>,
+    pub via_costs: Option<f64>,
+    pub plane_via_costs: Option<f64>,
+    pub start_ripup_costs: Option<f64>,
+    pub start_pass_no: Option<u32>,
+    pub layer_rules: Vec<LayerRule>,
+    pub unknown: Vec<UnknownScope>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeepoutKind{
+    /// Keeps out wires and vias.
+    Keepout,
+    ViaKeepout,
+    WireKeepout,
 }
 
-pub struct Boundary(pub Vec<(f64, f64)>);
+/// A keepout area of the structure, e.g. `(keepout "" (polygon F.Cu 0 ...))`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keepout{
+    pub kind: KeepoutKind,
+    pub name: Option<String>,
+    pub shape: LayerShape,
+    pub unknown: Vec<UnknownScope>,
+}
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Structure{
     pub layers: Vec<Layer>,
-    pub boundary: Boundary,
+    /// Every `(boundary ...)` scope in file order. The first is the board
+    /// outline; never empty.
+    pub boundaries: Vec<Boundary>,
+    pub control: Option<Control>,
+    pub grids: Vec<Grid>,
+    pub autoroute_settings: Option<AutorouteSettings>,
+    /// Padstack names listed in `(via ...)`, the vias the router may use.
+    pub vias: Vec<String>,
+    pub keepouts: Vec<Keepout>,
+    /// Board-wide rule, the lowest level of the rule hierarchy.
+    pub rule: Option<Rule>,
+    pub unknown: Vec<UnknownScope>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side{
+    Front,
+    Back,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct ComponentInst{
     pub reference: String,
-    pub position: (f64, f64),
+    pub position: Point,
+    pub side: Side,
     pub rotation: f64,
+    pub unknown: Vec<UnknownScope>,
 }
+
+impl ComponentInst{
+    /// Transform from image coordinates to board coordinates.
+    pub fn transform(&self) -> Transform {
+        Transform {
+            offset: self.position,
+            rotation: self.rotation,
+            mirrored: self.side == Side::Back,
+        }
+    }
+}
+#[derive(Debug, Clone, PartialEq)]
 pub struct Component{
     pub name: String,
     pub instances: Vec<ComponentInst>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Placement{
     pub components: Vec<Component>,
+    pub unknown: Vec<UnknownScope>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Pin{
     pub pad_stack_name: String,
-    pub pin_number: usize,
-    pub position: (f64, f64),
+    /// Pin id, a number for most parts but e.g. `A1` for a BGA.
+    pub pin_id: String,
+    /// Rotation of the pad relative to the image, in degrees.
+    pub rotation: f64,
+    pub position: Point,
+    /// Overrides the clearance class of the image for this pin.
+    pub clearance_class: Option<String>,
+    pub unknown: Vec<UnknownScope>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Image{
     pub name: String,
-    pub pins: HashMap<usize, Pin>,
+    pub pins: HashMap<String, Pin>,
+    pub clearance_class: Option<String>,
+    pub unknown: Vec<UnknownScope>,
 }
+#[derive(Debug, Clone, PartialEq)]
 pub enum Shape{
     Circle{
-        diameter: f64,
+        diameter: Length,
+        /// Offset of the centre from the origin of the pad or image.
+        center: Point,
     },
     Rect{
-        x_min: f64,
-        y_min: f64,
-        x_max: f64,
-        y_max: f64,
+        x_min: Length,
+        y_min: Length,
+        x_max: Length,
+        y_max: Length,
     },
     Polygon{
-        aperture_width: f64,
-        vertices: Vec<(f64, f64)>,
+        aperture_width: Length,
+        vertices: Vec<Point>,
     }
 }
+/// A shape on one layer, as used by padstacks and keepouts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerShape{
+    pub layer: String,
+    pub shape: Shape,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct PadStack{
     pub name: String,
-    pub shape: Shape,
-    pub through_hole: bool,
+    /// One entry per `(shape ...)` scope, in file order. Shapes the model
+    /// does not cover, such as the `path` of an oval pad, are kept in
+    /// `unknown` instead, so this is empty for a padstack made only of those.
+    pub shapes: Vec<LayerShape>,
+    pub attach: Option<bool>,
+    pub unknown: Vec<UnknownScope>,
 }
 
+impl PadStack{
+    /// Shape on the first layer, for callers that need a single pad outline.
+    pub fn shape(&self) -> Option<&Shape> {
+        self.shapes.first().map(|layer_shape| &layer_shape.shape)
+    }
+
+    /// A padstack with copper on more than one layer needs a drilled hole.
+    pub fn through_hole(&self) -> bool {
+        self.shapes.len() > 1
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Library{
     pub images: HashMap<String, Image>,
     pub pad_stacks: HashMap<String, PadStack>,
+    pub unknown: Vec<UnknownScope>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Netclass{
     pub net_class_name: String,
     pub net_names: Vec<String>,
     pub via_name: String,
-    pub width: f64,
-    pub clearance: f64,
+    /// Items of `(circuit ...)` other than `use_via`, e.g. `use_layer`.
+    pub circuit_unknown: Vec<UnknownScope>,
+    pub rule: Option<Rule>,
+    pub unknown: Vec<UnknownScope>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Pin2{
     pub component_name: String,
-    pub pin_number: usize,
+    pub pin_id: String,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Net{
     pub name: String,
     pub pins: Vec<Pin2>,
+    pub rule: Option<Rule>,
+    pub unknown: Vec<UnknownScope>,
+}
+
+/// A `(class_class (classes A B ...) (rule ...))` scope: the rule applies
+/// between two objects whose net classes are both in the list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassClass{
+    pub classes: Vec<String>,
+    pub rule: Rule,
+    pub unknown: Vec<UnknownScope>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Network{
     pub nets: Vec<Net>,
     pub netclasses: HashMap<String, Netclass>,
+    pub class_classes: Vec<ClassClass>,
+    pub unknown: Vec<UnknownScope>,
+}
+
+/// A routed wire, a `(wire (path <layer> <width> x y ...) ...)` scope.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wire{
+    pub layer: String,
+    pub width: Length,
+    pub points: Vec<Point>,
+    pub net: Option<String>,
+    pub unknown: Vec<UnknownScope>,
+}
+
+/// A `(via <padstack> x y ...)` placed in the wiring.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WiringVia{
+    pub padstack_name: String,
+    pub position: Point,
+    pub net: Option<String>,
+    pub unknown: Vec<UnknownScope>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wiring{
+    pub wires: Vec<Wire>,
+    pub vias: Vec<WiringVia>,
+    pub unknown: Vec<UnknownScope>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct DsnStruct{
+    /// Design identifier following `pcb`, usually the path the host exported to.
+    pub design: String,
+    pub parser: Option<ParserHeader>,
     pub resolution: Resolution,
+    /// Unit the coordinates of the file were written in.
+    pub unit: Unit,
     pub structure: Structure,
     pub placement: Placement,
     pub library: Library,
     pub network: Network,
-}
\ No newline at end of file
+    pub wiring: Option<Wiring>,
+    pub unknown: Vec<UnknownScope>,
+}
+
+impl DsnStruct{
+    /// File name of the design without directories and extension.
+    pub fn design_name(&self) -> &str {
+        let file_name = self.design.rsplit(['/', '\\']).next().unwrap_or(&self.design);
+        match file_name.rsplit_once('.') {
+            Some((stem, _)) if !stem.is_empty() => stem,
+            _ => file_name,
+        }
+    }
+}
@@ -0,0 +1,240 @@
+//! Engineering change orders on a parsed design.
+//!
+//! Every mutation checks the references it touches before changing anything,
+//! so a rejected edit leaves the design as it was.
+
+use crate::{
+    diagnostics::ScopePath,
+    dsn_error::{DsnError, DsnErrorKind},
+    dsn_struct::{Component, ComponentInst, DsnStruct, Net, Pin2, Side},
+    geometry::Point,
+};
+
+fn edit_error(kind: DsnErrorKind, segments: &[&str]) -> DsnError {
+    let mut path = vec!["pcb".to_string()];
+    path.extend(segments.iter().map(|segment| segment.to_string()));
+    DsnError::new(kind, ScopePath::from_segments(path))
+}
+
+fn dangling(kind: &str, name: &str, segments: &[&str]) -> DsnError {
+    edit_error(
+        DsnErrorKind::DanglingReference {
+            kind: kind.to_string(),
+            name: name.to_string(),
+        },
+        segments,
+    )
+}
+
+fn duplicate(kind: &str, name: &str, segments: &[&str]) -> DsnError {
+    edit_error(
+        DsnErrorKind::DuplicateName {
+            kind: kind.to_string(),
+            name: name.to_string(),
+        },
+        segments,
+    )
+}
+
+impl DsnStruct {
+    fn find_instance(&self, reference: &str) -> Option<(usize, usize)> {
+        self.placement
+            .components
+            .iter()
+            .enumerate()
+            .find_map(|(component_index, component)| {
+                component
+                    .instances
+                    .iter()
+                    .position(|instance| instance.reference == reference)
+                    .map(|instance_index| (component_index, instance_index))
+            })
+    }
+
+    fn instance_mut(&mut self, reference: &str) -> Result<&mut ComponentInst, DsnError> {
+        let (component_index, instance_index) = self
+            .find_instance(reference)
+            .ok_or_else(|| dangling("component", reference, &["placement"]))?;
+        Ok(&mut self.placement.components[component_index].instances[instance_index])
+    }
+
+    /// Net the pin is on, if any.
+    fn net_of_pin(&self, pin: &Pin2) -> Option<&Net> {
+        self.network.nets.iter().find(|net| net.pins.contains(pin))
+    }
+
+    /// Moves the component placed as `reference` to `position`.
+    pub fn move_component(&mut self, reference: &str, position: Point) -> Result<(), DsnError> {
+        self.instance_mut(reference)?.position = position;
+        Ok(())
+    }
+
+    /// Sets the rotation of the component, in degrees counterclockwise.
+    pub fn rotate_component(&mut self, reference: &str, rotation: f64) -> Result<(), DsnError> {
+        if !rotation.is_finite() {
+            return Err(edit_error(
+                DsnErrorKind::InvalidValue {
+                    field: "rotation".to_string(),
+                    value: rotation.to_string(),
+                },
+                &["placement"],
+            ));
+        }
+        self.instance_mut(reference)?.rotation = rotation.rem_euclid(360.0);
+        Ok(())
+    }
+
+    /// Moves the component to the other side of the board.
+    pub fn flip_component(&mut self, reference: &str) -> Result<(), DsnError> {
+        let instance = self.instance_mut(reference)?;
+        instance.side = match instance.side {
+            Side::Front => Side::Back,
+            Side::Back => Side::Front,
+        };
+        Ok(())
+    }
+
+    /// Places the component with another library image. Every pin the
+    /// component has on a net must exist in the new image.
+    pub fn swap_image(&mut self, reference: &str, image_name: &str) -> Result<(), DsnError> {
+        let (component_index, instance_index) = self
+            .find_instance(reference)
+            .ok_or_else(|| dangling("component", reference, &["placement"]))?;
+        let image = self
+            .library
+            .images
+            .get(image_name)
+            .ok_or_else(|| dangling("image", image_name, &["library"]))?;
+        for net in &self.network.nets {
+            for pin in &net.pins {
+                if pin.component_name == reference && !image.pins.contains_key(&pin.pin_id) {
+                    let segment = ScopePath::segment("net", &net.name);
+                    let name = format!("{}-{}", reference, pin.pin_id);
+                    return Err(dangling("pin", &name, &["network", &segment]));
+                }
+            }
+        }
+
+        let components = &mut self.placement.components;
+        if components[component_index].name == image_name {
+            return Ok(());
+        }
+        let instance = components[component_index].instances.remove(instance_index);
+        if components[component_index].instances.is_empty() {
+            components.remove(component_index);
+        }
+        match components
+            .iter_mut()
+            .find(|component| component.name == image_name)
+        {
+            Some(component) => component.instances.push(instance),
+            None => components.push(Component {
+                name: image_name.to_string(),
+                instances: vec![instance],
+            }),
+        }
+        Ok(())
+    }
+
+    /// Renames a placed component and every pin reference to it.
+    pub fn rename_reference(&mut self, old: &str, new: &str) -> Result<(), DsnError> {
+        if new.is_empty() || new.contains('-') {
+            return Err(edit_error(
+                DsnErrorKind::InvalidValue {
+                    field: "component reference".to_string(),
+                    value: new.to_string(),
+                },
+                &["placement"],
+            ));
+        }
+        if old != new && self.find_instance(new).is_some() {
+            return Err(duplicate("component", new, &["placement"]));
+        }
+        self.instance_mut(old)?.reference = new.to_string();
+        for net in &mut self.network.nets {
+            for pin in &mut net.pins {
+                if pin.component_name == old {
+                    pin.component_name = new.to_string();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves `net_name` out of its current net class into `class_name`.
+    pub fn move_net_to_class(&mut self, net_name: &str, class_name: &str) -> Result<(), DsnError> {
+        if !self.network.nets.iter().any(|net| net.name == net_name) {
+            return Err(dangling("net", net_name, &["network"]));
+        }
+        if !self.network.netclasses.contains_key(class_name) {
+            return Err(dangling("class", class_name, &["network"]));
+        }
+        for netclass in self.network.netclasses.values_mut() {
+            netclass.net_names.retain(|name| name != net_name);
+        }
+        if let Some(netclass) = self.network.netclasses.get_mut(class_name) {
+            netclass.net_names.push(net_name.to_string());
+        }
+        Ok(())
+    }
+
+    /// Adds a net connecting `pins`, optionally in `class_name`. The pins
+    /// must exist and must not be on another net yet.
+    pub fn add_net(
+        &mut self,
+        name: &str,
+        pins: Vec<Pin2>,
+        class_name: Option<&str>,
+    ) -> Result<(), DsnError> {
+        let segment = ScopePath::segment("net", name);
+        if self.network.nets.iter().any(|net| net.name == name) {
+            return Err(duplicate("net", name, &["network"]));
+        }
+        if let Some(class_name) = class_name
+            && !self.network.netclasses.contains_key(class_name)
+        {
+            return Err(dangling("class", class_name, &["network", &segment]));
+        }
+        for (index, pin) in pins.iter().enumerate() {
+            let pin_name = format!("{}-{}", pin.component_name, pin.pin_id);
+            let image = self
+                .find_instance(&pin.component_name)
+                .and_then(|(i, _)| self.library.images.get(&self.placement.components[i].name));
+            match image {
+                None => {
+                    return Err(dangling(
+                        "component",
+                        &pin.component_name,
+                        &["network", &segment],
+                    ));
+                }
+                Some(image) if !image.pins.contains_key(&pin.pin_id) => {
+                    return Err(dangling("pin", &pin_name, &["network", &segment]));
+                }
+                Some(_) => {}
+            }
+            let net = match self.net_of_pin(pin) {
+                Some(net) => Some(net.name.clone()),
+                None if pins[..index].contains(pin) => Some(name.to_string()),
+                None => None,
+            };
+            if let Some(net) = net {
+                return Err(edit_error(
+                    DsnErrorKind::AlreadyConnected { pin: pin_name, net },
+                    &["network", &segment],
+                ));
+            }
+        }
+
+        self.network.nets.push(Net {
+            name: name.to_string(),
+            pins,
+            rule: None,
+            unknown: Vec::new(),
+        });
+        if let Some(class_name) = class_name {
+            self.move_net_to_class(name, class_name)?;
+        }
+        Ok(())
+    }
+}
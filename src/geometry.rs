@@ -0,0 +1,99 @@
+use std::ops::{Add, Neg, Sub};
+
+use crate::units::Length;
+
+/// A point on the board in integer nanometres.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point {
+    pub x: Length,
+    pub y: Length,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point {
+        x: Length::ZERO,
+        y: Length::ZERO,
+    };
+
+    pub const fn new(x: Length, y: Length) -> Point {
+        Point { x, y }
+    }
+
+    pub fn as_mm(self) -> (f64, f64) {
+        (self.x.as_mm(), self.y.as_mm())
+    }
+
+    /// Rotates the point counterclockwise around the origin.
+    ///
+    /// Multiples of 90° are applied by swapping and negating coordinates, so
+    /// they are exact; any other angle is rounded to the nearest nanometre.
+    pub fn rotated(self, degrees: f64) -> Point {
+        let quarter_turns = degrees / 90.0;
+        if quarter_turns.fract() == 0.0 {
+            return match (quarter_turns as i64).rem_euclid(4) {
+                0 => self,
+                1 => Point::new(-self.y, self.x),
+                2 => Point::new(-self.x, -self.y),
+                _ => Point::new(self.y, -self.x),
+            };
+        }
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let x = self.x.nm() as f64;
+        let y = self.y.nm() as f64;
+        Point::new(
+            Length::from_nm((x * cos - y * sin).round() as i64),
+            Length::from_nm((x * sin + y * cos).round() as i64),
+        )
+    }
+
+    /// Mirrors the point about the Y axis, as done for images placed on the back side.
+    pub fn mirrored(self) -> Point {
+        Point::new(-self.x, self.y)
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+    fn add(self, rhs: Point) -> Point {
+        Point::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+    fn sub(self, rhs: Point) -> Point {
+        Point::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+    fn neg(self) -> Point {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+/// Places image-local coordinates on the board: mirror, then rotate, then translate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub offset: Point,
+    pub rotation: f64, // counterclockwise, in degrees
+    pub mirrored: bool,
+}
+
+impl Transform {
+    pub fn apply(&self, point: Point) -> Point {
+        let point = if self.mirrored {
+            point.mirrored()
+        } else {
+            point
+        };
+        point.rotated(self.rotation) + self.offset
+    }
+
+    /// Board angle of something rotated by `degrees` in image coordinates.
+    pub fn apply_rotation(&self, degrees: f64) -> f64 {
+        let degrees = if self.mirrored { -degrees } else { degrees };
+        degrees + self.rotation
+    }
+}
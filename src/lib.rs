@@ -1,11 +1,33 @@
+pub mod apply_session;
+pub mod connectivity;
+pub mod copper;
+pub mod design_diff;
+pub mod diagnostics;
 pub mod distinct_color_generator;
+pub mod drc;
+pub mod dsn_error;
+pub mod dsn_index;
 pub mod dsn_struct;
+pub mod edit;
+pub mod geometry;
+pub mod normalize_library;
 pub mod pad;
+pub mod panelize;
 pub mod parse_to_display;
 pub mod parse_to_display_format;
 pub mod parse_to_pcbproblem;
 pub mod parse_to_s_expr;
+pub mod parse_to_session;
 pub mod parse_to_struct;
 pub mod pcb_problem;
+pub mod route_stats;
+pub mod rules;
 pub mod s_expr;
+pub mod session_struct;
 pub mod shapes;
+pub mod shorts;
+pub mod units;
+pub mod via;
+pub mod write_dsn;
+pub mod write_s_expr;
+pub mod write_session;
@@ -1,6 +1,6 @@
 use pcb_parsing::dsn_struct::Shape;
 use pcb_parsing::parse_to_s_expr::parse_dsn_to_s_expr;
-use pcb_parsing::parse_to_struct::parse_s_expr_to_struct;
+use pcb_parsing::parse_to_struct::{ParseMode, parse_s_expr_to_struct_with_mode};
 
 fn main() {
     let data = std::fs::read_to_string("specctra_test.dsn").unwrap();
@@ -12,13 +12,26 @@ fn main() {
         }
     };
     //println!("{:#?}", result);
-    let dsn_struct = match parse_s_expr_to_struct(&result) {
-        Ok(structure) => structure,
+    let dsn_struct = match parse_s_expr_to_struct_with_mode(&result, ParseMode::Lenient) {
+        Ok((structure, diagnostics)) => {
+            for diagnostic in &diagnostics {
+                eprintln!("{}", diagnostic);
+            }
+            structure
+        }
         Err(e) => {
-            eprintln!("Error: {:?}", e);
+            eprintln!("Error: {}", e);
             panic!("Failed to convert S-Expression to struct");
         }
     };
+    println!("Design: {}", dsn_struct.design_name());
+    if let Some(parser) = &dsn_struct.parser {
+        println!(
+            "Host: {} {}",
+            parser.host_cad.as_deref().unwrap_or("unknown"),
+            parser.host_version.as_deref().unwrap_or("")
+        );
+    }
     println!(
         "Resolution: {} {}",
         dsn_struct.resolution.value, dsn_struct.resolution.unit
@@ -32,7 +45,14 @@ fn main() {
             .map(|l| &l.name)
             .collect::<Vec<_>>()
     );
-    println!("Boundary: {:?}", dsn_struct.structure.boundary.0);
+    println!(
+        "Boundary (mm): {:?}",
+        dsn_struct.structure.boundaries[0]
+            .points
+            .iter()
+            .map(|point| point.as_mm())
+            .collect::<Vec<_>>()
+    );
     println!(
         "COMPONENTS: {:?}",
         dsn_struct
@@ -47,7 +67,7 @@ fn main() {
         for instance in &component.instances {
             println!(
                 "  Instance: {} ({}, {}) rotation {}",
-                instance.reference, instance.position.0, instance.position.1, instance.rotation
+                instance.reference, instance.position.x, instance.position.y, instance.rotation
             );
         }
     }
@@ -58,7 +78,7 @@ fn main() {
         for (pin_num, pin) in &image.pins {
             println!(
                 "    Pin {}: pad_stack={}, position=({}, {})",
-                pin_num, pin.pad_stack_name, pin.position.0, pin.position.1
+                pin_num, pin.pad_stack_name, pin.position.x, pin.position.y
             );
         }
     }
@@ -66,33 +86,39 @@ fn main() {
     println!("\nLIBRARY PADSTACKS:");
     for (padstack_name, padstack) in &dsn_struct.library.pad_stacks {
         println!("PadStack: {}", padstack_name);
-        println!("  Through hole: {}", padstack.through_hole);
-        match &padstack.shape {
-            Shape::Circle { diameter } => {
-                println!("  Shape: Circle (diameter: {})", diameter);
-            }
-            Shape::Rect {
-                x_min,
-                y_min,
-                x_max,
-                y_max,
-            } => {
-                println!(
-                    "  Shape: Rect (x: {} to {}, y: {} to {})",
-                    x_min, x_max, y_min, y_max
-                );
-            }
-            Shape::Polygon {
-                aperture_width,
-                vertices,
-            } => {
-                println!(
-                    "  Shape: Polygon (aperture width: {}, vertices: {})",
+        println!("  Through hole: {}", padstack.through_hole());
+        for layer_shape in &padstack.shapes {
+            println!("  Layer: {}", layer_shape.layer);
+            match &layer_shape.shape {
+                Shape::Circle { diameter, center } => {
+                    println!(
+                        "  Shape: Circle (diameter: {}, centre: ({}, {}))",
+                        diameter, center.x, center.y
+                    );
+                }
+                Shape::Rect {
+                    x_min,
+                    y_min,
+                    x_max,
+                    y_max,
+                } => {
+                    println!(
+                        "  Shape: Rect (x: {} to {}, y: {} to {})",
+                        x_min, x_max, y_min, y_max
+                    );
+                }
+                Shape::Polygon {
                     aperture_width,
-                    vertices.len()
-                );
-                for (i, (x, y)) in vertices.iter().enumerate() {
-                    println!("    Vertex {}: ({}, {})", i + 1, x, y);
+                    vertices,
+                } => {
+                    println!(
+                        "  Shape: Polygon (aperture width: {}, vertices: {})",
+                        aperture_width,
+                        vertices.len()
+                    );
+                    for (i, vertex) in vertices.iter().enumerate() {
+                        println!("    Vertex {}: ({}, {})", i + 1, vertex.x, vertex.y);
+                    }
                 }
             }
         }
@@ -103,8 +129,14 @@ fn main() {
     for (class_name, netclass) in &dsn_struct.network.netclasses {
         println!("  Class: {}", class_name);
         println!("    Via: {}", netclass.via_name);
-        println!("    Width: {}", netclass.width);
-        println!("    Clearance: {}", netclass.clearance);
+        if let Some(rule) = &netclass.rule {
+            if let Some(width) = rule.width {
+                println!("    Width: {}", width);
+            }
+            for clearance in &rule.clearances {
+                println!("    Clearance: {} {:?}", clearance.value, clearance.types);
+            }
+        }
         println!("    Nets: {:?}", netclass.net_names);
     }
 
@@ -113,7 +145,7 @@ fn main() {
         println!("  Net: {}", net.name);
         println!("    Pins:");
         for pin in &net.pins {
-            println!("      {} pin {}", pin.component_name, pin.pin_number);
+            println!("      {} pin {}", pin.component_name, pin.pin_id);
         }
     }
 }
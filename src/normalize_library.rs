@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{
+    dsn_struct::{Component, DsnStruct, Image, PadStack},
+    session_struct::SessionStruct,
+    via::ViaName,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeKind {
+    Padstack,
+    Image,
+}
+
+/// Library entries found identical and merged into the one kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Merge {
+    pub kind: MergeKind,
+    pub kept: String,
+    /// Other names of the entry, now pointing at `kept`.
+    pub replaced: Vec<String>,
+    /// Entries dropped for repeating a name of the group, as a session's
+    /// `library_out` may.
+    pub duplicates: usize,
+}
+
+impl fmt::Display for Merge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            MergeKind::Padstack => "padstack",
+            MergeKind::Image => "image",
+        };
+        write!(f, "{} {}", kind, self.kept)?;
+        if !self.replaced.is_empty() {
+            write!(f, " replaces {}", self.replaced.join(", "))?;
+        }
+        let and = if self.replaced.is_empty() { "" } else { " and" };
+        match self.duplicates {
+            0 => Ok(()),
+            1 => write!(f, "{} drops 1 duplicate", and),
+            count => write!(f, "{} drops {} duplicates", and, count),
+        }
+    }
+}
+
+/// Padstacks are identical when their shapes and attributes are. A KiCad
+/// via name also encodes the drill, which the shapes do not show, so vias
+/// with different drills stay apart, and so do vias and pads.
+fn same_padstack(a: &PadStack, b: &PadStack) -> bool {
+    let drill = |padstack: &PadStack| ViaName::parse(&padstack.name).map(|via| via.drill);
+    a.shapes == b.shapes && a.attach == b.attach && a.unknown == b.unknown && drill(a) == drill(b)
+}
+
+fn same_image(a: &Image, b: &Image) -> bool {
+    a.pins == b.pins && a.clearance_class == b.clearance_class && a.unknown == b.unknown
+}
+
+/// Groups identical entries of `items`. Each group keeps the entry with the
+/// smallest name; the result maps every replaced name to the kept one.
+fn merge_identical<T>(
+    items: &mut HashMap<String, T>,
+    kind: MergeKind,
+    same: impl Fn(&T, &T) -> bool,
+    merges: &mut Vec<Merge>,
+) -> HashMap<String, String> {
+    let mut names: Vec<String> = items.keys().cloned().collect();
+    names.sort();
+
+    let mut renames = HashMap::new();
+    let mut kept: Vec<String> = Vec::new();
+    let mut groups: HashMap<String, Vec<String>> = HashMap::new();
+    for name in names {
+        let item = &items[&name];
+        match kept.iter().find(|kept_name| same(&items[*kept_name], item)) {
+            Some(kept_name) => {
+                renames.insert(name.clone(), kept_name.clone());
+                groups.entry(kept_name.clone()).or_default().push(name);
+            }
+            None => kept.push(name),
+        }
+    }
+    for name in renames.keys() {
+        items.remove(name);
+    }
+    for name in kept {
+        if let Some(replaced) = groups.remove(&name) {
+            merges.push(Merge {
+                kind,
+                kept: name,
+                replaced,
+                duplicates: 0,
+            });
+        }
+    }
+    renames
+}
+
+/// Merges identical padstacks in place, returning what was merged and the
+/// name each replaced padstack maps to.
+pub fn dedup_padstacks(
+    pad_stacks: &mut HashMap<String, PadStack>,
+) -> (Vec<Merge>, HashMap<String, String>) {
+    let mut merges = Vec::new();
+    let renames = merge_identical(pad_stacks, MergeKind::Padstack, same_padstack, &mut merges);
+    (merges, renames)
+}
+
+fn rename(name: &mut String, renames: &HashMap<String, String>) {
+    if let Some(new_name) = renames.get(name.as_str()) {
+        *name = new_name.clone();
+    }
+}
+
+/// Merges geometrically identical padstacks and images of the library and
+/// points every reference at the entry kept. Padstacks go first, so images
+/// that differed only in the names of their padstacks are merged as well.
+///
+/// A session routed on the design before it was normalized still places
+/// its components under the old image names, which
+/// [`apply_session`](crate::apply_session::apply_session) rejects. Rename
+/// them along the image merges returned before applying such a session.
+pub fn normalize_library(dsn: &mut DsnStruct) -> Vec<Merge> {
+    let (mut merges, padstack_renames) = dedup_padstacks(&mut dsn.library.pad_stacks);
+    for image in dsn.library.images.values_mut() {
+        for pin in image.pins.values_mut() {
+            rename(&mut pin.pad_stack_name, &padstack_renames);
+        }
+    }
+    let mut vias: Vec<String> = Vec::new();
+    for mut via in std::mem::take(&mut dsn.structure.vias) {
+        rename(&mut via, &padstack_renames);
+        if !vias.contains(&via) {
+            vias.push(via);
+        }
+    }
+    dsn.structure.vias = vias;
+    for netclass in dsn.network.netclasses.values_mut() {
+        rename(&mut netclass.via_name, &padstack_renames);
+    }
+    if let Some(wiring) = &mut dsn.wiring {
+        for via in &mut wiring.vias {
+            rename(&mut via.padstack_name, &padstack_renames);
+        }
+    }
+
+    let image_renames = merge_identical(
+        &mut dsn.library.images,
+        MergeKind::Image,
+        same_image,
+        &mut merges,
+    );
+    let mut components: Vec<Component> = Vec::new();
+    for mut component in std::mem::take(&mut dsn.placement.components) {
+        rename(&mut component.name, &image_renames);
+        match components
+            .iter_mut()
+            .find(|existing| existing.name == component.name)
+        {
+            Some(existing) => existing.instances.append(&mut component.instances),
+            None => components.push(component),
+        }
+    }
+    dsn.placement.components = components;
+    merges
+}
+
+/// Merges identical padstacks of a session's `library_out` and points the
+/// vias of `network_out` at the entry kept. Hosts may list one padstack
+/// several times under the same name; the copies are dropped and counted
+/// as duplicates. Each group keeps the smallest name, at the place of its
+/// first entry.
+pub fn normalize_session_library(session: &mut SessionStruct) -> Vec<Merge> {
+    let Some(routes) = &mut session.routes else {
+        return Vec::new();
+    };
+
+    // indices into `library_out` of each group, in file order
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for (index, padstack) in routes.library_out.iter().enumerate() {
+        let group = groups
+            .iter_mut()
+            .find(|group| same_padstack(&routes.library_out[group[0]], padstack));
+        match group {
+            Some(group) => group.push(index),
+            None => groups.push(vec![index]),
+        }
+    }
+
+    let mut merges = Vec::new();
+    let mut renames = HashMap::new();
+    let mut library_out = Vec::new();
+    for group in groups {
+        let mut names: Vec<&str> = group
+            .iter()
+            .map(|&index| routes.library_out[index].name.as_str())
+            .collect();
+        names.sort();
+        let entries = names.len();
+        names.dedup();
+        let duplicates = entries - names.len();
+        let kept = names.remove(0).to_string();
+        for name in &names {
+            renames.insert(name.to_string(), kept.clone());
+        }
+        if !names.is_empty() || duplicates > 0 {
+            merges.push(Merge {
+                kind: MergeKind::Padstack,
+                kept: kept.clone(),
+                replaced: names.iter().map(|name| name.to_string()).collect(),
+                duplicates,
+            });
+        }
+        let mut padstack = routes.library_out[group[0]].clone();
+        padstack.name = kept;
+        library_out.push(padstack);
+    }
+    routes.library_out = library_out;
+    for net in &mut routes.network_out {
+        for via in &mut net.vias {
+            rename(&mut via.padstack_name, &renames);
+        }
+    }
+    merges
+}
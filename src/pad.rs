@@ -1,16 +1,18 @@
+use crate::{geometry::Point, units::Length};
+
 #[derive(Debug, Clone)]
 pub enum PadShape {
     Circle {
-        diameter: f32,
+        diameter: Length,
     },
     Rectangle {
-        width: f32,
-        height: f32,
+        width: Length,
+        height: Length,
     },
     RoundRect {
-        width: f32,
-        height: f32,
-        corner_radius: f32,
+        width: Length,
+        height: Length,
+        corner_radius: Length,
     },
 }
 
@@ -20,8 +22,8 @@ pub struct PadName(pub String);
 #[derive(Debug, Clone)]
 pub struct Pad {
     pub name: PadName,
-    pub position: (f64, f64),
+    pub position: Point,
     pub shape: PadShape,
     pub rotation: cgmath::Deg<f32>, // Rotation in degrees
-    pub clearance: f32,             // Clearance around the pad
+    pub clearance: Length,          // Clearance around the pad
 }
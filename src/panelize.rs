@@ -0,0 +1,361 @@
+use std::collections::HashSet;
+
+use crate::{
+    diagnostics::{Diagnostic, ScopePath},
+    dsn_error::{DsnError, DsnErrorKind},
+    dsn_struct::{
+        Boundary, Component, DsnStruct, Keepout, KeepoutKind, LayerShape, Net, Pin2, Shape,
+    },
+    geometry::Point,
+    units::Length,
+};
+
+/// A breakaway tab between two copies. The router keeps wires and vias out
+/// of the rectangle on every layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tab {
+    /// Center of the tab in panel coordinates.
+    pub center: Point,
+    pub width: Length,
+    pub height: Length,
+}
+
+/// Where the copies of a panel go.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PanelLayout {
+    /// Offset of each copy from the original design. Copy `i` (counted from
+    /// one) gets the suffix `_i` on its references and nets.
+    pub offsets: Vec<Point>,
+    pub tabs: Vec<Tab>,
+}
+
+/// Index of the board outline: the first boundary on layer `pcb`. Later
+/// `pcb` boundaries are cutouts.
+fn outline_index(dsn: &DsnStruct) -> Option<usize> {
+    dsn.structure
+        .boundaries
+        .iter()
+        .position(|boundary| boundary.layer == "pcb")
+}
+
+/// Bounding box of the board outline as `(min, max)` corners.
+fn outline_bounds(dsn: &DsnStruct) -> Option<(Point, Point)> {
+    let outline = &dsn.structure.boundaries[outline_index(dsn)?];
+    let mut points = outline.points.iter();
+    let first = *points.next()?;
+    Some(points.fold((first, first), |(min, max), point| {
+        (
+            Point::new(min.x.min(point.x), min.y.min(point.y)),
+            Point::new(max.x.max(point.x), max.y.max(point.y)),
+        )
+    }))
+}
+
+impl PanelLayout {
+    /// `columns` × `rows` copies with `spacing` between neighbouring board
+    /// outlines. With a `tab_width`, every gap between neighbours gets a tab
+    /// of that width in its middle.
+    pub fn grid(
+        dsn: &DsnStruct,
+        columns: usize,
+        rows: usize,
+        spacing: Length,
+        tab_width: Option<Length>,
+    ) -> PanelLayout {
+        let (min, max) = outline_bounds(dsn).unwrap_or((Point::ORIGIN, Point::ORIGIN));
+        let pitch_x = max.x - min.x + spacing;
+        let pitch_y = max.y - min.y + spacing;
+        let center = Point::new((min.x + max.x) / 2, (min.y + max.y) / 2);
+
+        let mut offsets = Vec::new();
+        let mut tabs = Vec::new();
+        for row in 0..rows {
+            for column in 0..columns {
+                let offset = Point::new(pitch_x * column as i64, pitch_y * row as i64);
+                offsets.push(offset);
+                let Some(tab_width) = tab_width else {
+                    continue;
+                };
+                // tabs towards the right and upper neighbours
+                if column + 1 < columns {
+                    tabs.push(Tab {
+                        center: Point::new(max.x + spacing / 2, center.y) + offset,
+                        width: spacing,
+                        height: tab_width,
+                    });
+                }
+                if row + 1 < rows {
+                    tabs.push(Tab {
+                        center: Point::new(center.x, max.y + spacing / 2) + offset,
+                        width: tab_width,
+                        height: spacing,
+                    });
+                }
+            }
+        }
+        PanelLayout { offsets, tabs }
+    }
+}
+
+fn suffixed(name: &str, copy: usize) -> String {
+    format!("{}_{}", name, copy)
+}
+
+fn rect_keepout(tab: &Tab) -> Keepout {
+    let half = Point::new(tab.width / 2, tab.height / 2);
+    let min = tab.center - half;
+    let max = tab.center + half;
+    Keepout {
+        kind: KeepoutKind::Keepout,
+        name: None,
+        shape: LayerShape {
+            // `signal` stands for every signal layer
+            layer: "signal".to_string(),
+            shape: Shape::Rect {
+                x_min: min.x,
+                y_min: min.y,
+                x_max: max.x,
+                y_max: max.y,
+            },
+        },
+        unknown: Vec::new(),
+    }
+}
+
+fn translated_shape(layer_shape: &LayerShape, offset: Point) -> LayerShape {
+    let shape = match &layer_shape.shape {
+        Shape::Circle { diameter, center } => Shape::Circle {
+            diameter: *diameter,
+            center: *center + offset,
+        },
+        Shape::Rect {
+            x_min,
+            y_min,
+            x_max,
+            y_max,
+        } => Shape::Rect {
+            x_min: *x_min + offset.x,
+            y_min: *y_min + offset.y,
+            x_max: *x_max + offset.x,
+            y_max: *y_max + offset.y,
+        },
+        Shape::Polygon {
+            aperture_width,
+            vertices,
+        } => Shape::Polygon {
+            aperture_width: *aperture_width,
+            vertices: vertices.iter().map(|vertex| *vertex + offset).collect(),
+        },
+    };
+    LayerShape {
+        layer: layer_shape.layer.clone(),
+        shape,
+    }
+}
+
+/// Tiles `dsn` into a panel with one copy per offset of `layout`.
+///
+/// References and nets get the copy suffix (`U1_1`, `U1_2`, ...), net
+/// classes list the renamed nets of every copy, and library images and
+/// padstacks are shared. The board outline of the panel is the rectangle
+/// around all copies, and each copy keeps its own outline as a `signal`
+/// boundary. Further boundaries, keepouts, wires and vias are moved with
+/// their copy. Scopes the parser kept verbatim are taken over once,
+/// unchanged; those that sit somewhere on the board, keepouts and wiring
+/// the model lacks, are returned as warnings since the other copies go
+/// without them.
+pub fn panelize(
+    dsn: &DsnStruct,
+    layout: &PanelLayout,
+) -> Result<(DsnStruct, Vec<Diagnostic>), DsnError> {
+    if layout.offsets.is_empty() {
+        return Err(DsnError::new(
+            DsnErrorKind::MissingField {
+                field: "panel copy".to_string(),
+            },
+            ScopePath::new(),
+        ));
+    }
+    let copies = || (1..).zip(layout.offsets.iter().copied());
+
+    let mut panel = dsn.clone();
+
+    // outline: the rectangle around the outlines of all copies
+    let (min, max) = outline_bounds(dsn).ok_or_else(|| {
+        DsnError::new(
+            DsnErrorKind::MissingField {
+                field: "pcb boundary".to_string(),
+            },
+            ScopePath::from_segments(vec!["pcb".to_string(), "structure".to_string()]),
+        )
+    })?;
+    let outline = outline_index(dsn);
+    let (mut panel_min, mut panel_max) = (min, max);
+    for (_, offset) in copies() {
+        panel_min = Point::new(
+            panel_min.x.min(min.x + offset.x),
+            panel_min.y.min(min.y + offset.y),
+        );
+        panel_max = Point::new(
+            panel_max.x.max(max.x + offset.x),
+            panel_max.y.max(max.y + offset.y),
+        );
+    }
+    // The router needs one outline, so the panel gets the rectangle around
+    // all copies. Each copy's own outline comes along as a `signal`
+    // boundary, which keeps copper inside the copy, and the other
+    // boundaries (cutouts, routing areas) are translated as they are.
+    let panel_outline = Boundary {
+        layer: "pcb".to_string(),
+        width: Length::ZERO,
+        points: vec![
+            panel_min,
+            Point::new(panel_max.x, panel_min.y),
+            panel_max,
+            Point::new(panel_min.x, panel_max.y),
+            panel_min,
+        ],
+    };
+    panel.structure.boundaries = std::iter::once(panel_outline)
+        .chain(copies().flat_map(|(_, offset)| {
+            dsn.structure
+                .boundaries
+                .iter()
+                .enumerate()
+                .map(move |(index, boundary)| Boundary {
+                    layer: if Some(index) == outline {
+                        "signal".to_string()
+                    } else {
+                        boundary.layer.clone()
+                    },
+                    width: boundary.width,
+                    points: boundary
+                        .points
+                        .iter()
+                        .map(|point| *point + offset)
+                        .collect(),
+                })
+        }))
+        .collect();
+
+    panel.structure.keepouts = copies()
+        .flat_map(|(_, offset)| {
+            dsn.structure.keepouts.iter().map(move |keepout| Keepout {
+                shape: translated_shape(&keepout.shape, offset),
+                ..keepout.clone()
+            })
+        })
+        .chain(layout.tabs.iter().map(rect_keepout))
+        .collect();
+
+    panel.placement.components = dsn
+        .placement
+        .components
+        .iter()
+        .map(|component| Component {
+            name: component.name.clone(),
+            instances: copies()
+                .flat_map(|(copy, offset)| {
+                    component.instances.iter().map(move |instance| {
+                        let mut instance = instance.clone();
+                        instance.reference = suffixed(&instance.reference, copy);
+                        instance.position = instance.position + offset;
+                        instance
+                    })
+                })
+                .collect(),
+        })
+        .collect();
+
+    panel.network.nets = copies()
+        .flat_map(|(copy, _)| {
+            dsn.network.nets.iter().map(move |net| Net {
+                name: suffixed(&net.name, copy),
+                pins: net
+                    .pins
+                    .iter()
+                    .map(|pin| Pin2 {
+                        component_name: suffixed(&pin.component_name, copy),
+                        pin_id: pin.pin_id.clone(),
+                    })
+                    .collect(),
+                ..net.clone()
+            })
+        })
+        .collect();
+    for netclass in panel.network.netclasses.values_mut() {
+        netclass.net_names = copies()
+            .flat_map(|(copy, _)| {
+                dsn.network.netclasses[&netclass.net_class_name]
+                    .net_names
+                    .iter()
+                    .map(move |net| suffixed(net, copy))
+            })
+            .collect();
+    }
+
+    if let (Some(wiring), Some(panel_wiring)) = (&dsn.wiring, &mut panel.wiring) {
+        let renamed: HashSet<&str> = dsn
+            .network
+            .nets
+            .iter()
+            .map(|net| net.name.as_str())
+            .collect();
+        let net_of_copy = |net: &Option<String>, copy: usize| match net {
+            Some(net) if renamed.contains(net.as_str()) => Some(suffixed(net, copy)),
+            other => other.clone(),
+        };
+        panel_wiring.wires = copies()
+            .flat_map(|(copy, offset)| {
+                wiring.wires.iter().map(move |wire| {
+                    let mut wire = wire.clone();
+                    wire.points = wire.points.iter().map(|point| *point + offset).collect();
+                    wire.net = net_of_copy(&wire.net, copy);
+                    wire
+                })
+            })
+            .collect();
+        panel_wiring.vias = copies()
+            .flat_map(|(copy, offset)| {
+                wiring.vias.iter().map(move |via| {
+                    let mut via = via.clone();
+                    via.position = via.position + offset;
+                    via.net = net_of_copy(&via.net, copy);
+                    via
+                })
+            })
+            .collect();
+    }
+
+    let mut warnings = Vec::new();
+    if layout.offsets.len() > 1 {
+        let structure_keepouts = dsn.structure.unknown.iter().filter(|scope| {
+            scope.expr.as_list().is_some_and(|list| {
+                list.first()
+                    .and_then(|keyword| keyword.as_atom())
+                    .is_some_and(|keyword| keyword.ends_with("keepout"))
+            })
+        });
+        let wiring = dsn.wiring.iter().flat_map(|wiring| &wiring.unknown);
+        for (scope, parent) in structure_keepouts
+            .map(|scope| (scope, "structure"))
+            .chain(wiring.map(|scope| (scope, "wiring")))
+        {
+            let segment = scope
+                .expr
+                .as_list()
+                .map(|list| ScopePath::segment_for(list))
+                .unwrap_or_default();
+            warnings.push(Diagnostic {
+                location: ScopePath::from_segments(vec![
+                    "pcb".to_string(),
+                    parent.to_string(),
+                    segment,
+                ]),
+                message: "not modelled, so kept once at its original place".to_string(),
+            });
+        }
+    }
+
+    Ok((panel, warnings))
+}
@@ -1,30 +1,32 @@
-use crate::dsn_struct::{
-    Boundary, Component, ComponentInst, DsnStruct, Network, PadStack, Pin, Pin2, Placement, Shape,
-};
-use crate::pad::{self, Pad, PadName, PadShape};
+use crate::dsn_index::DsnIndex;
+use crate::dsn_struct::{Boundary, DsnStruct, Pin2, Shape};
+use crate::geometry::Point;
+use crate::pad::{Pad, PadName, PadShape};
 use crate::parse_to_display_format::{DisplayFormat, DisplayNetInfo, ExtraInfo};
 use crate::pcb_problem::{NetClassName, NetName};
+use crate::rules::{RuleObject, RuleResolver};
 use crate::shapes::{Line, Polygon};
-use cgmath::{Deg, Matrix2, Rad, Vector2};
-use core::net;
+use crate::units::Length;
+use cgmath::Deg;
 use std::collections::HashMap;
 
-fn calculate_boundary(boundary: &Boundary) -> Result<(f32, f32, (f32, f32)), String> {
-    let mut min_x = f64::MAX;
-    let mut max_x = f64::MIN;
-    let mut min_y = f64::MAX;
-    let mut max_y = f64::MIN;
-
-    for (x, y) in &boundary.0 {
-        min_x = min_x.min(*x);
-        max_x = max_x.max(*x);
-        min_y = min_y.min(*y);
-        max_y = max_y.max(*y);
+/// Bounding box over the points of every boundary.
+fn calculate_boundary(boundaries: &[Boundary]) -> Result<(Length, Length, Point), String> {
+    let mut points = boundaries.iter().flat_map(|boundary| &boundary.points);
+    let first = points.next().ok_or("Boundary has no points")?;
+    let (mut min_x, mut max_x) = (first.x, first.x);
+    let (mut min_y, mut max_y) = (first.y, first.y);
+
+    for point in points {
+        min_x = min_x.min(point.x);
+        max_x = max_x.max(point.x);
+        min_y = min_y.min(point.y);
+        max_y = max_y.max(point.y);
     }
 
-    let width = (max_x - min_x) as f32;
-    let height = (max_y - min_y) as f32;
-    let center = ((min_x + max_x) as f32 / 2.0, (min_y + max_y) as f32 / 2.0);
+    let width = max_x - min_x;
+    let height = max_y - min_y;
+    let center = Point::new((min_x + max_x) / 2, (min_y + max_y) / 2);
 
     Ok((width, height, center))
 }
@@ -73,27 +75,10 @@ fn buildpadmap(
 }
 */
 
-#[derive(Debug, Clone)]
-pub struct TransformedPad {
-    pub component_name: String, // 如 "J1"
-    pub pin_number: usize,
-    pub position: (f64, f64), // 最终PCB坐标系下的位置
-    pub shape: PadShape,
-    pub rotation: cgmath::Deg<f32>, // 最终旋转角度（度）
-}
-
-fn transform_point(point: (f64, f64), rotation_deg: f64, translation: (f64, f64)) -> (f64, f64) {
-    let rotation = Rad::from(Deg(rotation_deg));
-    let mat = Matrix2::from_angle(rotation);
-    let vec = Vector2::new(point.0, point.1);
-    let rotated = mat * vec;
-    (rotated.x + translation.0, rotated.y + translation.1)
-}
-
 fn convert_shape(shape: &Shape) -> Result<PadShape, String> {
     match shape {
-        Shape::Circle { diameter } => Ok(PadShape::Circle {
-            diameter: *diameter as f32,
+        Shape::Circle { diameter, .. } => Ok(PadShape::Circle {
+            diameter: *diameter,
         }),
         Shape::Rect {
             x_min,
@@ -101,8 +86,8 @@ fn convert_shape(shape: &Shape) -> Result<PadShape, String> {
             x_max,
             y_max,
         } => Ok(PadShape::Rectangle {
-            width: (*x_max - *x_min) as f32,
-            height: (*y_max - *y_min) as f32,
+            width: *x_max - *x_min,
+            height: *y_max - *y_min,
         }),
         Shape::Polygon {
             aperture_width,
@@ -113,105 +98,63 @@ fn convert_shape(shape: &Shape) -> Result<PadShape, String> {
             }
             // For simplicity, we treat the polygon as a round rectangle
             Ok(PadShape::RoundRect {
-                width: *aperture_width as f32,
-                height: *aperture_width as f32, // Assuming square for simplicity
-                corner_radius: 0.0,             // Not specified in the original code
+                width: *aperture_width,
+                height: *aperture_width,     // Assuming square for simplicity
+                corner_radius: Length::ZERO, // Not specified in the original code
             })
         }
     }
 }
 
-fn build_pad_map(dsn: &DsnStruct) -> Result<HashMap<String, TransformedPad>, String> {
-    let mut pad_map: HashMap<String, TransformedPad> = HashMap::new();
-
-    for component in &dsn.placement.components {
-        let image = dsn
-            .library
-            .images
-            .get(&component.name)
-            .ok_or_else(|| format!("Image not found: {}", component.name))?;
-
-        for instance in &component.instances {
-            for (pin_number, pin) in &image.pins {
-                let pad_stack = dsn
-                    .library
-                    .pad_stacks
-                    .get(&pin.pad_stack_name)
-                    .ok_or_else(|| format!("Pad stack not found: {}", pin.pad_stack_name))?;
-
-                // 1. 先应用pin相对footprint的位移
-                let mut position = pin.position;
-
-                // 2. 应用footprint旋转
-                position = transform_point(position, instance.rotation, (0.0, 0.0));
-
-                // 3. 应用footprint位移
-                position.0 += instance.position.0;
-                position.1 += instance.position.1;
-
-                // 转换形状
-                let shape = convert_shape(&pad_stack.shape)?;
-
-                // 创建唯一标识符
-                let pad_key = format!("{}-{}", instance.reference, pin_number);
-
-                pad_map.insert(
-                    pad_key,
-                    TransformedPad {
-                        component_name: instance.reference.clone(),
-                        pin_number: *pin_number,
-                        position,
-                        shape,
-                        rotation: Deg(instance.rotation as f32),
-                    },
-                );
-            }
-        }
+/// Where the shape sits relative to the pad origin; the pad is drawn around
+/// this point rather than the pin itself.
+fn shape_offset(shape: &Shape) -> Point {
+    match shape {
+        Shape::Circle { center, .. } => *center,
+        Shape::Rect {
+            x_min,
+            y_min,
+            x_max,
+            y_max,
+        } => Point::new((*x_min + *x_max) / 2, (*y_min + *y_max) / 2),
+        Shape::Polygon { .. } => Point::ORIGIN,
     }
-
-    Ok(pad_map)
 }
 
-fn pins_to_pads(pins: &Vec<Pin2>, dsn: &DsnStruct) -> Result<Vec<Pad>, String> {
-    let mut pad_map = build_pad_map(&dsn)?;
+fn pins_to_pads(pins: &[Pin2], index: &DsnIndex) -> Result<Vec<Pad>, String> {
+    let rules = RuleResolver::new(index);
     let mut pads: Vec<Pad> = Vec::new();
-    let mut net_clearance_map = HashMap::new();
-    for (_, netclass) in &dsn.network.netclasses {
-        for net_name in &netclass.net_names {
-            net_clearance_map.insert(net_name.clone(), netclass.clearance as f32);
-        }
-    }
-
-    // 预构建pin到net_name的映射
-    let mut pin_to_net = HashMap::new();
-    for net in &dsn.network.nets {
-        for pin in &net.pins {
-            let key = format!("{}-{}", pin.component_name, pin.pin_number);
-            pin_to_net.insert(key, net.name.clone());
-        }
-    }
 
     // 转换每个Pin2
     for pin in pins {
-        let pad_key = format!("{}-{}", pin.component_name, pin.pin_number);
+        let pad_key = format!("{}-{}", pin.component_name, pin.pin_id);
 
         // 查找pad基本信息
-        let transformed_pad = pad_map
-            .get(&pad_key)
-            .ok_or_else(|| format!("Pad {}-{} not found", pin.component_name, pin.pin_number))?;
-
-        // 查找所属网络的clearance
-        let clearance = pin_to_net
-            .get(&pad_key)
-            .and_then(|net_name| net_clearance_map.get(net_name))
-            .copied()
-            .unwrap_or(0.0); // 默认值
+        let instance = index.instance(&pin.component_name);
+        let image_pin = index.pin(&pin.component_name, &pin.pin_id);
+        let pad_stack = index.padstack(&pin.component_name, &pin.pin_id);
+        let shape = pad_stack.and_then(|pad_stack| pad_stack.shape());
+        let (Some(instance), Some(image_pin), Some(shape)) = (instance, image_pin, shape) else {
+            return Err(format!("Pad {} not found", pad_key));
+        };
+
+        // pin相对footprint的位置 -> 镜像(背面) -> 旋转 -> 平移
+        let transform = instance.transform();
+        // 焊盘形状相对pin的偏移随pin一起旋转
+        let offset = shape_offset(shape).rotated(image_pin.rotation);
+        let position = transform.apply(image_pin.position + offset);
+
+        // pad与任意对象之间的clearance
+        let clearance = rules.clearance(
+            rules.pin_object(&pin.component_name, &pin.pin_id),
+            RuleObject::default(),
+        );
 
         pads.push(Pad {
             name: PadName(pad_key),
-            position: transformed_pad.position,
-            shape: transformed_pad.shape.clone(),
-            rotation: transformed_pad.rotation,
+            position,
+            shape: convert_shape(shape)?,
+            rotation: Deg(transform.apply_rotation(image_pin.rotation) as f32),
             clearance,
         });
     }
@@ -219,56 +162,37 @@ fn pins_to_pads(pins: &Vec<Pin2>, dsn: &DsnStruct) -> Result<Vec<Pad>, String> {
     Ok(pads)
 }
 
-#[derive(Debug)]
-pub struct NetClassProperties {
-    pub name: NetClassName,
-    pub width: f32,
-    pub clearance: f32,
-    pub via_name: String,
-}
-
-fn find_netclass(network: &Network, net_name: &String) -> Result<NetClassProperties, String> {
-    network
-        .netclasses
-        .values()
-        .find(|netclass| netclass.net_names.iter().any(|net| net == net_name))
-        .map(|found_class| NetClassProperties {
-            name: NetClassName(found_class.net_class_name.clone()),
-            width: found_class.width as f32,
-            clearance: found_class.clearance as f32,
-            via_name: found_class.via_name.clone(),
-        })
-        .ok_or_else(|| format!("Net '{}' doesn't belong to any netclass", net_name))
-}
-
-fn parse_net_info(dsn: &DsnStruct) -> Result<HashMap<NetName, DisplayNetInfo>, String> {
+fn parse_net_info(index: &DsnIndex) -> Result<HashMap<NetName, DisplayNetInfo>, String> {
+    let dsn = index.dsn();
+    let rules = RuleResolver::new(index);
     let mut net_info: HashMap<NetName, DisplayNetInfo> = HashMap::new();
     for all_nets in dsn.network.nets.iter() {
         let net_name = all_nets.name.clone();
-        let pads = pins_to_pads(&all_nets.pins, &dsn)?;
-        let net_class_properties = find_netclass(&dsn.network, &net_name)?;
-        let via_diameter = dsn
-            .library
-            .pad_stacks
-            .get(&net_class_properties.via_name)
-            .and_then(|pad_stack| match &pad_stack.shape {
-                Shape::Circle { diameter } => Some(*diameter as f32),
-                _ => None,
-            })
+        let pads = pins_to_pads(&all_nets.pins, index)?;
+        let netclass = index
+            .net_class(&net_name)
+            .ok_or_else(|| format!("Net '{}' doesn't belong to any netclass", net_name))?;
+        let via_diameter = index
+            .via(&netclass.via_name)
+            .map(|via| via.diameter())
             .ok_or_else(|| {
                 format!(
-                    "Invalid via '{}' for net '{}': not found or not circular",
-                    net_class_properties.via_name, net_name
+                    "Via '{}' for net '{}' not found",
+                    netclass.via_name, net_name
                 )
             })?;
+        let net_object = RuleObject {
+            net: Some(&all_nets.name),
+            clearance_class: None,
+        };
         net_info.insert(
             NetName(net_name.clone()),
             DisplayNetInfo {
                 net_name: NetName(net_name),
                 pads,
-                net_class_name: net_class_properties.name,
-                default_trace_width: net_class_properties.width,
-                default_trace_clearance: net_class_properties.clearance,
+                net_class_name: NetClassName(netclass.net_class_name.clone()),
+                default_trace_width: rules.width(Some(&all_nets.name)),
+                default_trace_clearance: rules.clearance(net_object, RuleObject::default()),
                 via_diameter,
             },
         );
@@ -276,11 +200,37 @@ fn parse_net_info(dsn: &DsnStruct) -> Result<HashMap<NetName, DisplayNetInfo>, S
     Ok(net_info)
 }
 
+/// Pads whose pin rules ask for another clearance than their net become
+/// clearance overrides. A DSN file has no per-pin widths or source pads, so
+/// those are left to the defaults of the net.
+fn extra_info(display_format: &DisplayFormat) -> ExtraInfo {
+    let mut pad_name_to_trace_clearance: HashMap<PadName, Length> = HashMap::new();
+    for net in display_format.nets.values() {
+        for pad in &net.pads {
+            if pad.clearance != net.default_trace_clearance {
+                pad_name_to_trace_clearance.insert(pad.name.clone(), pad.clearance);
+            }
+        }
+    }
+    ExtraInfo {
+        pad_name_to_trace_width: HashMap::new(),
+        pad_name_to_trace_clearance,
+        net_name_to_source_pad: HashMap::new(),
+    }
+}
+
 pub fn dsn_to_display(dsn: DsnStruct) -> Result<(DisplayFormat, ExtraInfo), String> {
-    let (width, height, center) = calculate_boundary(&dsn.structure.boundary)?;
+    let (width, height, center) = calculate_boundary(&dsn.structure.boundaries)?;
     let obstacle_lines: Vec<Line> = Vec::new();
     let obstacle_polygons: Vec<Polygon> = Vec::new();
-    let net_info: HashMap<NetName, DisplayNetInfo> = parse_net_info(&dsn)?;
+    let index = DsnIndex::new(&dsn).map_err(|errors| {
+        errors
+            .iter()
+            .map(|error| error.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    })?;
+    let net_info: HashMap<NetName, DisplayNetInfo> = parse_net_info(&index)?;
 
     let display_format = DisplayFormat {
         width,
@@ -291,5 +241,7 @@ pub fn dsn_to_display(dsn: DsnStruct) -> Result<(DisplayFormat, ExtraInfo), Stri
         nets: net_info,
     };
 
-    todo!("Implement dsn_to_display function");
+    let extra_info = extra_info(&display_format);
+
+    Ok((display_format, extra_info))
 }
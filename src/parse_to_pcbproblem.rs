@@ -1,10 +1,10 @@
 // convert_to_problem.rs
 use crate::pad::Pad;
 use crate::parse_to_display_format::{DisplayFormat, ExtraInfo};
+use crate::units::Length;
 use crate::{
     pad::PadName,
-    pcb_problem::{Connection, ConnectionID, NetInfo, NetName, PcbProblem},
-    shapes::{Line, Polygon},
+    pcb_problem::{NetName, PcbProblem},
 };
 use std::collections::HashMap;
 
@@ -107,10 +107,10 @@ impl Converter {
     /// 获取trace设置（优先使用extra_info中的覆盖值）
     fn get_trace_settings(
         pad_name: &PadName,
-        default_width: f32,
-        default_clearance: f32,
+        default_width: Length,
+        default_clearance: Length,
         extra_info: &ExtraInfo,
-    ) -> (f32, f32) {
+    ) -> (Length, Length) {
         (
             extra_info
                 .pad_name_to_trace_width
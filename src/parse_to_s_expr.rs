@@ -1,9 +1,12 @@
 use nom::{
-    bytes::complete::{tag, take_while, take_while1}, character::complete::{char, digit1, multispace0, multispace1}, combinator::{map, map_res}, multi::many0, sequence::{delimited, preceded, separated_pair}, IResult, Parser
+    bytes::complete::{tag, take_while, take_while1}, character::complete::{anychar, char, multispace0, multispace1}, error::{Error, ErrorKind}, multi::many0, sequence::{delimited, preceded}, IResult, Parser
 };
 
 use crate::s_expr::SExpr;
 
+/// Deepest list nesting accepted. Real files stay below ten levels; the limit
+/// keeps hostile input from overflowing the stack.
+pub const MAX_DEPTH: usize = 256;
 
 fn is_atom_char(c: char) -> bool {
     !c.is_whitespace() && c != '(' && c != ')'
@@ -23,24 +26,36 @@ fn parse_quoted_string(input: &str) -> IResult<&str, SExpr> {
     Ok((input, SExpr::Atom(s.to_string())))
 }
 
-fn parse_list(input: &str) -> IResult<&str, SExpr> {
+/// `(string_quote ")` names the quote character itself, so the character after
+/// the keyword is a token of its own and must not open a quoted string.
+fn parse_string_quote(input: &str) -> IResult<&str, SExpr> {
+    let (input, (_, _, keyword, _, quote, _, _)) = (
+        char('('), multispace0, tag("string_quote"), multispace1, anychar, multispace0, char(')')
+    ).parse(input)?;
+    Ok((input, SExpr::List(vec![SExpr::Atom(keyword.to_string()), SExpr::Atom(quote.to_string())])))
+}
+
+fn parse_list(input: &str, depth: usize) -> IResult<&str, SExpr> {
     let (input, _) = char('(')(input)?;
+    if depth >= MAX_DEPTH {
+        return Err(nom::Err::Failure(Error::new(input, ErrorKind::TooLarge)));
+    }
     let (input, _) = multispace0(input)?;
-    let (input, items) = many0(preceded(multispace0, parse_expr)).parse(input)?;
+    let (input, items) = many0(preceded(multispace0, |input| parse_expr(input, depth + 1))).parse(input)?;
     let (input, _) = multispace0(input)?;
     let (input, _) = char(')')(input)?;
     Ok((input, SExpr::List(items)))
 }
 
-fn parse_expr(input: &str) -> IResult<&str, SExpr> {
+fn parse_expr(input: &str, depth: usize) -> IResult<&str, SExpr> {
     preceded(
         multispace0,
-        nom::branch::alt((parse_list, parse_quoted_string, parse_atom))
+        nom::branch::alt((parse_string_quote, |input| parse_list(input, depth), parse_quoted_string, parse_atom))
     ).parse(input)
 }
 
 pub fn parse_dsn_to_s_expr(input: &str) -> Result<SExpr, nom::Err<nom::error::Error<&str>>> {
-    let (remaining, expr) = parse_expr(input)?;
+    let (remaining, expr) = parse_expr(input, 0)?;
     if !remaining.trim().is_empty() {
         eprintln!("Warning: leftover input: {:?}", remaining);
     }
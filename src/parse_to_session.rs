@@ -0,0 +1,295 @@
+use std::collections::HashSet;
+
+use crate::{
+    diagnostics::{Diagnostic, ScopePath},
+    dsn_error::DsnError,
+    dsn_struct::{Component, PadStack, ParserHeader, Resolution, UnknownScope, Wire, WiringVia},
+    parse_to_struct::{
+        ParseContext, ParseMode, parse_component, parse_padstack, parse_parser,
+        parse_pin_reference, parse_resolution, parse_wire, parse_wiring_via, placed_components,
+    },
+    s_expr::SExpr,
+    session_struct::{PinSwap, Routes, SessionNet, SessionPlacement, SessionStruct, WasIs},
+    units::Unit,
+};
+
+/// Reads the `(resolution ...)` of a session scope and switches the context
+/// to it. Session coordinates are integers counted in resolution steps, and
+/// every scope that holds coordinates declares its own resolution.
+fn scope_resolution(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<Resolution, DsnError> {
+    let resolution_list = s_expr
+        .iter()
+        .filter_map(|x| x.as_list())
+        .find(|list| {
+            list.first().and_then(|x| x.as_atom()).map(|s| s.as_str()) == Some("resolution")
+        })
+        .ok_or_else(|| ctx.missing("resolution"))?;
+    let resolution = ctx.scoped(resolution_list, |ctx| {
+        parse_resolution(resolution_list, ctx)
+    })?;
+    ctx.unit = resolution.unit;
+    ctx.per_unit = resolution.value;
+    Ok(resolution)
+}
+
+fn parse_session_placement(
+    s_expr: &[SExpr],
+    ctx: &mut ParseContext,
+) -> Result<SessionPlacement, DsnError> {
+    ctx.expect_keyword(s_expr, "placement")?;
+    let resolution = scope_resolution(s_expr, ctx)?;
+    let mut components: Vec<Component> = Vec::new();
+    let mut unknown: Vec<UnknownScope> = Vec::new();
+    for (position, item) in s_expr.iter().enumerate().skip(1) {
+        let Some(expr_list) = ctx.scope_item(&mut unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
+            "resolution" => {}
+            "component" => {
+                components.push(ctx.scoped(expr_list, |ctx| parse_component(expr_list, ctx))?);
+            }
+            _ => {
+                ctx.unknown_scope(&mut unknown, position, expr_list)?;
+            }
+        }
+    }
+    Ok(SessionPlacement {
+        resolution,
+        components,
+        unknown,
+    })
+}
+
+fn parse_was_is(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<WasIs, DsnError> {
+    ctx.expect_keyword(s_expr, "was_is")?;
+    let mut pin_swaps: Vec<PinSwap> = Vec::new();
+    let mut unknown: Vec<UnknownScope> = Vec::new();
+    for (position, item) in s_expr.iter().enumerate().skip(1) {
+        let Some(expr_list) = ctx.scope_item(&mut unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
+            "pins" => {
+                let was = parse_pin_reference(ctx.atom(expr_list, 1, "was pin")?, ctx)?;
+                let is = parse_pin_reference(ctx.atom(expr_list, 2, "is pin")?, ctx)?;
+                pin_swaps.push(PinSwap { was, is });
+            }
+            _ => {
+                ctx.unknown_scope(&mut unknown, position, expr_list)?;
+            }
+        }
+    }
+    Ok(WasIs { pin_swaps, unknown })
+}
+
+/// Fills `pad_stacks` and returns the items of `library_out` that are no
+/// padstack.
+fn parse_library_out(
+    s_expr: &[SExpr],
+    pad_stacks: &mut Vec<PadStack>,
+    ctx: &mut ParseContext,
+) -> Result<Vec<UnknownScope>, DsnError> {
+    let mut unknown: Vec<UnknownScope> = Vec::new();
+    for (position, item) in s_expr.iter().enumerate().skip(1) {
+        let Some(expr_list) = ctx.scope_item(&mut unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
+            "padstack" => {
+                pad_stacks.push(ctx.scoped(expr_list, |ctx| parse_padstack(expr_list, ctx))?);
+            }
+            _ => {
+                ctx.unknown_scope(&mut unknown, position, expr_list)?;
+            }
+        }
+    }
+    Ok(unknown)
+}
+
+fn parse_session_net(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<SessionNet, DsnError> {
+    ctx.expect_keyword(s_expr, "net")?;
+    let name = ctx.atom(s_expr, 1, "net name")?.to_string();
+    let mut wires: Vec<Wire> = Vec::new();
+    let mut vias: Vec<WiringVia> = Vec::new();
+    let mut unknown: Vec<UnknownScope> = Vec::new();
+    for (position, item) in s_expr.iter().enumerate().skip(2) {
+        let Some(expr_list) = ctx.scope_item(&mut unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
+            "wire" => {
+                // only path wires are modelled
+                let is_path = expr_list
+                    .get(1)
+                    .and_then(|x| x.as_list())
+                    .and_then(|shape| shape.first())
+                    .and_then(|x| x.as_atom())
+                    .is_some_and(|keyword| keyword == "path");
+                if is_path {
+                    wires.push(ctx.scoped(expr_list, |ctx| parse_wire(expr_list, ctx))?);
+                } else {
+                    ctx.unknown_scope(&mut unknown, position, expr_list)?;
+                }
+            }
+            "via" => {
+                vias.push(ctx.scoped(expr_list, |ctx| parse_wiring_via(expr_list, ctx))?);
+            }
+            _ => {
+                ctx.unknown_scope(&mut unknown, position, expr_list)?;
+            }
+        }
+    }
+    Ok(SessionNet {
+        name,
+        wires,
+        vias,
+        unknown,
+    })
+}
+
+/// Fills `nets` and returns the items of `network_out` that are no net.
+fn parse_network_out(
+    s_expr: &[SExpr],
+    nets: &mut Vec<SessionNet>,
+    ctx: &mut ParseContext,
+) -> Result<Vec<UnknownScope>, DsnError> {
+    let mut unknown: Vec<UnknownScope> = Vec::new();
+    for (position, item) in s_expr.iter().enumerate().skip(1) {
+        let Some(expr_list) = ctx.scope_item(&mut unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
+            "net" => {
+                nets.push(ctx.scoped(expr_list, |ctx| parse_session_net(expr_list, ctx))?);
+            }
+            _ => {
+                ctx.unknown_scope(&mut unknown, position, expr_list)?;
+            }
+        }
+    }
+    Ok(unknown)
+}
+
+fn parse_routes(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<Routes, DsnError> {
+    ctx.expect_keyword(s_expr, "routes")?;
+    let resolution = scope_resolution(s_expr, ctx)?;
+    let mut parser: Option<ParserHeader> = None;
+    let mut library_out: Vec<PadStack> = Vec::new();
+    let mut library_out_unknown: Vec<UnknownScope> = Vec::new();
+    let mut network_out: Vec<SessionNet> = Vec::new();
+    let mut network_out_unknown: Vec<UnknownScope> = Vec::new();
+    let mut unknown: Vec<UnknownScope> = Vec::new();
+    for (position, item) in s_expr.iter().enumerate().skip(1) {
+        let Some(expr_list) = ctx.scope_item(&mut unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
+            "resolution" => {}
+            "parser" => {
+                parser = Some(ctx.scoped(expr_list, |ctx| parse_parser(expr_list, ctx))?);
+            }
+            "library_out" => {
+                library_out_unknown = ctx.scoped(expr_list, |ctx| {
+                    parse_library_out(expr_list, &mut library_out, ctx)
+                })?;
+            }
+            "network_out" => {
+                network_out_unknown = ctx.scoped(expr_list, |ctx| {
+                    parse_network_out(expr_list, &mut network_out, ctx)
+                })?;
+            }
+            _ => {
+                ctx.unknown_scope(&mut unknown, position, expr_list)?;
+            }
+        }
+    }
+    Ok(Routes {
+        resolution,
+        parser,
+        library_out,
+        library_out_unknown,
+        network_out,
+        network_out_unknown,
+        unknown,
+    })
+}
+
+/// Parses a session file in strict mode: unknown scopes are kept, stray
+/// atoms are an error.
+pub fn parse_s_expr_to_session(s_expr: &SExpr) -> Result<SessionStruct, DsnError> {
+    parse_s_expr_to_session_with_mode(s_expr, ParseMode::Strict).map(|(session, _)| session)
+}
+
+/// Parses a session file, returning the warnings collected in lenient mode.
+pub fn parse_s_expr_to_session_with_mode(
+    s_expr: &SExpr,
+    mode: ParseMode,
+) -> Result<(SessionStruct, Vec<Diagnostic>), DsnError> {
+    let mut ctx = ParseContext {
+        mode,
+        // replaced by the resolution of each scope
+        unit: Unit::Um,
+        per_unit: 1.0,
+        path: ScopePath::new(),
+        diagnostics: Vec::new(),
+        components: HashSet::new(),
+    };
+    let expr_list = ctx.list(s_expr)?;
+    ctx.expect_keyword(expr_list, "session")?;
+    let name = ctx.atom(expr_list, 1, "session name")?.to_string();
+    ctx.path.push("session".to_string());
+    // the pin swaps name pins by their component, see `parse_pin_reference`
+    for expr_list2 in expr_list.iter().filter_map(|expr| expr.as_list()) {
+        if expr_list2
+            .first()
+            .and_then(|x| x.as_atom())
+            .map(|s| s.as_str())
+            == Some("placement")
+        {
+            ctx.components.extend(placed_components(expr_list2));
+        }
+    }
+
+    let mut base_design: Option<String> = None;
+    let mut placement: Option<SessionPlacement> = None;
+    let mut was_is: Option<WasIs> = None;
+    let mut routes: Option<Routes> = None;
+    let mut unknown: Vec<UnknownScope> = Vec::new();
+    for (position, expr) in expr_list.iter().enumerate().skip(2) {
+        let Some(expr_list2) = ctx.scope_item(&mut unknown, position, expr)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list2)? {
+            "base_design" => {
+                base_design = Some(ctx.atom(expr_list2, 1, "base design")?.to_string());
+            }
+            "placement" => {
+                placement =
+                    Some(ctx.scoped(expr_list2, |ctx| parse_session_placement(expr_list2, ctx))?);
+            }
+            "was_is" => {
+                was_is = Some(ctx.scoped(expr_list2, |ctx| parse_was_is(expr_list2, ctx))?);
+            }
+            "routes" => {
+                routes = Some(ctx.scoped(expr_list2, |ctx| parse_routes(expr_list2, ctx))?);
+            }
+            _ => {
+                ctx.unknown_scope(&mut unknown, position, expr_list2)?;
+            }
+        }
+    }
+    let base_design = base_design.ok_or_else(|| ctx.missing("base_design"))?;
+
+    Ok((
+        SessionStruct {
+            name,
+            base_design,
+            placement,
+            was_is,
+            routes,
+            unknown,
+        },
+        ctx.diagnostics,
+    ))
+}
@@ -1,329 +1,836 @@
 //use core::net;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::{
+    diagnostics::{Diagnostic, ScopePath},
+    dsn_error::{DsnError, DsnErrorKind},
     dsn_struct::{
-        Boundary, Component, ComponentInst, DsnStruct, Image, Layer, Library, Net, Netclass,
-        Network, PadStack, Pin, Pin2, Placement, Resolution, Shape, Structure,
+        AutorouteSettings, Boundary, ClassClass, Clearance, Component, ComponentInst, Control,
+        DsnStruct, Grid, GridDirection, GridKind, Image, Keepout, KeepoutKind, Layer, LayerRule,
+        LayerShape, Library, Net, Netclass, Network, PadStack, ParserHeader, Pin, Pin2, Placement,
+        PreferredDirection, Resolution, Rule, Shape, Side, Structure, UnknownScope, Wire, Wiring,
+        WiringVia,
     },
+    geometry::Point,
     s_expr::SExpr,
+    units::{Length, Unit},
 };
 
-fn parse_layer(s_expr: &Vec<SExpr>) -> Result<Layer, String> {
-    let first_item = s_expr
-        .first()
-        .ok_or("Expected at least one item in the layer scope")?;
-    let first_item = first_item
-        .as_atom()
-        .ok_or("Expected an atom as the first item in the layer scope")?;
-    if first_item != "layer" {
-        return Err(format!(
-            "Expected 'layer' as the first item, found: {}",
-            first_item
-        ));
-    }
-    let second_item = s_expr
-        .get(1)
-        .ok_or("Expected a second item in the layer scope")?;
-    let second_item = second_item
-        .as_atom()
-        .ok_or("Expected an atom as the second item in the layer scope")?;
+/// How the parser reacts to input it does not model. Scopes with an unknown
+/// keyword are kept in either mode so they survive a round trip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Keep unknown scopes silently, but fail on stray atoms and pad shapes
+    /// the model lacks.
+    Strict,
+    /// Keep all of them and record a warning for each.
+    Lenient,
+}
+
+/// State shared by the DSN and session parsers.
+pub(crate) struct ParseContext {
+    pub(crate) mode: ParseMode,
+    pub(crate) unit: Unit,
+    /// How many of the numbers in the file make one `unit`: 1 in a DSN file,
+    /// the resolution value in a session file, which writes integers in
+    /// resolution steps.
+    pub(crate) per_unit: f64,
+    pub(crate) path: ScopePath,
+    pub(crate) diagnostics: Vec<Diagnostic>,
+    /// Reference names of the placed components, read before the nets so
+    /// that a pin reference can be split where its component name ends.
+    pub(crate) components: HashSet<String>,
+}
+
+/// Short description of an expression for error messages.
+fn describe(expr: &SExpr) -> String {
+    match expr {
+        SExpr::Atom(atom) => format!("atom '{}'", atom),
+        SExpr::List(list) => match list.first().and_then(|x| x.as_atom()) {
+            Some(keyword) => format!("list '({} ...)'", keyword),
+            None => "list".to_string(),
+        },
+    }
+}
+
+impl ParseContext {
+    /// Attaches the current scope path to `kind`.
+    pub(crate) fn error(&self, kind: DsnErrorKind) -> DsnError {
+        DsnError::new(kind, self.path.clone())
+    }
+
+    pub(crate) fn missing(&self, field: &str) -> DsnError {
+        self.error(DsnErrorKind::MissingField {
+            field: field.to_string(),
+        })
+    }
+
+    /// Runs `parse` with `scope` appended to the current scope path.
+    pub(crate) fn scoped<T>(
+        &mut self,
+        scope: &[SExpr],
+        parse: impl FnOnce(&mut ParseContext) -> Result<T, DsnError>,
+    ) -> Result<T, DsnError> {
+        self.scoped_as(ScopePath::segment_for(scope), parse)
+    }
+
+    pub(crate) fn scoped_as<T>(
+        &mut self,
+        segment: String,
+        parse: impl FnOnce(&mut ParseContext) -> Result<T, DsnError>,
+    ) -> Result<T, DsnError> {
+        self.path.push(segment);
+        let result = parse(self);
+        self.path.pop();
+        result
+    }
+
+    /// Fails with `kind` in strict mode. In lenient mode, records a warning
+    /// at `location` and keeps `expr` in `unknown` so it survives a round trip.
+    fn skip(
+        &mut self,
+        unknown: &mut Vec<UnknownScope>,
+        position: usize,
+        location: ScopePath,
+        kind: DsnErrorKind,
+        expr: &SExpr,
+    ) -> Result<(), DsnError> {
+        match self.mode {
+            ParseMode::Strict => Err(self.error(kind)),
+            ParseMode::Lenient => {
+                self.diagnostics.push(Diagnostic {
+                    location,
+                    message: kind.to_string(),
+                });
+                unknown.push(UnknownScope {
+                    position,
+                    expr: expr.clone(),
+                });
+                Ok(())
+            }
+        }
+    }
+
+    /// Keeps the scope in `unknown` so it survives a round trip, and in
+    /// lenient mode also records a warning.
+    pub(crate) fn unknown_scope(
+        &mut self,
+        unknown: &mut Vec<UnknownScope>,
+        position: usize,
+        scope: &[SExpr],
+    ) -> Result<(), DsnError> {
+        let kind = DsnErrorKind::UnknownKeyword {
+            keyword: self.keyword(scope)?.to_string(),
+        };
+        if self.mode == ParseMode::Strict {
+            unknown.push(UnknownScope {
+                position,
+                expr: SExpr::List(scope.to_vec()),
+            });
+            return Ok(());
+        }
+        let location = self.path.child(ScopePath::segment_for(scope));
+        self.skip(
+            unknown,
+            position,
+            location,
+            kind,
+            &SExpr::List(scope.to_vec()),
+        )
+    }
+
+    /// Returns `item` as a scope. An atom among scopes fails in strict mode;
+    /// in lenient mode it is kept in `unknown` like an unknown scope and
+    /// `None` is returned.
+    pub(crate) fn scope_item<'a>(
+        &mut self,
+        unknown: &mut Vec<UnknownScope>,
+        position: usize,
+        item: &'a SExpr,
+    ) -> Result<Option<&'a [SExpr]>, DsnError> {
+        if let Some(list) = item.as_list() {
+            return Ok(Some(list));
+        }
+        let kind = DsnErrorKind::UnexpectedExpr {
+            expected: "a list".to_string(),
+            found: describe(item),
+        };
+        self.skip(unknown, position, self.path.clone(), kind, item)?;
+        Ok(None)
+    }
+
+    pub(crate) fn list<'a>(&self, item: &'a SExpr) -> Result<&'a [SExpr], DsnError> {
+        item.as_list().map(|list| list.as_slice()).ok_or_else(|| {
+            self.error(DsnErrorKind::UnexpectedExpr {
+                expected: "a list".to_string(),
+                found: describe(item),
+            })
+        })
+    }
+
+    pub(crate) fn keyword<'a>(&self, scope: &'a [SExpr]) -> Result<&'a str, DsnError> {
+        self.atom(scope, 0, "keyword")
+    }
+
+    pub(crate) fn expect_keyword(&self, scope: &[SExpr], expected: &str) -> Result<(), DsnError> {
+        let found = self.keyword(scope)?;
+        if found != expected {
+            return Err(self.error(DsnErrorKind::UnexpectedKeyword {
+                expected: expected.to_string(),
+                found: found.to_string(),
+            }));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn atom<'a>(
+        &self,
+        scope: &'a [SExpr],
+        index: usize,
+        field: &str,
+    ) -> Result<&'a str, DsnError> {
+        let item = scope.get(index).ok_or_else(|| self.missing(field))?;
+        item.as_atom().map(|atom| atom.as_str()).ok_or_else(|| {
+            self.error(DsnErrorKind::UnexpectedExpr {
+                expected: format!("an atom for {}", field),
+                found: describe(item),
+            })
+        })
+    }
+
+    pub(crate) fn parse_number<T: std::str::FromStr>(
+        &self,
+        atom: &str,
+        field: &str,
+    ) -> Result<T, DsnError> {
+        atom.parse::<T>()
+            .map_err(|_| self.invalid_number(field, atom))
+    }
+
+    fn invalid_number(&self, field: &str, value: &str) -> DsnError {
+        self.error(DsnErrorKind::InvalidNumber {
+            field: field.to_string(),
+            value: value.to_string(),
+        })
+    }
+
+    /// Reads a finite number; `inf` and `nan` are rejected.
+    pub(crate) fn number(
+        &self,
+        scope: &[SExpr],
+        index: usize,
+        field: &str,
+    ) -> Result<f64, DsnError> {
+        let atom = self.atom(scope, index, field)?;
+        let value: f64 = self.parse_number(atom, field)?;
+        if !value.is_finite() {
+            return Err(self.invalid_number(field, atom));
+        }
+        Ok(value)
+    }
+
+    /// Reads a length written in the file unit, up to [`Length::MAX`].
+    pub(crate) fn length(
+        &self,
+        scope: &[SExpr],
+        index: usize,
+        field: &str,
+    ) -> Result<Length, DsnError> {
+        let value = self.number(scope, index, field)?;
+        Length::checked_from_unit(value / self.per_unit, self.unit)
+            .ok_or_else(|| self.invalid_number(field, &value.to_string()))
+    }
+
+    /// Reads the two lengths at `index` and `index + 1` as a point.
+    pub(crate) fn point(
+        &self,
+        scope: &[SExpr],
+        index: usize,
+        field: &str,
+    ) -> Result<Point, DsnError> {
+        Ok(Point::new(
+            self.length(scope, index, &format!("{} x", field))?,
+            self.length(scope, index + 1, &format!("{} y", field))?,
+        ))
+    }
+}
+
+/// Keeps every item of `scope` from `start` on verbatim.
+pub(crate) fn keep_verbatim(scope: &[SExpr], start: usize) -> Vec<UnknownScope> {
+    scope
+        .iter()
+        .enumerate()
+        .skip(start)
+        .map(|(position, expr)| UnknownScope {
+            position,
+            expr: expr.clone(),
+        })
+        .collect()
+}
+
+fn parse_layer(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<Layer, DsnError> {
+    ctx.expect_keyword(s_expr, "layer")?;
+    let name = ctx.atom(s_expr, 1, "layer name")?;
+    // type and property scopes are kept verbatim
     Ok(Layer {
-        name: second_item.to_string(),
+        name: name.to_string(),
+        unknown: keep_verbatim(s_expr, 2),
     })
 }
 
-fn parse_boundary(s_expr: &Vec<SExpr>) -> Result<Boundary, String> {
-    let first_item = s_expr
-        .first()
-        .ok_or("Expected at least one item in the boundary scope")?;
-    let first_item = first_item
-        .as_atom()
-        .ok_or("Expected an atom as the first item in the boundary scope")?;
-    if first_item != "boundary" {
-        return Err(format!(
-            "Expected 'boundary' as the first item, found: {}",
-            first_item
-        ));
-    }
-    let second_item = s_expr
-        .get(1)
-        .ok_or("Expected a second item in the boundary scope")?;
-    let second_list = second_item
-        .as_list()
-        .ok_or("Expected a list as the second item in the boundary scope")?;
-    if second_list.len() < 3 {
-        return Err("Expected at least three items in the boundary list".to_string());
-    }
-    let mut points: Vec<(f64, f64)> = Vec::new();
-    let mut prev_number: Option<f64> = None;
-    for item in second_list.iter().skip(3) {
-        match prev_number {
-            Some(prev_num) => {
-                let number = item
-                    .as_atom()
-                    .ok_or("Expected an atom in the boundary list")?;
-                let number = number
-                    .parse::<f64>()
-                    .map_err(|e| format!("Failed to parse boundary number: {}", e))?;
-                points.push((prev_num, number));
-                prev_number = None;
-            }
-            None => {
-                let number = item
-                    .as_atom()
-                    .ok_or("Expected an atom in the boundary list")?;
-                let number = number
-                    .parse::<f64>()
-                    .map_err(|e| format!("Failed to parse boundary number: {}", e))?;
-                prev_number = Some(number);
-            }
-        }
-    }
-    if prev_number.is_some() {
-        return Err("Expected an even number of items in the boundary list".to_string());
-    }
-    let boundary = Boundary(points);
-    Ok(boundary)
-}
-
-fn parse_structure(s_expr: &Vec<SExpr>) -> Result<Structure, String> {
-    // Placeholder for structure parsing logic
-    // This function should parse the structure part of the S-expression
-    // and populate the DsnStruct accordingly.
-    let first_item = s_expr
-        .first()
-        .ok_or("Expected at least one item in the structure scope")?;
-    let first_item = first_item
-        .as_atom()
-        .ok_or("Expected an atom as the first item in the structure scope")?;
-    if first_item != "structure" {
-        return Err(format!(
-            "Expected 'structure' as the first item, found: {}",
-            first_item
-        ));
+/// Reads `(path <layer> <aperture_width> x y x y ...)` into its layer, width and points.
+fn parse_path(
+    s_expr: &[SExpr],
+    ctx: &mut ParseContext,
+) -> Result<(String, Length, Vec<Point>), DsnError> {
+    ctx.expect_keyword(s_expr, "path")?;
+    let layer = ctx.atom(s_expr, 1, "path layer")?.to_string();
+    let width = ctx.length(s_expr, 2, "path aperture width")?;
+    let points = parse_points(s_expr, 3, "path point", ctx)?;
+    Ok((layer, width, points))
+}
+
+/// Reads the `x y` pairs from `start` to the end of the scope. A dangling
+/// coordinate at the end is an error rather than dropped.
+fn parse_points(
+    s_expr: &[SExpr],
+    start: usize,
+    field: &str,
+    ctx: &mut ParseContext,
+) -> Result<Vec<Point>, DsnError> {
+    let coordinates = s_expr.len().saturating_sub(start);
+    if !coordinates.is_multiple_of(2) {
+        return Err(ctx.error(DsnErrorKind::InvalidValue {
+            field: format!("{} coordinates", field),
+            value: format!("{} numbers", coordinates),
+        }));
+    }
+    let mut points: Vec<Point> = Vec::new();
+    for index in (start..s_expr.len()).step_by(2) {
+        points.push(ctx.point(s_expr, index, field)?);
     }
+    Ok(points)
+}
+
+fn parse_boundary(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<Boundary, DsnError> {
+    ctx.expect_keyword(s_expr, "boundary")?;
+    let second_item = s_expr.get(1).ok_or_else(|| ctx.missing("boundary path"))?;
+    let path = ctx.list(second_item)?;
+    let (layer, width, points) = parse_path(path, ctx)?;
+    Ok(Boundary {
+        layer,
+        width,
+        points,
+    })
+}
+
+fn parse_control(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<Control, DsnError> {
+    ctx.expect_keyword(s_expr, "control")?;
+    let mut control = Control {
+        off_grid: None,
+        via_at_smd: None,
+        unknown: Vec::new(),
+    };
+    for (position, item) in s_expr.iter().enumerate().skip(1) {
+        let Some(expr_list) = ctx.scope_item(&mut control.unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
+            "off_grid" => {
+                control.off_grid = Some(parse_on_off(
+                    ctx.atom(expr_list, 1, "off_grid")?,
+                    "off_grid",
+                    ctx,
+                )?);
+            }
+            "via_at_smd" => {
+                control.via_at_smd = Some(parse_on_off(
+                    ctx.atom(expr_list, 1, "via_at_smd")?,
+                    "via_at_smd",
+                    ctx,
+                )?);
+            }
+            _ => {
+                ctx.unknown_scope(&mut control.unknown, position, expr_list)?;
+            }
+        }
+    }
+    Ok(control)
+}
+
+fn parse_grid(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<Grid, DsnError> {
+    ctx.expect_keyword(s_expr, "grid")?;
+    let kind = match ctx.atom(s_expr, 1, "grid kind")? {
+        "via" => GridKind::Via,
+        "wire" => GridKind::Wire,
+        "via_keepout" => GridKind::ViaKeepout,
+        "place" => GridKind::Place,
+        "snap" => GridKind::Snap,
+        other => {
+            return Err(ctx.error(DsnErrorKind::InvalidValue {
+                field: "grid kind".to_string(),
+                value: other.to_string(),
+            }));
+        }
+    };
+    let mut grid = Grid {
+        kind,
+        step: ctx.length(s_expr, 2, "grid step")?,
+        direction: None,
+        offset: None,
+        unknown: Vec::new(),
+    };
+    for (position, item) in s_expr.iter().enumerate().skip(3) {
+        let Some(expr_list) = ctx.scope_item(&mut grid.unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
+            "direction" => {
+                grid.direction = match ctx.atom(expr_list, 1, "grid direction")? {
+                    "x" => Some(GridDirection::X),
+                    "y" => Some(GridDirection::Y),
+                    other => {
+                        return Err(ctx.error(DsnErrorKind::InvalidValue {
+                            field: "grid direction".to_string(),
+                            value: other.to_string(),
+                        }));
+                    }
+                };
+            }
+            "offset" => {
+                grid.offset = Some(ctx.length(expr_list, 1, "grid offset")?);
+            }
+            _ => {
+                ctx.unknown_scope(&mut grid.unknown, position, expr_list)?;
+            }
+        }
+    }
+    Ok(grid)
+}
+
+fn parse_layer_rule(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<LayerRule, DsnError> {
+    ctx.expect_keyword(s_expr, "layer_rule")?;
+    let mut rule = LayerRule {
+        layer: ctx.atom(s_expr, 1, "layer name")?.to_string(),
+        active: None,
+        preferred_direction: None,
+        preferred_direction_trace_costs: None,
+        against_preferred_direction_trace_costs: None,
+        unknown: Vec::new(),
+    };
+    for (position, item) in s_expr.iter().enumerate().skip(2) {
+        let Some(expr_list) = ctx.scope_item(&mut rule.unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
+            "active" => {
+                rule.active = Some(parse_on_off(
+                    ctx.atom(expr_list, 1, "active")?,
+                    "active",
+                    ctx,
+                )?);
+            }
+            "preferred_direction" => {
+                rule.preferred_direction = match ctx.atom(expr_list, 1, "preferred direction")? {
+                    "horizontal" => Some(PreferredDirection::Horizontal),
+                    "vertical" => Some(PreferredDirection::Vertical),
+                    other => {
+                        return Err(ctx.error(DsnErrorKind::InvalidValue {
+                            field: "preferred direction".to_string(),
+                            value: other.to_string(),
+                        }));
+                    }
+                };
+            }
+            "preferred_direction_trace_costs" => {
+                rule.preferred_direction_trace_costs =
+                    Some(ctx.number(expr_list, 1, "preferred direction trace costs")?);
+            }
+            "against_preferred_direction_trace_costs" => {
+                rule.against_preferred_direction_trace_costs =
+                    Some(ctx.number(expr_list, 1, "against preferred direction trace costs")?);
+            }
+            _ => {
+                ctx.unknown_scope(&mut rule.unknown, position, expr_list)?;
+            }
+        }
+    }
+    Ok(rule)
+}
+
+fn parse_autoroute_settings(
+    s_expr: &[SExpr],
+    ctx: &mut ParseContext,
+) -> Result<AutorouteSettings, DsnError> {
+    ctx.expect_keyword(s_expr, "autoroute_settings")?;
+    let mut settings = AutorouteSettings {
+        fanout: None,
+        autoroute: None,
+        postroute: None,
+        vias: None,
+        via_costs: None,
+        plane_via_costs: None,
+        start_ripup_costs: None,
+        start_pass_no: None,
+        layer_rules: Vec::new(),
+        unknown: Vec::new(),
+    };
+    for (position, item) in s_expr.iter().enumerate().skip(1) {
+        let Some(expr_list) = ctx.scope_item(&mut settings.unknown, position, item)? else {
+            continue;
+        };
+        let keyword = ctx.keyword(expr_list)?;
+        match keyword {
+            "fanout" | "autoroute" | "postroute" | "vias" => {
+                let value = Some(parse_on_off(
+                    ctx.atom(expr_list, 1, keyword)?,
+                    keyword,
+                    ctx,
+                )?);
+                match keyword {
+                    "fanout" => settings.fanout = value,
+                    "autoroute" => settings.autoroute = value,
+                    "postroute" => settings.postroute = value,
+                    _ => settings.vias = value,
+                }
+            }
+            "via_costs" => settings.via_costs = Some(ctx.number(expr_list, 1, keyword)?),
+            "plane_via_costs" => {
+                settings.plane_via_costs = Some(ctx.number(expr_list, 1, keyword)?)
+            }
+            "start_ripup_costs" => {
+                settings.start_ripup_costs = Some(ctx.number(expr_list, 1, keyword)?)
+            }
+            "start_pass_no" => {
+                settings.start_pass_no =
+                    Some(ctx.parse_number(ctx.atom(expr_list, 1, keyword)?, keyword)?);
+            }
+            "layer_rule" => {
+                let rule = ctx.scoped(expr_list, |ctx| parse_layer_rule(expr_list, ctx))?;
+                settings.layer_rules.push(rule);
+            }
+            _ => {
+                ctx.unknown_scope(&mut settings.unknown, position, expr_list)?;
+            }
+        }
+    }
+    Ok(settings)
+}
+
+fn parse_clearance(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<Clearance, DsnError> {
+    // (clearance <value> [(type <type> ...)])
+    let value = ctx.length(s_expr, 1, "clearance")?;
+    let mut types = Vec::new();
+    for item in s_expr.iter().skip(2) {
+        let type_list = ctx.list(item)?;
+        ctx.expect_keyword(type_list, "type")?;
+        for index in 1..type_list.len() {
+            types.push(ctx.atom(type_list, index, "clearance type")?.to_string());
+        }
+    }
+    Ok(Clearance { value, types })
+}
+
+fn parse_rule(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<Rule, DsnError> {
+    ctx.expect_keyword(s_expr, "rule")?;
+    let mut rule = Rule {
+        width: None,
+        clearances: Vec::new(),
+        unknown: Vec::new(),
+    };
+    for (position, item) in s_expr.iter().enumerate().skip(1) {
+        let Some(expr_list) = ctx.scope_item(&mut rule.unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
+            "width" => {
+                rule.width = Some(ctx.length(expr_list, 1, "width")?);
+            }
+            "clearance" => {
+                rule.clearances.push(parse_clearance(expr_list, ctx)?);
+            }
+            _ => {
+                ctx.unknown_scope(&mut rule.unknown, position, expr_list)?;
+            }
+        }
+    }
+    Ok(rule)
+}
+
+/// Keepouts are modelled for rect and polygon shapes, which carry their
+/// position in their coordinates.
+fn is_keepout_shape(shape: &[SExpr]) -> bool {
+    matches!(
+        shape.first().and_then(|x| x.as_atom()).map(|s| s.as_str()),
+        Some("rect") | Some("polygon")
+    )
+}
+
+fn parse_keepout(
+    s_expr: &[SExpr],
+    kind: KeepoutKind,
+    ctx: &mut ParseContext,
+) -> Result<Keepout, DsnError> {
+    // (keepout ["<name>"] <shape> ...)
+    let name = s_expr.get(1).and_then(|x| x.as_atom()).cloned();
+    let shape_index = if name.is_some() { 2 } else { 1 };
+    let shape_item = s_expr
+        .get(shape_index)
+        .ok_or_else(|| ctx.missing("keepout shape"))?;
+    let shape = parse_layer_shape(ctx.list(shape_item)?, ctx)?;
+    Ok(Keepout {
+        kind,
+        name,
+        shape,
+        // rules and the like are kept verbatim
+        unknown: keep_verbatim(s_expr, shape_index + 1),
+    })
+}
+
+fn parse_structure(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<Structure, DsnError> {
+    ctx.expect_keyword(s_expr, "structure")?;
     let mut layers: Vec<Layer> = Vec::new();
-    let mut boundary: Option<Boundary> = None;
-    for item in s_expr.iter().skip(1) {
-        let expr_list = item.as_list().ok_or(format!(
-            "Expected a list in the structure scope, found: {:?}",
-            item
-        ))?;
-        let first_item = expr_list
-            .first()
-            .ok_or("Expected at least one item in the structure item")?;
-        let first_item = first_item
-            .as_atom()
-            .ok_or("Expected an atom as the first item in the structure item")?;
-        match first_item.as_str() {
+    let mut boundaries: Vec<Boundary> = Vec::new();
+    let mut control: Option<Control> = None;
+    let mut grids: Vec<Grid> = Vec::new();
+    let mut autoroute_settings: Option<AutorouteSettings> = None;
+    let mut vias: Vec<String> = Vec::new();
+    let mut keepouts: Vec<Keepout> = Vec::new();
+    let mut rule: Option<Rule> = None;
+    let mut unknown: Vec<UnknownScope> = Vec::new();
+    for (position, item) in s_expr.iter().enumerate().skip(1) {
+        let Some(expr_list) = ctx.scope_item(&mut unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
             "layer" => {
-                let layer = parse_layer(expr_list)?;
+                let layer = ctx.scoped(expr_list, |ctx| parse_layer(expr_list, ctx))?;
                 layers.push(layer);
             }
             "boundary" => {
-                boundary = Some(parse_boundary(expr_list)?);
+                let boundary = ctx.scoped(expr_list, |ctx| parse_boundary(expr_list, ctx))?;
+                boundaries.push(boundary);
             }
-            "via" => {
-                continue;
+            "control" => {
+                control = Some(ctx.scoped(expr_list, |ctx| parse_control(expr_list, ctx))?);
+            }
+            "grid" => {
+                let grid = ctx.scoped(expr_list, |ctx| parse_grid(expr_list, ctx))?;
+                grids.push(grid);
+            }
+            "autoroute_settings" => {
+                autoroute_settings =
+                    Some(ctx.scoped(expr_list, |ctx| parse_autoroute_settings(expr_list, ctx))?);
             }
             "rule" => {
-                continue;
+                rule = Some(ctx.scoped(expr_list, |ctx| parse_rule(expr_list, ctx))?);
+            }
+            keyword @ ("keepout" | "via_keepout" | "wire_keepout") => {
+                let kind = match keyword {
+                    "keepout" => KeepoutKind::Keepout,
+                    "via_keepout" => KeepoutKind::ViaKeepout,
+                    _ => KeepoutKind::WireKeepout,
+                };
+                let shape_index = if expr_list.get(1).is_some_and(|x| x.is_atom()) {
+                    2
+                } else {
+                    1
+                };
+                let plain = expr_list
+                    .get(shape_index)
+                    .and_then(|x| x.as_list())
+                    .is_some_and(|shape| is_keepout_shape(shape));
+                if plain {
+                    let keepout =
+                        ctx.scoped(expr_list, |ctx| parse_keepout(expr_list, kind, ctx))?;
+                    keepouts.push(keepout);
+                } else {
+                    // other shapes are kept verbatim
+                    unknown.push(UnknownScope {
+                        position,
+                        expr: item.clone(),
+                    });
+                }
+            }
+            "via" => {
+                // (via <padstack> ...)
+                for index in 1..expr_list.len() {
+                    vias.push(ctx.atom(expr_list, index, "via padstack name")?.to_string());
+                }
             }
             _ => {
-                return Err(format!("Unknown structure item: {}", first_item));
+                ctx.unknown_scope(&mut unknown, position, expr_list)?;
             }
         }
     }
-    let boundary = boundary.ok_or("Expected a boundary in the structure scope")?;
-    Ok(Structure { layers, boundary })
+    if boundaries.is_empty() {
+        return Err(ctx.missing("boundary"));
+    }
+    Ok(Structure {
+        layers,
+        boundaries,
+        control,
+        grids,
+        autoroute_settings,
+        vias,
+        keepouts,
+        rule,
+        unknown,
+    })
 }
 
-fn parse_placement(s_expr: &Vec<SExpr>) -> Result<Placement, String> {
-    // Placeholder for placement parsing logic
-    // This function should parse the placement part of the S-expression
-    // and populate the DsnStruct accordingly.
-    let first_item = s_expr
-        .first()
-        .ok_or("Expected at least one item in the placement scope")?;
-    let first_item = first_item
-        .as_atom()
-        .ok_or("Expected an atom as the first item in the placement scope")?;
-    if first_item != "placement" {
-        return Err(format!(
-            "Expected 'placement' as the first item, found: {}",
-            first_item
-        ));
+fn parse_side(atom: &str, ctx: &ParseContext) -> Result<Side, DsnError> {
+    match atom {
+        "front" => Ok(Side::Front),
+        "back" => Ok(Side::Back),
+        other => Err(ctx.error(DsnErrorKind::InvalidValue {
+            field: "side".to_string(),
+            value: other.to_string(),
+        })),
     }
+}
+
+fn parse_place(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<ComponentInst, DsnError> {
+    ctx.expect_keyword(s_expr, "place")?;
+    let reference = ctx.atom(s_expr, 1, "component reference")?.to_string();
+    let position = ctx.point(s_expr, 2, "position")?;
+    let side = parse_side(ctx.atom(s_expr, 4, "side")?, ctx)?;
+    let rotation = ctx.number(s_expr, 5, "rotation")?;
+
+    // Properties such as (PN ...) are kept verbatim
+    Ok(ComponentInst {
+        reference,
+        position,
+        side,
+        rotation,
+        unknown: keep_verbatim(s_expr, 6),
+    })
+}
+
+pub(crate) fn parse_component(
+    s_expr: &[SExpr],
+    ctx: &mut ParseContext,
+) -> Result<Component, DsnError> {
+    ctx.expect_keyword(s_expr, "component")?;
+    let name = ctx.atom(s_expr, 1, "component image name")?.to_string();
+    let mut instances = Vec::new();
+    for place_expr in s_expr.iter().skip(2) {
+        let place_list = ctx.list(place_expr)?;
+        let instance = ctx.scoped(place_list, |ctx| parse_place(place_list, ctx))?;
+        instances.push(instance);
+    }
+    Ok(Component { name, instances })
+}
+
+fn parse_placement(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<Placement, DsnError> {
+    ctx.expect_keyword(s_expr, "placement")?;
     let mut components: Vec<Component> = Vec::new();
-    for item in s_expr.iter().skip(1) {
-        let expr_list = item.as_list().ok_or(format!(
-            "Expected a list in the placement scope, found: {:?}",
-            item
-        ))?;
-        let first_item = expr_list
-            .first()
-            .ok_or("Expected at least one item in the placement item")?;
-        let first_item = first_item
-            .as_atom()
-            .ok_or("Expected an atom as the first item in the placement item")?;
-        if first_item != "component" {
-            return Err(format!(
-                "Expected 'component' as the first item in the placement item, found: {}",
-                first_item
-            ));
-        }
-
-        let component_name = expr_list
-            .get(1)
-            .ok_or("Expected component name")?
-            .as_atom()
-            .ok_or("Expected component name to be an atom")?
-            .to_string();
-
-        let mut instances = Vec::new();
-
-        for place_expr in expr_list.iter().skip(2) {
-            let place_list = place_expr
-                .as_list()
-                .ok_or(format!("Expected place list, found: {:?}", place_expr))?;
-
-            let first_place_item = place_list
-                .first()
-                .ok_or("Expected at least one item in place list")?
-                .as_atom()
-                .ok_or("Expected 'place' as first item in place list")?;
-
-            if first_place_item != "place" {
-                return Err(format!(
-                    "Expected 'place' as first item in place list, found: {}",
-                    first_place_item
-                ));
-            }
-
-            // Parse place instance details
-            let reference = place_list
-                .get(1)
-                .ok_or("Expected reference in place list")?
-                .as_atom()
-                .ok_or("Expected reference to be an atom")?
-                .to_string();
-
-            let x_pos = place_list
-                .get(2)
-                .ok_or("Expected x position in place list")?
-                .as_atom()
-                .ok_or("Expected x position to be an atom")?
-                .parse::<f64>()
-                .map_err(|e| format!("Failed to parse x position: {}", e))?;
-
-            let y_pos = place_list
-                .get(3)
-                .ok_or("Expected y position in place list")?
-                .as_atom()
-                .ok_or("Expected y position to be an atom")?
-                .parse::<f64>()
-                .map_err(|e| format!("Failed to parse y position: {}", e))?;
-
-            let rotation = place_list
-                .get(5)
-                .ok_or("Expected rotation in place list")?
-                .as_atom()
-                .ok_or("Expected rotation to be an atom")?
-                .parse::<f64>()
-                .map_err(|e| format!("Failed to parse rotation: {}", e))?;
-
-            // Create the component instance
-            let instance = ComponentInst {
-                reference,
-                position: (x_pos, y_pos),
-                rotation,
-            };
-            instances.push(instance);
+    let mut unknown: Vec<UnknownScope> = Vec::new();
+    for (position, item) in s_expr.iter().enumerate().skip(1) {
+        let Some(expr_list) = ctx.scope_item(&mut unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
+            "component" => {
+                let component = ctx.scoped(expr_list, |ctx| parse_component(expr_list, ctx))?;
+                components.push(component);
+            }
+            _ => {
+                ctx.unknown_scope(&mut unknown, position, expr_list)?;
+            }
         }
+    }
+
+    Ok(Placement {
+        components,
+        unknown,
+    })
+}
+
+/// Index of the pin id in a `(pin ...)` scope, which an optional
+/// `(rotate <angle>)` after the padstack name pushes back by one.
+fn pin_id_index(s_expr: &[SExpr]) -> usize {
+    let rotated = s_expr
+        .get(2)
+        .and_then(|x| x.as_list())
+        .and_then(|list| list.first())
+        .and_then(|x| x.as_atom())
+        .is_some_and(|keyword| keyword == "rotate");
+    if rotated { 3 } else { 2 }
+}
 
-        let component = Component {
-            name: component_name,
-            instances,
+fn parse_pin(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<Pin, DsnError> {
+    // (pin <padstack> [(rotate <angle>)] <pin id> x y [(clearance_class <class>)])
+    let pad_stack_name = ctx.atom(s_expr, 1, "pin padstack name")?.to_string();
+    let id_index = pin_id_index(s_expr);
+    let rotation = if id_index == 3 {
+        let rotate = ctx.list(&s_expr[2])?;
+        ctx.number(rotate, 1, "pin rotation")?
+    } else {
+        0.0
+    };
+    let pin_id = ctx.atom(s_expr, id_index, "pin id")?.to_string();
+    let position = ctx.point(s_expr, id_index + 1, "pin position")?;
+    let mut clearance_class = None;
+    let mut unknown: Vec<UnknownScope> = Vec::new();
+    for (position, item) in s_expr.iter().enumerate().skip(id_index + 3) {
+        let Some(expr_list) = ctx.scope_item(&mut unknown, position, item)? else {
+            continue;
         };
-        components.push(component);
-    }
-
-    Ok(Placement { components })
-}
-
-fn parse_image(s_expr: &Vec<SExpr>) -> Result<Image, String> {
-    // Placeholder for image parsing logic
-    let first_item = s_expr
-        .first()
-        .ok_or("Expected at least one item in the image scope")?;
-    let first_item = first_item
-        .as_atom()
-        .ok_or("Expected an atom as the first item in the image scope")?;
-    if first_item != "image" {
-        return Err(format!(
-            "Expected 'image' as the first item, found: {}",
-            first_item
-        ));
-    }
-    let image_name = s_expr
-        .get(1)
-        .ok_or("Expected image name as the second item")?
-        .as_atom()
-        .ok_or("Expected image name to be an atom")?
-        .to_string();
-
-    let mut pins: HashMap<usize, Pin> = HashMap::new();
-    for item in s_expr.iter().skip(2) {
-        let expr_list = item.as_list().ok_or(format!(
-            "Expected a list in the structure scope, found: {:?}",
-            item
-        ))?;
-        let first_item = expr_list
-            .first()
-            .ok_or("Expected at least one item in the structure item")?;
-        let first_item = first_item
-            .as_atom()
-            .ok_or("Expected an atom as the first item in the structure item")?;
-        match first_item.as_str() {
+        match ctx.keyword(expr_list)? {
+            "clearance_class" => {
+                clearance_class = Some(ctx.atom(expr_list, 1, "clearance class")?.to_string());
+            }
+            _ => {
+                ctx.unknown_scope(&mut unknown, position, expr_list)?;
+            }
+        }
+    }
+    Ok(Pin {
+        pad_stack_name,
+        pin_id,
+        rotation,
+        position,
+        clearance_class,
+        unknown,
+    })
+}
+
+fn parse_image(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<Image, DsnError> {
+    ctx.expect_keyword(s_expr, "image")?;
+    let image_name = ctx.atom(s_expr, 1, "image name")?.to_string();
+
+    let mut pins: HashMap<String, Pin> = HashMap::new();
+    let mut clearance_class = None;
+    let mut unknown: Vec<UnknownScope> = Vec::new();
+    for (position, item) in s_expr.iter().enumerate().skip(2) {
+        let Some(expr_list) = ctx.scope_item(&mut unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
             "outline" => {
-                continue;
+                unknown.push(UnknownScope {
+                    position,
+                    expr: item.clone(),
+                });
             }
             "pin" => {
-                let pad_stack_name = expr_list[1]
-                    .as_atom()
-                    .ok_or("Pad stack name must be an atom")?
-                    .to_string();
-
-                let pin_number = expr_list[2]
-                    .as_atom()
-                    .ok_or("Pin number must be an atom")?
-                    .parse::<usize>()
-                    .map_err(|e| format!("Invalid pin number: {}", e))?;
-
-                let x = expr_list[3]
-                    .as_atom()
-                    .ok_or("X coordinate must be a number")?
-                    .parse::<f64>()
-                    .map_err(|e| format!("Invalid x coordinate: {}", e))?;
-
-                let y = expr_list[4]
-                    .as_atom()
-                    .ok_or("Y coordinate must be a number")?
-                    .parse::<f64>()
-                    .map_err(|e| format!("Invalid y coordinate: {}", e))?;
-
-                pins.insert(
-                    pin_number,
-                    Pin {
-                        pad_stack_name,
-                        pin_number,
-                        position: (x, y),
-                    },
-                );
+                // pins are named by their id, not by their padstack
+                let segment = match expr_list
+                    .get(pin_id_index(expr_list))
+                    .and_then(|x| x.as_atom())
+                {
+                    Some(pin_id) => format!("pin {}", pin_id),
+                    None => "pin".to_string(),
+                };
+                let pin = ctx.scoped_as(segment, |ctx| parse_pin(expr_list, ctx))?;
+                pins.insert(pin.pin_id.clone(), pin);
+            }
+            "clearance_class" => {
+                clearance_class = Some(ctx.atom(expr_list, 1, "clearance class")?.to_string());
             }
             _ => {
-                return Err(format!("Unknown image item: {}", first_item));
+                ctx.unknown_scope(&mut unknown, position, expr_list)?;
             }
         }
     }
@@ -331,371 +838,296 @@ fn parse_image(s_expr: &Vec<SExpr>) -> Result<Image, String> {
     Ok(Image {
         name: image_name,
         pins,
+        clearance_class,
+        unknown,
     })
 }
 
-fn parse_shape(s_expr: &Vec<SExpr>) -> Result<Shape, String> {
-    let shape_type = s_expr
-        .get(1)
-        .ok_or("Missing shape type")?
-        .as_list()
-        .ok_or("Shape type must be a list")?;
+fn parse_shape(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<LayerShape, DsnError> {
+    let second_item = s_expr.get(1).ok_or_else(|| ctx.missing("shape"))?;
+    let shape_type = ctx.list(second_item)?;
+    parse_layer_shape(shape_type, ctx)
+}
+
+fn is_modelled_shape(keyword: &str) -> bool {
+    matches!(keyword, "circle" | "rect" | "polygon")
+}
 
-    let first_item = shape_type
-        .first()
-        .ok_or("Empty shape definition")?
-        .as_atom()
-        .ok_or("Shape type must be an atom")?;
+/// Reads a `(circle ...)`, `(rect ...)` or `(polygon ...)` shape.
+fn parse_layer_shape(shape_type: &[SExpr], ctx: &mut ParseContext) -> Result<LayerShape, DsnError> {
+    let layer = ctx.atom(shape_type, 1, "shape layer")?.to_string();
 
-    match first_item.as_str() {
+    let shape = match ctx.keyword(shape_type)? {
         "circle" => {
-            // (shape (circle F.Cu diameter))
-            let diameter = shape_type[2]
-                .as_atom()
-                .ok_or("Circle diameter must be a number")?
-                .parse::<f64>()
-                .map_err(|e| format!("Invalid circle diameter: {}", e))?;
-
-            Ok(Shape::Circle { diameter })
+            // (shape (circle F.Cu diameter [x y]))
+            let diameter = ctx.length(shape_type, 2, "circle diameter")?;
+            let center = if shape_type.len() > 3 {
+                ctx.point(shape_type, 3, "circle centre")?
+            } else {
+                Point::ORIGIN
+            };
+            Shape::Circle { diameter, center }
         }
         "rect" => {
             // (shape (rect F.Cu x_min y_min x_max y_max))
-            if shape_type.len() < 6 {
-                return Err("Rect requires 4 coordinates".into());
-            }
-            let x_min = shape_type[2]
-                .as_atom()
-                .ok_or("Rect x_min must be a number")?
-                .parse::<f64>()
-                .map_err(|e| format!("Invalid x_min: {}", e))?;
-            let y_min = shape_type[3]
-                .as_atom()
-                .ok_or("Rect y_min must be a number")?
-                .parse::<f64>()
-                .map_err(|e| format!("Invalid y_min: {}", e))?;
-            let x_max = shape_type[4]
-                .as_atom()
-                .ok_or("Rect x_max must be a number")?
-                .parse::<f64>()
-                .map_err(|e| format!("Invalid x_max: {}", e))?;
-            let y_max = shape_type[5]
-                .as_atom()
-                .ok_or("Rect y_max must be a number")?
-                .parse::<f64>()
-                .map_err(|e| format!("Invalid y_max: {}", e))?;
-
-            Ok(Shape::Rect {
-                x_min,
-                y_min,
-                x_max,
-                y_max,
-            })
+            let min = ctx.point(shape_type, 2, "rect lower left corner")?;
+            let max = ctx.point(shape_type, 4, "rect upper right corner")?;
+            Shape::Rect {
+                x_min: min.x,
+                y_min: min.y,
+                x_max: max.x,
+                y_max: max.y,
+            }
         }
         "polygon" => {
             // (shape (polygon F.Cu aperture_width vertices...))
-            if shape_type.len() < 4 {
-                return Err("Polygon requires aperture width and vertices".into());
-            }
-            let aperture_width = shape_type[2]
-                .as_atom()
-                .ok_or("Aperture width must be a number")?
-                .parse::<f64>()
-                .map_err(|e| format!("Invalid aperture width: {}", e))?;
-
-            let mut vertices = Vec::new();
-            for i in (3..shape_type.len()).step_by(2) {
-                if i + 1 >= shape_type.len() {
-                    break;
-                }
-                let x = shape_type[i]
-                    .as_atom()
-                    .ok_or("Vertex x must be a number")?
-                    .parse::<f64>()
-                    .map_err(|e| format!("Invalid vertex x: {}", e))?;
-                let y = shape_type[i + 1]
-                    .as_atom()
-                    .ok_or("Vertex y must be a number")?
-                    .parse::<f64>()
-                    .map_err(|e| format!("Invalid vertex y: {}", e))?;
-                vertices.push((x, y));
-            }
-
-            Ok(Shape::Polygon {
+            let aperture_width = ctx.length(shape_type, 2, "polygon aperture width")?;
+            let vertices = parse_points(shape_type, 3, "polygon vertex", ctx)?;
+            if vertices.is_empty() {
+                return Err(ctx.missing("polygon vertices"));
+            }
+
+            Shape::Polygon {
                 aperture_width,
                 vertices,
-            })
+            }
         }
-        _ => Err(format!("Unknown shape type: {}", first_item)),
-    }
-}
-
-fn parse_padstack(s_expr: &Vec<SExpr>) -> Result<PadStack, String> {
-    // Placeholder for padstack parsing logic
-    let first_item = s_expr
-        .first()
-        .ok_or("Expected at least one item in the padstack scope")?;
-    let first_item = first_item
-        .as_atom()
-        .ok_or("Expected an atom as the first item in the padstack scope")?;
-    if first_item != "padstack" {
-        return Err(format!(
-            "Expected 'padstack' as the first item, found: {}",
-            first_item
-        ));
-    }
-    let padstack_name = s_expr
-        .get(1)
-        .ok_or("Expected padstack name as the second item")?
-        .as_atom()
-        .ok_or("Expected padstack name to be an atom")?
-        .to_string();
-
-    let mut shapes = None;
-    let mut shape_num = 0;
-    for item in s_expr.iter().skip(2) {
-        let expr_list = item.as_list().ok_or(format!(
-            "Expected a list in the padstack scope, found: {:?}",
-            item
-        ))?;
-        let first_item = expr_list
-            .first()
-            .ok_or("Expected at least one item in the padstack item")?;
-        let first_item = first_item
-            .as_atom()
-            .ok_or("Expected an atom as the first item in the padstack item")?;
-        match first_item.as_str() {
+        other => {
+            return Err(ctx.error(DsnErrorKind::UnknownKeyword {
+                keyword: other.to_string(),
+            }));
+        }
+    };
+    Ok(LayerShape { layer, shape })
+}
+
+pub(crate) fn parse_padstack(
+    s_expr: &[SExpr],
+    ctx: &mut ParseContext,
+) -> Result<PadStack, DsnError> {
+    ctx.expect_keyword(s_expr, "padstack")?;
+    let padstack_name = ctx.atom(s_expr, 1, "padstack name")?.to_string();
+
+    let mut shapes = Vec::new();
+    let mut attach = None;
+    let mut unknown: Vec<UnknownScope> = Vec::new();
+    let mut unknown_shapes = false;
+    for (position, item) in s_expr.iter().enumerate().skip(2) {
+        let Some(expr_list) = ctx.scope_item(&mut unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
             "shape" => {
-                shape_num += 1;
-                if shapes.is_none() {
-                    shapes = Some(parse_shape(expr_list)?);
+                let shape_keyword = expr_list
+                    .get(1)
+                    .and_then(|x| x.as_list())
+                    .and_then(|shape| shape.first())
+                    .and_then(|x| x.as_atom());
+                match shape_keyword {
+                    // e.g. the (path ...) KiCad writes for oval pads
+                    Some(keyword) if !is_modelled_shape(keyword) => {
+                        let kind = DsnErrorKind::UnknownKeyword {
+                            keyword: keyword.to_string(),
+                        };
+                        ctx.scoped(expr_list, |ctx| {
+                            let location = ctx.path.clone();
+                            ctx.skip(&mut unknown, position, location, kind, item)
+                        })?;
+                        unknown_shapes = true;
+                    }
+                    _ => {
+                        shapes.push(ctx.scoped(expr_list, |ctx| parse_shape(expr_list, ctx))?);
+                    }
                 }
             }
             "attach" => {
-                continue;
+                attach = Some(parse_on_off(
+                    ctx.atom(expr_list, 1, "attach")?,
+                    "attach",
+                    ctx,
+                )?);
             }
             _ => {
-                return Err(format!("Unknown padstack item: {}", first_item));
+                ctx.unknown_scope(&mut unknown, position, expr_list)?;
             }
         }
     }
-    let shape = shapes.ok_or("Padstack must have at least one shape")?;
-    let through_hole = shape_num > 1;
+    if shapes.is_empty() && !unknown_shapes {
+        return Err(ctx.missing("padstack shape"));
+    }
     Ok(PadStack {
         name: padstack_name,
-        shape,
-        through_hole,
+        shapes,
+        attach,
+        unknown,
     })
 }
 
-fn parse_library(s_expr: &Vec<SExpr>) -> Result<Library, String> {
-    // Placeholder for library parsing logic
-    // This function should parse the library part of the S-expression
-    // and populate the DsnStruct accordingly.
-    let first_item = s_expr
-        .first()
-        .ok_or("Expected at least one item in the library scope")?;
-    let first_item = first_item
-        .as_atom()
-        .ok_or("Expected an atom as the first item in the library scope")?;
-    if first_item != "library" {
-        return Err(format!(
-            "Expected 'library' as the first item, found: {}",
-            first_item
-        ));
-    }
+fn parse_library(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<Library, DsnError> {
+    ctx.expect_keyword(s_expr, "library")?;
 
     let mut images: HashMap<String, Image> = HashMap::new();
     let mut pad_stacks: HashMap<String, PadStack> = HashMap::new();
+    let mut unknown: Vec<UnknownScope> = Vec::new();
 
-    for item in s_expr.iter().skip(1) {
-        let expr_list = item.as_list().ok_or(format!(
-            "Expected a list in the library scope, found: {:?}",
-            item
-        ))?;
-        let first_item = expr_list
-            .first()
-            .ok_or("Expected at least one item in the library item")?;
-        let first_item = first_item
-            .as_atom()
-            .ok_or("Expected an atom as the first item in the library item")?;
-        match first_item.as_str() {
+    for (position, item) in s_expr.iter().enumerate().skip(1) {
+        let Some(expr_list) = ctx.scope_item(&mut unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
             "image" => {
-                let image = parse_image(expr_list)?;
+                let image = ctx.scoped(expr_list, |ctx| parse_image(expr_list, ctx))?;
                 images.insert(image.name.clone(), image);
             }
             "padstack" => {
-                let padstack = parse_padstack(expr_list)?;
+                let padstack = ctx.scoped(expr_list, |ctx| parse_padstack(expr_list, ctx))?;
                 pad_stacks.insert(padstack.name.clone(), padstack);
             }
             _ => {
-                return Err(format!("Unknown library item: {}", first_item));
+                ctx.unknown_scope(&mut unknown, position, expr_list)?;
             }
         }
     }
-    Ok(Library { images, pad_stacks })
+    Ok(Library {
+        images,
+        pad_stacks,
+        unknown,
+    })
 }
 
-fn parse_net(s_expr: &Vec<SExpr>) -> Result<Net, String> {
-    let first_item = s_expr
-        .first()
-        .ok_or("Expected at least one item in the net scope")?;
-    let first_item = first_item
-        .as_atom()
-        .ok_or("Expected an atom as the first item in the net scope")?;
-    if first_item != "net" {
-        return Err(format!(
-            "Expected 'net' as the first item, found: {}",
-            first_item
-        ));
-    }
-
-    let net_name = s_expr
-        .get(1)
-        .ok_or("Expected net name as the second item")?
-        .as_atom()
-        .ok_or("Expected net name to be an atom")?
-        .to_string();
-
-    let pins_list = match &s_expr[2] {
-        SExpr::List(list) => list,
-        _ => return Err("Pins must be a list".to_string()),
+/// Splits a pin reference such as `U1-5` or `U2-A-1` into component and pin
+/// id. Both may contain `-`, so the split is at the last `-` that leaves a
+/// placed component name; when none does, at the first.
+pub(crate) fn parse_pin_reference(pin_str: &str, ctx: &ParseContext) -> Result<Pin2, DsnError> {
+    let invalid = || {
+        ctx.error(DsnErrorKind::InvalidValue {
+            field: "pin reference".to_string(),
+            value: pin_str.to_string(),
+        })
     };
-    if pins_list.is_empty() {
-        return Err("Empty pins list".to_string());
+    let (component_name, pin_id) = pin_str
+        .rmatch_indices('-')
+        .map(|(index, _)| (&pin_str[..index], &pin_str[index + 1..]))
+        .find(|(component_name, _)| ctx.components.contains(*component_name))
+        .or_else(|| pin_str.split_once('-'))
+        .ok_or_else(invalid)?;
+    if component_name.is_empty() || pin_id.is_empty() {
+        return Err(invalid());
     }
-    let pins_head = match &pins_list[0] {
-        SExpr::Atom(head) => head,
-        _ => return Err("Pins list must start with 'pins'".to_string()),
-    };
-    if pins_head != "pins" {
-        return Err(format!("Expected 'pins', got '{}'", pins_head));
+    Ok(Pin2 {
+        component_name: component_name.to_string(),
+        pin_id: pin_id.to_string(),
+    })
+}
+
+/// Reference names of the components in a placement scope, without parsing
+/// it; errors in the placement are reported when it is parsed.
+pub(crate) fn placed_components(s_expr: &[SExpr]) -> HashSet<String> {
+    fn lists<'a>(s_expr: &'a [SExpr], keyword: &str) -> impl Iterator<Item = &'a [SExpr]> {
+        s_expr
+            .iter()
+            .filter_map(|expr| expr.as_list())
+            .filter(move |list| {
+                list.first().and_then(|x| x.as_atom()).map(|s| s.as_str()) == Some(keyword)
+            })
+            .map(|list| list.as_slice())
     }
+    lists(s_expr, "component")
+        .flat_map(|component| lists(component, "place"))
+        .filter_map(|place| place.get(1).and_then(|x| x.as_atom()).cloned())
+        .collect()
+}
 
-    let mut pins: Vec<Pin2> = Vec::new();
-    for pin_expr in pins_list.iter().skip(1) {
-        let pin_str = pin_expr.as_atom().ok_or(format!(
-            "Expected pin as atom (e.g. 'U1-5'), found: {:?}",
-            pin_expr
-        ))?;
+fn parse_net(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<Net, DsnError> {
+    ctx.expect_keyword(s_expr, "net")?;
+    let net_name = ctx.atom(s_expr, 1, "net name")?.to_string();
 
-        // Split the pin string into component name and pin number
-        let parts: Vec<&str> = pin_str.split('-').collect();
-        if parts.len() != 2 {
-            return Err(format!(
-                "Invalid pin format: expected 'COMPONENT-PINNUM', got '{}'",
-                pin_str
-            ));
-        }
+    let pins_item = s_expr.get(2).ok_or_else(|| ctx.missing("pins"))?;
+    let pins_list = ctx.list(pins_item)?;
+    ctx.expect_keyword(pins_list, "pins")?;
 
-        let component_name = parts[0].to_string();
-        let pin_number = parts[1]
-            .parse::<usize>()
-            .map_err(|e| format!("Invalid pin number in '{}': {}", pin_str, e))?;
+    let mut pins: Vec<Pin2> = Vec::new();
+    for index in 1..pins_list.len() {
+        let pin_str = ctx.atom(pins_list, index, "pin reference")?;
+        pins.push(parse_pin_reference(pin_str, ctx)?);
+    }
 
-        pins.push(Pin2 {
-            component_name,
-            pin_number,
-        });
+    let mut rule: Option<Rule> = None;
+    let mut unknown: Vec<UnknownScope> = Vec::new();
+    for (position, item) in s_expr.iter().enumerate().skip(3) {
+        let Some(expr_list) = ctx.scope_item(&mut unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
+            "rule" => {
+                rule = Some(ctx.scoped(expr_list, |ctx| parse_rule(expr_list, ctx))?);
+            }
+            _ => {
+                ctx.unknown_scope(&mut unknown, position, expr_list)?;
+            }
+        }
     }
 
     Ok(Net {
         name: net_name,
         pins,
+        rule,
+        unknown,
     })
 }
 
-fn parse_netclass(s_expr: &Vec<SExpr>) -> Result<Netclass, String> {
-    let first_item = s_expr
-        .first()
-        .ok_or("Expected at least one item in the netclass scope")?;
-    let first_item = first_item
-        .as_atom()
-        .ok_or("Expected an atom as the first item in the netclass scope")?;
-    if first_item != "class" {
-        return Err(format!(
-            "Expected 'class' as the first item, found: {}",
-            first_item
-        ));
-    }
-
-    let net_class_name = s_expr
-        .get(1)
-        .ok_or("Expected net class name as the second item")?
-        .as_atom()
-        .ok_or("Expected net class name to be an atom")?
-        .to_string();
-
-    let mut net_names: Vec<String> = Vec::new();
-    let mut current_pos = 2;
-    while current_pos < s_expr.len() {
-        match s_expr.get(current_pos) {
-            Some(SExpr::Atom(name)) => {
-                net_names.push(name.to_string());
-                current_pos += 1;
+fn parse_circuit(
+    s_expr: &[SExpr],
+    via_name: &mut String,
+    ctx: &mut ParseContext,
+) -> Result<Vec<UnknownScope>, DsnError> {
+    let mut unknown: Vec<UnknownScope> = Vec::new();
+    for (position, item) in s_expr.iter().enumerate().skip(1) {
+        let Some(expr_list) = ctx.scope_item(&mut unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
+            "use_via" => {
+                *via_name = ctx.atom(expr_list, 1, "via name")?.to_string();
+            }
+            _ => {
+                ctx.unknown_scope(&mut unknown, position, expr_list)?;
             }
-            Some(SExpr::List(_)) => break,
-            None => break,
-            _ => return Err("Unexpected non-atom in net names".into()),
         }
     }
+    Ok(unknown)
+}
+
+fn parse_netclass(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<Netclass, DsnError> {
+    ctx.expect_keyword(s_expr, "class")?;
+    let net_class_name = ctx.atom(s_expr, 1, "net class name")?.to_string();
+
+    let net_names: Vec<String> = s_expr
+        .iter()
+        .skip(2)
+        .map_while(|x| x.as_atom())
+        .cloned()
+        .collect();
 
     let mut via_name = String::new();
-    let mut width = 0.0;
-    let mut clearance = 0.0;
-    for item in s_expr.iter().skip(current_pos) {
-        if let SExpr::List(list) = item {
-            match list.first().and_then(|x| x.as_atom()).map(|s| s.as_str()) {
-                Some("circuit") => {
-                    if let Some(use_via) = list.get(1) {
-                        if let Some(use_via_list) = use_via.as_list() {
-                            if use_via_list
-                                .first()
-                                .and_then(|x| x.as_atom())
-                                .map(|s| s.as_str())
-                                == Some("use_via")
-                            {
-                                via_name = use_via_list
-                                    .get(1)
-                                    .ok_or("Missing via name in use_via")?
-                                    .as_atom()
-                                    .ok_or("Via name must be an atom")?
-                                    .to_string();
-                            }
-                        }
-                    }
-                }
-                Some("rule") => {
-                    for rule_item in list.iter().skip(1) {
-                        if let Some(rule_list) = rule_item.as_list() {
-                            match rule_list
-                                .first()
-                                .and_then(|x| x.as_atom())
-                                .map(|s| s.as_str())
-                            {
-                                Some("width") => {
-                                    width = rule_list
-                                        .get(1)
-                                        .ok_or("Missing width value")?
-                                        .as_atom()
-                                        .ok_or("Width must be a number")?
-                                        .parse::<f64>()
-                                        .map_err(|e| format!("Invalid width: {}", e))?;
-                                }
-                                Some("clearance") => {
-                                    clearance = rule_list
-                                        .get(1)
-                                        .ok_or("Missing clearance value")?
-                                        .as_atom()
-                                        .ok_or("Clearance must be a number")?
-                                        .parse::<f64>()
-                                        .map_err(|e| format!("Invalid clearance: {}", e))?;
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
-                }
-                _ => {}
+    let mut circuit_unknown: Vec<UnknownScope> = Vec::new();
+    let mut rule: Option<Rule> = None;
+    let mut unknown: Vec<UnknownScope> = Vec::new();
+    for (position, item) in s_expr.iter().enumerate().skip(2 + net_names.len()) {
+        let Some(expr_list) = ctx.scope_item(&mut unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
+            "circuit" => {
+                circuit_unknown = ctx.scoped(expr_list, |ctx| {
+                    parse_circuit(expr_list, &mut via_name, ctx)
+                })?;
+            }
+            "rule" => {
+                rule = Some(ctx.scoped(expr_list, |ctx| parse_rule(expr_list, ctx))?);
+            }
+            _ => {
+                ctx.unknown_scope(&mut unknown, position, expr_list)?;
             }
         }
     }
@@ -704,132 +1136,371 @@ fn parse_netclass(s_expr: &Vec<SExpr>) -> Result<Netclass, String> {
         net_class_name,
         net_names,
         via_name,
-        width,
-        clearance,
+        circuit_unknown,
+        rule,
+        unknown,
     })
 }
 
-fn parse_network(s_expr: &Vec<SExpr>) -> Result<Network, String> {
-    // Placeholder for network parsing logic
-    // This function should parse the network part of the S-expression
-    // and populate the DsnStruct accordingly.
-
-    let first_item = s_expr
-        .first()
-        .ok_or("Expected at least one item in the network scope")?;
-    let first_item = first_item
-        .as_atom()
-        .ok_or("Expected an atom as the first item in the network scope")?;
-    if first_item != "network" {
-        return Err(format!(
-            "Expected 'network' as the first item, found: {}",
-            first_item
-        ));
+fn parse_class_class(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<ClassClass, DsnError> {
+    ctx.expect_keyword(s_expr, "class_class")?;
+    let classes_item = s_expr.get(1).ok_or_else(|| ctx.missing("classes"))?;
+    let classes_list = ctx.list(classes_item)?;
+    ctx.expect_keyword(classes_list, "classes")?;
+    let mut classes = Vec::new();
+    for index in 1..classes_list.len() {
+        classes.push(ctx.atom(classes_list, index, "class name")?.to_string());
+    }
+    let mut rule: Option<Rule> = None;
+    let mut unknown: Vec<UnknownScope> = Vec::new();
+    for (position, item) in s_expr.iter().enumerate().skip(2) {
+        let Some(expr_list) = ctx.scope_item(&mut unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
+            "rule" => {
+                rule = Some(ctx.scoped(expr_list, |ctx| parse_rule(expr_list, ctx))?);
+            }
+            _ => {
+                ctx.unknown_scope(&mut unknown, position, expr_list)?;
+            }
+        }
     }
+    Ok(ClassClass {
+        classes,
+        rule: rule.ok_or_else(|| ctx.missing("rule"))?,
+        unknown,
+    })
+}
+
+fn parse_network(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<Network, DsnError> {
+    ctx.expect_keyword(s_expr, "network")?;
 
     let mut nets: Vec<Net> = Vec::new();
     let mut netclasses: HashMap<String, Netclass> = HashMap::new();
+    let mut class_classes: Vec<ClassClass> = Vec::new();
+    let mut unknown: Vec<UnknownScope> = Vec::new();
 
-    for item in s_expr.iter().skip(1) {
-        let expr_list = item.as_list().ok_or(format!(
-            "Expected a list in the network scope, found: {:?}",
-            item
-        ))?;
-        let first_item = expr_list
-            .first()
-            .ok_or("Expected at least one item in the network item")?;
-        let first_item = first_item
-            .as_atom()
-            .ok_or("Expected an atom as the first item in the network item")?;
-        match first_item.as_str() {
+    for (position, item) in s_expr.iter().enumerate().skip(1) {
+        let Some(expr_list) = ctx.scope_item(&mut unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
             "net" => {
-                let net = parse_net(expr_list)?;
+                let net = ctx.scoped(expr_list, |ctx| parse_net(expr_list, ctx))?;
                 nets.push(net);
             }
             "class" => {
-                let netclass = parse_netclass(expr_list)?;
+                let netclass = ctx.scoped(expr_list, |ctx| parse_netclass(expr_list, ctx))?;
                 netclasses.insert(netclass.net_class_name.clone(), netclass);
             }
+            "class_class" => {
+                let class_class = ctx.scoped(expr_list, |ctx| parse_class_class(expr_list, ctx))?;
+                class_classes.push(class_class);
+            }
+            _ => {
+                ctx.unknown_scope(&mut unknown, position, expr_list)?;
+            }
+        }
+    }
+
+    Ok(Network {
+        nets,
+        netclasses,
+        class_classes,
+        unknown,
+    })
+}
+
+pub(crate) fn parse_wire(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<Wire, DsnError> {
+    ctx.expect_keyword(s_expr, "wire")?;
+    let path_item = s_expr.get(1).ok_or_else(|| ctx.missing("wire path"))?;
+    let path = ctx.list(path_item)?;
+    let (layer, width, points) = parse_path(path, ctx)?;
+    let mut net = None;
+    let mut unknown: Vec<UnknownScope> = Vec::new();
+    for (position, item) in s_expr.iter().enumerate().skip(2) {
+        let Some(expr_list) = ctx.scope_item(&mut unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
+            "net" => {
+                net = Some(ctx.atom(expr_list, 1, "net name")?.to_string());
+            }
+            // (type ...), (attr ...) and the like are kept verbatim
+            _ => unknown.push(UnknownScope {
+                position,
+                expr: item.clone(),
+            }),
+        }
+    }
+    Ok(Wire {
+        layer,
+        width,
+        points,
+        net,
+        unknown,
+    })
+}
+
+pub(crate) fn parse_wiring_via(
+    s_expr: &[SExpr],
+    ctx: &mut ParseContext,
+) -> Result<WiringVia, DsnError> {
+    ctx.expect_keyword(s_expr, "via")?;
+    let padstack_name = ctx.atom(s_expr, 1, "via padstack name")?.to_string();
+    let position = ctx.point(s_expr, 2, "via position")?;
+    let mut net = None;
+    let mut unknown: Vec<UnknownScope> = Vec::new();
+    for (position, item) in s_expr.iter().enumerate().skip(4) {
+        let Some(expr_list) = ctx.scope_item(&mut unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
+            "net" => {
+                net = Some(ctx.atom(expr_list, 1, "net name")?.to_string());
+            }
+            _ => unknown.push(UnknownScope {
+                position,
+                expr: item.clone(),
+            }),
+        }
+    }
+    Ok(WiringVia {
+        padstack_name,
+        position,
+        net,
+        unknown,
+    })
+}
+
+fn parse_wiring(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<Wiring, DsnError> {
+    ctx.expect_keyword(s_expr, "wiring")?;
+    let mut wires: Vec<Wire> = Vec::new();
+    let mut vias: Vec<WiringVia> = Vec::new();
+    let mut unknown: Vec<UnknownScope> = Vec::new();
+    for (position, item) in s_expr.iter().enumerate().skip(1) {
+        let Some(expr_list) = ctx.scope_item(&mut unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
+            "wire" => {
+                // only path wires are modelled
+                let is_path = expr_list
+                    .get(1)
+                    .and_then(|x| x.as_list())
+                    .and_then(|shape| shape.first())
+                    .and_then(|x| x.as_atom())
+                    .is_some_and(|keyword| keyword == "path");
+                if is_path {
+                    wires.push(ctx.scoped(expr_list, |ctx| parse_wire(expr_list, ctx))?);
+                } else {
+                    ctx.unknown_scope(&mut unknown, position, expr_list)?;
+                }
+            }
+            "via" => {
+                vias.push(ctx.scoped(expr_list, |ctx| parse_wiring_via(expr_list, ctx))?);
+            }
+            _ => {
+                ctx.unknown_scope(&mut unknown, position, expr_list)?;
+            }
+        }
+    }
+    Ok(Wiring {
+        wires,
+        vias,
+        unknown,
+    })
+}
+
+fn parse_on_off(atom: &str, field: &str, ctx: &ParseContext) -> Result<bool, DsnError> {
+    match atom {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        other => Err(ctx.error(DsnErrorKind::InvalidValue {
+            field: field.to_string(),
+            value: other.to_string(),
+        })),
+    }
+}
+
+pub(crate) fn parse_parser(
+    s_expr: &[SExpr],
+    ctx: &mut ParseContext,
+) -> Result<ParserHeader, DsnError> {
+    ctx.expect_keyword(s_expr, "parser")?;
+    let mut header = ParserHeader {
+        string_quote: None,
+        space_in_quoted_tokens: false,
+        host_cad: None,
+        host_version: None,
+        unknown: Vec::new(),
+    };
+    for (position, item) in s_expr.iter().enumerate().skip(1) {
+        let Some(expr_list) = ctx.scope_item(&mut header.unknown, position, item)? else {
+            continue;
+        };
+        match ctx.keyword(expr_list)? {
+            "string_quote" => {
+                let quote = ctx.atom(expr_list, 1, "string quote")?;
+                let mut chars = quote.chars();
+                header.string_quote = match (chars.next(), chars.next()) {
+                    (Some(c), None) => Some(c),
+                    _ => {
+                        return Err(ctx.error(DsnErrorKind::InvalidValue {
+                            field: "string quote".to_string(),
+                            value: quote.to_string(),
+                        }));
+                    }
+                };
+            }
+            "space_in_quoted_tokens" => {
+                let atom = ctx.atom(expr_list, 1, "space_in_quoted_tokens")?;
+                header.space_in_quoted_tokens = parse_on_off(atom, "space_in_quoted_tokens", ctx)?;
+            }
+            "host_cad" => {
+                header.host_cad = Some(ctx.atom(expr_list, 1, "host cad")?.to_string());
+            }
+            "host_version" => {
+                header.host_version = Some(ctx.atom(expr_list, 1, "host version")?.to_string());
+            }
             _ => {
-                return Err(format!("Unknown network item: {}", first_item));
+                ctx.unknown_scope(&mut header.unknown, position, expr_list)?;
             }
         }
     }
+    Ok(header)
+}
+
+fn parse_unit_keyword(atom: &str, ctx: &ParseContext) -> Result<Unit, DsnError> {
+    Unit::from_keyword(atom).ok_or_else(|| {
+        ctx.error(DsnErrorKind::InvalidValue {
+            field: "unit".to_string(),
+            value: atom.to_string(),
+        })
+    })
+}
+
+pub(crate) fn parse_resolution(
+    s_expr: &[SExpr],
+    ctx: &mut ParseContext,
+) -> Result<Resolution, DsnError> {
+    let unit = parse_unit_keyword(ctx.atom(s_expr, 1, "resolution unit")?, ctx)?;
+    let value = ctx.number(s_expr, 2, "resolution value")?;
+    // lengths in a session are divided by it
+    if value <= 0.0 {
+        return Err(ctx.invalid_number("resolution value", &value.to_string()));
+    }
+    Ok(Resolution { unit, value })
+}
+
+fn parse_unit(s_expr: &[SExpr], ctx: &mut ParseContext) -> Result<Unit, DsnError> {
+    parse_unit_keyword(ctx.atom(s_expr, 1, "unit")?, ctx)
+}
 
-    Ok(Network { nets, netclasses })
+/// Parses a DSN file in strict mode: unknown scopes are kept, stray atoms
+/// and pad shapes the model lacks are an error.
+pub fn parse_s_expr_to_struct(s_expr: &SExpr) -> Result<DsnStruct, DsnError> {
+    parse_s_expr_to_struct_with_mode(s_expr, ParseMode::Strict).map(|(dsn, _)| dsn)
 }
 
-pub fn parse_s_expr_to_struct(s_expr: &SExpr) -> Result<DsnStruct, String> {
+/// Parses a DSN file, returning the warnings collected in lenient mode.
+pub fn parse_s_expr_to_struct_with_mode(
+    s_expr: &SExpr,
+    mode: ParseMode,
+) -> Result<(DsnStruct, Vec<Diagnostic>), DsnError> {
+    let mut ctx = ParseContext {
+        mode,
+        // replaced once the unit descriptors are read
+        unit: Unit::Mm,
+        per_unit: 1.0,
+        path: ScopePath::new(),
+        diagnostics: Vec::new(),
+        components: HashSet::new(),
+    };
+    let expr_list = ctx.list(s_expr)?;
+    ctx.expect_keyword(expr_list, "pcb")?;
+    let design = ctx.atom(expr_list, 1, "design name")?.to_string();
+    ctx.path.push("pcb".to_string());
+
+    // Every length in the file is written in the file unit, so the unit
+    // descriptors have to be known before any other scope is parsed; the
+    // component names are needed to split the pin references of the nets.
     let mut resolution: Option<Resolution> = None;
+    let mut file_unit: Option<Unit> = None;
+    for expr in expr_list {
+        let Some(expr_list2) = expr.as_list() else {
+            continue;
+        };
+        match expr_list2
+            .first()
+            .and_then(|x| x.as_atom())
+            .map(|s| s.as_str())
+        {
+            Some("resolution") => {
+                resolution = Some(ctx.scoped(expr_list2, |ctx| parse_resolution(expr_list2, ctx))?)
+            }
+            Some("unit") => {
+                file_unit = Some(ctx.scoped(expr_list2, |ctx| parse_unit(expr_list2, ctx))?)
+            }
+            Some("placement") => ctx.components.extend(placed_components(expr_list2)),
+            _ => {}
+        }
+    }
+    let resolution = resolution.ok_or_else(|| ctx.missing("resolution"))?;
+    // Without a unit descriptor the coordinates are in the resolution unit.
+    let unit = file_unit.unwrap_or(resolution.unit);
+    ctx.unit = unit;
+
+    let mut parser: Option<ParserHeader> = None;
     let mut structure: Option<Structure> = None;
     let mut placement: Option<Placement> = None;
     let mut library: Option<Library> = None;
     let mut network: Option<Network> = None;
-    let expr_list = s_expr.as_list().ok_or("Expected a list at the top level")?;
-    for expr in expr_list {
-        let expr_list2 = match expr {
-            SExpr::List(list) => list,
-            _ => continue,
+    let mut wiring: Option<Wiring> = None;
+    let mut unknown: Vec<UnknownScope> = Vec::new();
+    for (position, expr) in expr_list.iter().enumerate().skip(2) {
+        let Some(expr_list2) = ctx.scope_item(&mut unknown, position, expr)? else {
+            continue;
         };
-        let first_item = expr_list2
-            .first()
-            .ok_or(format!("Expected at least one item in the outermost scope"))?;
-        let first_item = first_item.as_atom().ok_or(format!(
-            "Expected an atom as the first item in the outermost scope"
-        ))?;
-        match first_item.as_str() {
+        match ctx.keyword(expr_list2)? {
             "parser" => {
-                continue;
+                parser = Some(ctx.scoped(expr_list2, |ctx| parse_parser(expr_list2, ctx))?);
             }
-            "resolution" => {
-                let second_item = expr_list2
-                    .get(1)
-                    .ok_or(format!("Expected a second item in the resolution scope"))?;
-                let third_item = expr_list2
-                    .get(2)
-                    .ok_or(format!("Expected a third item in the resolution scope"))?;
-                let second_item = second_item.as_atom().ok_or(format!(
-                    "Expected an atom as the second item in the resolution scope"
-                ))?;
-                let third_item = third_item.as_atom().ok_or(format!(
-                    "Expected an atom as the third item in the resolution scope"
-                ))?;
-                let value = third_item
-                    .parse::<f64>()
-                    .map_err(|e| format!("Failed to parse resolution value: {}", e))?;
-                resolution = Some(Resolution {
-                    unit: second_item.to_string(),
-                    value,
-                });
+            "wiring" => {
+                wiring = Some(ctx.scoped(expr_list2, |ctx| parse_wiring(expr_list2, ctx))?);
             }
-            "unit" => {
+            "resolution" | "unit" => {
                 continue;
             }
             "structure" => {
-                structure = Some(parse_structure(expr_list2)?);
+                structure = Some(ctx.scoped(expr_list2, |ctx| parse_structure(expr_list2, ctx))?);
             }
             "placement" => {
-                placement = Some(parse_placement(expr_list2)?);
+                placement = Some(ctx.scoped(expr_list2, |ctx| parse_placement(expr_list2, ctx))?);
             }
             "library" => {
-                library = Some(parse_library(expr_list2)?);
+                library = Some(ctx.scoped(expr_list2, |ctx| parse_library(expr_list2, ctx))?);
             }
             "network" => {
-                network = Some(parse_network(expr_list2)?);
-            }
-            "wiring" => {
-                continue;
+                network = Some(ctx.scoped(expr_list2, |ctx| parse_network(expr_list2, ctx))?);
             }
             _ => {
-                return Err(format!("Unknown S-expression type: {}", first_item));
+                ctx.unknown_scope(&mut unknown, position, expr_list2)?;
             }
         }
     }
-    Ok(DsnStruct {
-        resolution: resolution.ok_or("Missing required field: resolution")?,
-        structure: structure.ok_or("Missing required field: structure")?,
-        placement: placement.ok_or("Missing required field: placement")?,
-        library: library.ok_or("Missing required field: library")?,
-        network: network.ok_or("Missing required field: network")?,
-    })
+    let dsn = DsnStruct {
+        design,
+        parser,
+        resolution,
+        unit,
+        structure: structure.ok_or_else(|| ctx.missing("structure"))?,
+        placement: placement.ok_or_else(|| ctx.missing("placement"))?,
+        library: library.ok_or_else(|| ctx.missing("library"))?,
+        network: network.ok_or_else(|| ctx.missing("network"))?,
+        wiring,
+        unknown,
+    };
+    Ok((dsn, ctx.diagnostics))
 }
@@ -1,15 +1,9 @@
-use core::panic;
 use std::{
-    cell::RefCell,
-    collections::{BTreeSet, BinaryHeap, HashMap, HashSet},
-    num::NonZeroUsize,
+    collections::HashMap,
     rc::Rc,
-    sync::{Arc, Mutex},
 };
 
-use ordered_float::NotNan;
-
-use crate::{distinct_color_generator::{ColorFloat3, DistinctColorGenerator}, pad::Pad, shapes::{Line, Polygon}};
+use crate::{distinct_color_generator::{ColorFloat3, DistinctColorGenerator}, geometry::Point, pad::Pad, shapes::{Line, Polygon}, units::Length};
 
 
 // use shared::interface_types::{Color, ColorGrid};
@@ -21,8 +15,8 @@ pub struct Connection {
     pub net_name: NetName,               // The net that the connection belongs to
     pub connection_id: ConnectionID, // Unique identifier for the connection    
     pub sink: Pad,
-    pub sink_trace_width: f32, // Width of the trace
-    pub sink_trace_clearance: f32, // Clearance around the trace
+    pub sink_trace_width: Length, // Width of the trace
+    pub sink_trace_clearance: Length, // Clearance around the trace
     // pub traces: HashMap<TraceID, TraceInfo>, // List of traces connecting the source and sink pads
 }
 
@@ -31,8 +25,8 @@ pub struct NetInfo {
     pub net_name: NetName,
     pub color: ColorFloat3,                                   // Color of the net
     pub source: Pad,
-    pub source_trace_width: f32, // Width of the trace from the source pad
-    pub source_trace_clearance: f32, // Clearance around the trace from the source pad
+    pub source_trace_width: Length, // Width of the trace from the source pad
+    pub source_trace_clearance: Length, // Clearance around the trace from the source pad
     pub connections: HashMap<ConnectionID, Rc<Connection>>, // List of connections in the net, the source pad is the same
 }
 
@@ -46,9 +40,9 @@ pub struct ConnectionID(pub usize);
 
 /// use new, add_net, add_connection to construct this struct
 pub struct PcbProblem {
-    width: f32, // in specctra dsn units
-    height: f32, // in specctra dsn units
-    center: (f32, f32), // Center of the PCB, in specctra dsn units
+    width: Length,
+    height: Length,
+    center: Point, // Center of the PCB
     obstacle_lines: Vec<Line>, // Lines that represent obstacles in the PCB
     obstacle_polygons: Vec<Polygon>, // Polygons that represent obstacles in the PCB
     nets: HashMap<NetName, NetInfo>, // NetID to NetInfo
@@ -58,7 +52,7 @@ pub struct PcbProblem {
 
 
 impl PcbProblem {
-    pub fn new(width: f32, height: f32, center: (f32, f32)) -> Self {
+    pub fn new(width: Length, height: Length, center: Point) -> Self {
         PcbProblem {
             width,
             height,
@@ -70,7 +64,25 @@ impl PcbProblem {
             distinct_color_generator: Box::new(DistinctColorGenerator::new()),
         }
     }
-    pub fn add_net(&mut self, net_name: NetName, source: Pad, source_trace_width: f32, source_trace_clearance: f32) {
+    pub fn width(&self) -> Length {
+        self.width
+    }
+    pub fn height(&self) -> Length {
+        self.height
+    }
+    pub fn center(&self) -> Point {
+        self.center
+    }
+    pub fn obstacle_lines(&self) -> &[Line] {
+        &self.obstacle_lines
+    }
+    pub fn obstacle_polygons(&self) -> &[Polygon] {
+        &self.obstacle_polygons
+    }
+    pub fn nets(&self) -> &HashMap<NetName, NetInfo> {
+        &self.nets
+    }
+    pub fn add_net(&mut self, net_name: NetName, source: Pad, source_trace_width: Length, source_trace_clearance: Length) {
         assert!(!self.nets.contains_key(&net_name), "NetID already exists: {}", net_name.0);
         let color = self.distinct_color_generator.next().expect("Distinct color generator exhausted");
         let net_info = NetInfo {
@@ -84,7 +96,7 @@ impl PcbProblem {
         self.nets.insert(net_name, net_info);
     }
     /// assert the sources in the same net are the same
-    pub fn add_connection(&mut self, net_name: NetName, sink: Pad, trace_width: f32, trace_clearance: f32) -> ConnectionID {
+    pub fn add_connection(&mut self, net_name: NetName, sink: Pad, trace_width: Length, trace_clearance: Length) -> ConnectionID {
         let net_info = self.nets.get_mut(&net_name).expect("NetID not found");
         let connection_id = self
             .connection_id_generator
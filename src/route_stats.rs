@@ -0,0 +1,177 @@
+//! Figures for comparing routing results: length, vias, segments and bends
+//! per net and layer.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::{
+    apply_session::apply_session,
+    connectivity::{board_completion, route_completeness},
+    dsn_error::DsnError,
+    dsn_struct::{DsnStruct, Wire},
+    geometry::Point,
+    session_struct::SessionStruct,
+    units::Length,
+};
+
+/// Routing figures of one net.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NetStats {
+    pub net: String,
+    pub length: Length,
+    pub vias: usize,
+    pub segments: usize,
+    /// Changes of direction within the wires.
+    pub bends: usize,
+    /// Wire length on each layer the net uses.
+    pub layer_lengths: BTreeMap<String, Length>,
+}
+
+/// Routing figures of the board.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteStats {
+    /// Every net of the design in netlist order, routed or not.
+    pub nets: Vec<NetStats>,
+    pub layer_lengths: BTreeMap<String, Length>,
+    pub vias: usize,
+    /// Share of the connections of the board that are routed, in percent.
+    pub completion: f64,
+}
+
+impl RouteStats {
+    pub fn length(&self) -> Length {
+        self.layer_lengths.values().copied().sum()
+    }
+}
+
+fn distance(a: Point, b: Point) -> f64 {
+    let dx = (b.x - a.x).nm() as f64;
+    let dy = (b.y - a.y).nm() as f64;
+    dx.hypot(dy)
+}
+
+/// Adds the length, segments and bends of `wire` to `stats`. Repeated points
+/// are not counted as segments, and collinear segments do not bend.
+fn add_wire(stats: &mut NetStats, wire: &Wire) {
+    let mut points = wire.points.clone();
+    points.dedup();
+    let length: f64 = points
+        .windows(2)
+        .map(|pair| distance(pair[0], pair[1]))
+        .sum();
+    let length = Length::from_nm(length.round() as i64);
+    stats.length += length;
+    *stats.layer_lengths.entry(wire.layer.clone()).or_default() += length;
+    stats.segments += points.len().saturating_sub(1);
+    stats.bends += points
+        .windows(3)
+        .filter(|corner| {
+            let (a, b) = (corner[1] - corner[0], corner[2] - corner[1]);
+            let (ax, ay) = (a.x.nm() as i128, a.y.nm() as i128);
+            let (bx, by) = (b.x.nm() as i128, b.y.nm() as i128);
+            // straight on means parallel and pointing the same way
+            ax * by != ay * bx || ax * bx + ay * by < 0
+        })
+        .count();
+}
+
+/// Collects the figures of the wiring of `dsn`. Wires and vias without a
+/// net are counted in the board totals only.
+pub fn route_stats(dsn: &DsnStruct) -> Result<RouteStats, Vec<DsnError>> {
+    let mut nets: Vec<NetStats> = dsn
+        .network
+        .nets
+        .iter()
+        .map(|net| NetStats {
+            net: net.name.clone(),
+            ..NetStats::default()
+        })
+        .collect();
+    let position: BTreeMap<&str, usize> = dsn
+        .network
+        .nets
+        .iter()
+        .enumerate()
+        .map(|(index, net)| (net.name.as_str(), index))
+        .collect();
+
+    let mut layer_lengths: BTreeMap<String, Length> = BTreeMap::new();
+    let mut vias = 0;
+    if let Some(wiring) = &dsn.wiring {
+        for wire in &wiring.wires {
+            let mut stats = NetStats::default();
+            add_wire(&mut stats, wire);
+            *layer_lengths.entry(wire.layer.clone()).or_default() += stats.length;
+            if let Some(&index) = wire.net.as_deref().and_then(|net| position.get(net)) {
+                add_wire(&mut nets[index], wire);
+            }
+        }
+        for via in &wiring.vias {
+            vias += 1;
+            if let Some(&index) = via.net.as_deref().and_then(|net| position.get(net)) {
+                nets[index].vias += 1;
+            }
+        }
+    }
+
+    let completion = board_completion(&route_completeness(dsn)?);
+
+    Ok(RouteStats {
+        nets,
+        layer_lengths,
+        vias,
+        completion,
+    })
+}
+
+/// [`route_stats`] of the design once `session` has been applied.
+pub fn session_stats(
+    dsn: &DsnStruct,
+    session: &SessionStruct,
+) -> Result<RouteStats, Vec<DsnError>> {
+    let mut routed = dsn.clone();
+    apply_session(&mut routed, session).map_err(|error| vec![error])?;
+    route_stats(&routed)
+}
+
+fn describe_layers(layer_lengths: &BTreeMap<String, Length>) -> String {
+    layer_lengths
+        .iter()
+        .map(|(layer, length)| format!("{} {}", layer, length))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl fmt::Display for NetStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "net {}: {}, {} segments, {} bends, {} vias",
+            self.net, self.length, self.segments, self.bends, self.vias
+        )?;
+        if !self.layer_lengths.is_empty() {
+            write!(f, " ({})", describe_layers(&self.layer_lengths))?;
+        }
+        Ok(())
+    }
+}
+
+/// One line per net, then the board totals.
+impl fmt::Display for RouteStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for net in &self.nets {
+            writeln!(f, "{}", net)?;
+        }
+        write!(
+            f,
+            "board: {}, {} vias, {:.1}% routed",
+            self.length(),
+            self.vias,
+            self.completion
+        )?;
+        if !self.layer_lengths.is_empty() {
+            write!(f, " ({})", describe_layers(&self.layer_lengths))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,165 @@
+use crate::{dsn_index::DsnIndex, dsn_struct::Rule, units::Length};
+
+/// An object on the board as far as the rules are concerned: the net it
+/// belongs to and its clearance class. The default value stands for any
+/// object without a net or clearance class.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RuleObject<'a> {
+    pub net: Option<&'a str>,
+    pub clearance_class: Option<&'a str>,
+}
+
+/// Whether a class_class over `classes` sets rules between `class_a` and
+/// `class_b`. Two objects of one class need that class listed twice.
+fn pairs(classes: &[String], class_a: &str, class_b: &str) -> bool {
+    let Some(index) = classes.iter().position(|class| class == class_a) else {
+        return false;
+    };
+    classes
+        .iter()
+        .enumerate()
+        .any(|(other, class)| other != index && class == class_b)
+}
+
+/// The value of the most specific level that sets one, the larger of two
+/// within a level.
+fn most_specific(levels: &[Vec<&Rule>], value: impl Fn(&Rule) -> Option<Length>) -> Option<Length> {
+    levels
+        .iter()
+        .filter_map(|rules| rules.iter().filter_map(|rule| value(rule)).max())
+        .next_back()
+}
+
+/// Resolves widths and clearances through the DSN rule hierarchy.
+///
+/// Levels are applied from the most general to the most specific, each one
+/// overriding the previous: pcb (the structure rule), net class, class_class,
+/// net and pin. The pin level is a clearance typed with the clearance classes
+/// of the two objects, e.g. `(type BGA_BGA)`, which any of the other levels'
+/// rules may hold. When the two objects get different values on one level,
+/// the larger one wins.
+pub struct RuleResolver<'a> {
+    index: &'a DsnIndex<'a>,
+}
+
+impl<'a> RuleResolver<'a> {
+    pub fn new(index: &'a DsnIndex<'a>) -> RuleResolver<'a> {
+        RuleResolver { index }
+    }
+
+    /// The object for a pin of a placed component. A clearance class on the
+    /// pin takes precedence over the one of its image.
+    pub fn pin_object(&self, reference: &str, pin_id: &str) -> RuleObject<'a> {
+        let pin_class = self
+            .index
+            .pin(reference, pin_id)
+            .and_then(|pin| pin.clearance_class.as_deref());
+        let image_class = self
+            .index
+            .image(reference)
+            .and_then(|image| image.clearance_class.as_deref());
+        RuleObject {
+            net: self
+                .index
+                .net(reference, pin_id)
+                .map(|net| net.name.as_str()),
+            clearance_class: pin_class.or(image_class),
+        }
+    }
+
+    fn pcb_rule(&self) -> Option<&'a Rule> {
+        self.index.dsn().structure.rule.as_ref()
+    }
+
+    fn class_name(&self, object: RuleObject) -> Option<&'a str> {
+        let netclass = self.index.net_class(object.net?)?;
+        Some(netclass.net_class_name.as_str())
+    }
+
+    fn class_rule(&self, object: RuleObject) -> Option<&'a Rule> {
+        self.index.net_class(object.net?)?.rule.as_ref()
+    }
+
+    fn net_rule(&self, object: RuleObject) -> Option<&'a Rule> {
+        self.index.net_by_name(object.net?)?.rule.as_ref()
+    }
+
+    /// Trace width for a net, or zero if no level sets one.
+    pub fn width(&self, net: Option<&str>) -> Length {
+        let object = RuleObject {
+            net,
+            clearance_class: None,
+        };
+        [
+            self.pcb_rule(),
+            self.class_rule(object),
+            self.net_rule(object),
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|rule| rule.width)
+        .next_back()
+        .unwrap_or(Length::ZERO)
+    }
+
+    /// Rules of the levels that apply between `a` and `b`, from the most
+    /// general to the most specific. A level holds the rules of both
+    /// objects, or of every class_class naming their two classes.
+    fn clearance_levels(&self, a: RuleObject, b: RuleObject) -> [Vec<&'a Rule>; 4] {
+        let class_classes = match (self.class_name(a), self.class_name(b)) {
+            (Some(class_a), Some(class_b)) => self
+                .index
+                .dsn()
+                .network
+                .class_classes
+                .iter()
+                .filter(|class_class| pairs(&class_class.classes, class_a, class_b))
+                .map(|class_class| &class_class.rule)
+                .collect(),
+            _ => Vec::new(),
+        };
+        [
+            self.pcb_rule().into_iter().collect(),
+            [self.class_rule(a), self.class_rule(b)]
+                .into_iter()
+                .flatten()
+                .collect(),
+            class_classes,
+            [self.net_rule(a), self.net_rule(b)]
+                .into_iter()
+                .flatten()
+                .collect(),
+        ]
+    }
+
+    /// Clearance required between `a` and `b`, or zero if no level sets one.
+    ///
+    /// The untyped clearance comes from the most specific level that has
+    /// one. A clearance typed with the clearance classes of the two objects
+    /// overrides it, again taken from the most specific level that has one.
+    /// An object without a clearance class counts as class `default`, as in
+    /// KiCad's `(type default_smd)`.
+    pub fn clearance(&self, a: RuleObject, b: RuleObject) -> Length {
+        let pin_types = match (a.clearance_class, b.clearance_class) {
+            (None, None) => Vec::new(),
+            (class_a, class_b) => {
+                let class_a = class_a.unwrap_or("default");
+                let class_b = class_b.unwrap_or("default");
+                vec![
+                    format!("{}_{}", class_a, class_b),
+                    format!("{}_{}", class_b, class_a),
+                ]
+            }
+        };
+
+        let levels = self.clearance_levels(a, b);
+        let default = most_specific(&levels, |rule| rule.default_clearance());
+        let pin = most_specific(&levels, |rule| {
+            pin_types
+                .iter()
+                .filter_map(|clearance_type| rule.typed_clearance(clearance_type))
+                .max()
+        });
+        pin.or(default).unwrap_or(Length::ZERO)
+    }
+}
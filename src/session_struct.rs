@@ -0,0 +1,63 @@
+use crate::dsn_struct::{Component, PadStack, ParserHeader, Pin2, Resolution, UnknownScope, Wire, WiringVia};
+
+/// The `(placement ...)` of a session: where the router left the components.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionPlacement{
+    /// Resolution the coordinates of this scope were written in.
+    pub resolution: Resolution,
+    pub components: Vec<Component>,
+    pub unknown: Vec<UnknownScope>,
+}
+
+/// A `(pins <was> <is>)` entry of `was_is`: the connection the design had
+/// on `was` moved to `is` when the router swapped pins or gates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PinSwap{
+    pub was: Pin2,
+    pub is: Pin2,
+}
+
+/// The `(was_is ...)` scope.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WasIs{
+    pub pin_swaps: Vec<PinSwap>,
+    pub unknown: Vec<UnknownScope>,
+}
+
+/// A `(net ...)` of the `network_out` scope with the copper routed for it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionNet{
+    pub name: String,
+    pub wires: Vec<Wire>,
+    pub vias: Vec<WiringVia>,
+    pub unknown: Vec<UnknownScope>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Routes{
+    /// Resolution the coordinates of this scope were written in.
+    pub resolution: Resolution,
+    pub parser: Option<ParserHeader>,
+    /// Padstacks the router used, e.g. for vias. Hosts may list the same
+    /// padstack more than once, so these are kept in file order.
+    pub library_out: Vec<PadStack>,
+    /// Items of `(library_out ...)` other than padstacks.
+    pub library_out_unknown: Vec<UnknownScope>,
+    pub network_out: Vec<SessionNet>,
+    /// Items of `(network_out ...)` other than nets.
+    pub network_out_unknown: Vec<UnknownScope>,
+    pub unknown: Vec<UnknownScope>,
+}
+
+/// A Specctra session file, the router's answer to a DSN file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionStruct{
+    /// Session identifier following `session`.
+    pub name: String,
+    /// Design identifier the session was routed from.
+    pub base_design: String,
+    pub placement: Option<SessionPlacement>,
+    pub was_is: Option<WasIs>,
+    pub routes: Option<Routes>,
+    pub unknown: Vec<UnknownScope>,
+}
@@ -1,48 +1,38 @@
-#[derive(Debug, Clone)]
-pub struct OutlinePolygon(pub Vec<(f32, f32)>);
+use crate::{geometry::Point, units::Length};
 
-use cgmath::{Rotation, Rotation2};
+#[derive(Debug, Clone)]
+pub struct OutlinePolygon(pub Vec<Point>);
 
 #[derive(Debug, Clone)]
 pub struct CircleShape{
-    pub position: (f32, f32),
-    pub diameter: f32,
+    pub position: Point,
+    pub diameter: Length,
 }
 #[derive(Debug, Clone)]
 pub struct RectangleShape {
-    pub position: (f32, f32), // center position of the rectangle
-    pub width: f32,
-    pub height: f32,
+    pub position: Point, // center position of the rectangle
+    pub width: Length,
+    pub height: Length,
     pub rotation: cgmath::Deg<f32>, // Rotation counterclockwise in degrees
 }
 
 impl RectangleShape {
     pub fn to_polygon(&self) -> Polygon {
-        let hw = self.width / 2.0;
-        let hh = self.height / 2.0;
+        let hw = self.width / 2;
+        let hh = self.height / 2;
 
         // Corner positions before rotation (relative to center)
         let corners = [
-            cgmath::Vector2::new(-hw, -hh),
-            cgmath::Vector2::new(hw, -hh),
-            cgmath::Vector2::new(hw, hh),
-            cgmath::Vector2::new(-hw, hh),
+            Point::new(-hw, -hh),
+            Point::new(hw, -hh),
+            Point::new(hw, hh),
+            Point::new(-hw, hh),
         ];
-        // Convert rotation to radians
-        let rotation_rad: cgmath::Rad<f32> = self.rotation.into();
-
-        // Create rotation matrix
-        let rotation = cgmath::Basis2::from_angle(rotation_rad);
 
         // Apply rotation and translate to position
-        let rotated_corners: Vec<(f32, f32)> = corners
+        let rotated_corners: Vec<Point> = corners
             .iter()
-            .map(|corner| {
-                let rotated_corner = rotation.rotate_vector(*corner);
-
-                // self.position + rotation.rotate_vector(*corner)
-                (self.position.0 + rotated_corner.x, self.position.1 + rotated_corner.y)
-            })
+            .map(|corner| corner.rotated(self.rotation.0 as f64) + self.position)
             .collect();
 
         Polygon(rotated_corners)
@@ -51,11 +41,11 @@ impl RectangleShape {
 
 #[derive(Debug, Clone)]
 pub struct RoundedRectShape {
-    pub position: (f32, f32), // center position of the rectangle
-    pub width: f32,
-    pub height: f32,
+    pub position: Point, // center position of the rectangle
+    pub width: Length,
+    pub height: Length,
     pub rotation: cgmath::Deg<f32>, // Rotation counterclockwise in degrees
-    pub corner_radius: f32,
+    pub corner_radius: Length,
 }
 
 #[derive(Debug, Clone)]
@@ -66,7 +56,7 @@ pub enum PrimShape {
 }
 
 #[derive(Debug, Clone)]
-pub struct Polygon(pub Vec<(f32, f32)>);
+pub struct Polygon(pub Vec<Point>);
 
 #[derive(Debug, Clone)]
-pub struct Line(pub (f32, f32), pub (f32, f32));
\ No newline at end of file
+pub struct Line(pub Point, pub Point);
\ No newline at end of file
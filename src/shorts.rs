@@ -0,0 +1,85 @@
+//! Copper of different nets touching on the same layer.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::{
+    apply_session::apply_session,
+    copper::{CopperSource, close_pairs, design_copper},
+    dsn_error::DsnError,
+    dsn_index::DsnIndex,
+    dsn_struct::DsnStruct,
+    geometry::Point,
+    session_struct::SessionStruct,
+    units::Length,
+};
+
+/// Two pieces of copper of different nets that touch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Short {
+    pub nets: (String, String),
+    pub objects: (CopperSource, CopperSource),
+    pub layer: String,
+    /// Where the two pieces of copper meet.
+    pub location: Point,
+}
+
+impl fmt::Display for Short {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "short between {} and {} on {} at ({}, {}): {} touches {}",
+            self.nets.0,
+            self.nets.1,
+            self.layer,
+            self.location.x,
+            self.location.y,
+            self.objects.0,
+            self.objects.1
+        )
+    }
+}
+
+/// Finds every place where copper of two different nets touches: pads of
+/// the placed components, and wires and vias of the wiring. Copper without
+/// a net, such as an unconnected pad, cannot short anything.
+///
+/// Each pair of objects is reported once per layer they touch on.
+pub fn find_shorts(dsn: &DsnStruct) -> Result<Vec<Short>, Vec<DsnError>> {
+    let index = DsnIndex::new(dsn)?;
+    let items = design_copper(&index).map_err(|error| vec![error])?;
+
+    let mut reported: HashSet<(&CopperSource, &CopperSource, &str)> = HashSet::new();
+    let mut shorts = Vec::new();
+    for (a, b) in close_pairs(&items, Length::ZERO) {
+        let (item, other) = (&items[a], &items[b]);
+        let (Some(net), Some(other_net)) = (&item.net, &other.net) else {
+            continue;
+        };
+        if net == other_net || reported.contains(&(&item.source, &other.source, &item.layer)) {
+            continue;
+        }
+        let (gap, location) = item.shape.gap(&other.shape);
+        if gap != Length::ZERO {
+            continue;
+        }
+        reported.insert((&item.source, &other.source, &item.layer));
+        shorts.push(Short {
+            nets: (net.clone(), other_net.clone()),
+            objects: (item.source.clone(), other.source.clone()),
+            layer: item.layer.clone(),
+            location,
+        });
+    }
+    Ok(shorts)
+}
+
+/// [`find_shorts`] in the design once `session` has been applied.
+pub fn session_shorts(
+    dsn: &DsnStruct,
+    session: &SessionStruct,
+) -> Result<Vec<Short>, Vec<DsnError>> {
+    let mut routed = dsn.clone();
+    apply_session(&mut routed, session).map_err(|error| vec![error])?;
+    find_shorts(&routed)
+}
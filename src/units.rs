@@ -0,0 +1,180 @@
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// Units accepted by the `resolution` and `unit` descriptors of a DSN file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unit {
+    Inch,
+    Mil,
+    Cm,
+    Mm,
+    Um,
+}
+
+impl Unit {
+    pub fn from_keyword(keyword: &str) -> Option<Unit> {
+        match keyword.to_ascii_lowercase().as_str() {
+            "inch" => Some(Unit::Inch),
+            "mil" => Some(Unit::Mil),
+            "cm" => Some(Unit::Cm),
+            "mm" => Some(Unit::Mm),
+            "um" => Some(Unit::Um),
+            _ => None,
+        }
+    }
+
+    pub fn keyword(self) -> &'static str {
+        match self {
+            Unit::Inch => "inch",
+            Unit::Mil => "mil",
+            Unit::Cm => "cm",
+            Unit::Mm => "mm",
+            Unit::Um => "um",
+        }
+    }
+
+    /// Number of nanometres in one of this unit.
+    pub fn nm_per_unit(self) -> f64 {
+        match self {
+            Unit::Inch => 25_400_000.0,
+            Unit::Mil => 25_400.0,
+            Unit::Cm => 10_000_000.0,
+            Unit::Mm => 1_000_000.0,
+            Unit::Um => 1_000.0,
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.keyword())
+    }
+}
+
+/// A length on the board, stored as whole nanometres.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Length(i64);
+
+impl Length {
+    pub const ZERO: Length = Length(0);
+
+    /// Largest length a file may hold, about 1.1 km. Far beyond any board,
+    /// and small enough that sums of coordinates cannot overflow.
+    pub const MAX: Length = Length(1 << 40);
+
+    pub const fn from_nm(nm: i64) -> Length {
+        Length(nm)
+    }
+
+    /// Rounds `value` given in `unit` to the nearest nanometre.
+    pub fn from_unit(value: f64, unit: Unit) -> Length {
+        Length((value * unit.nm_per_unit()).round() as i64)
+    }
+
+    /// Like [`Length::from_unit`], but `None` for values that are not finite
+    /// or lie beyond [`Length::MAX`].
+    pub fn checked_from_unit(value: f64, unit: Unit) -> Option<Length> {
+        let nm = (value * unit.nm_per_unit()).round();
+        if nm.is_finite() && nm.abs() <= Length::MAX.0 as f64 {
+            Some(Length(nm as i64))
+        } else {
+            None
+        }
+    }
+
+    pub fn from_mm(mm: f64) -> Length {
+        Length::from_unit(mm, Unit::Mm)
+    }
+
+    pub fn from_mil(mil: f64) -> Length {
+        Length::from_unit(mil, Unit::Mil)
+    }
+
+    pub fn from_um(um: f64) -> Length {
+        Length::from_unit(um, Unit::Um)
+    }
+
+    pub const fn nm(self) -> i64 {
+        self.0
+    }
+
+    pub fn in_unit(self, unit: Unit) -> f64 {
+        self.0 as f64 / unit.nm_per_unit()
+    }
+
+    pub fn as_mm(self) -> f64 {
+        self.in_unit(Unit::Mm)
+    }
+
+    pub fn as_mil(self) -> f64 {
+        self.in_unit(Unit::Mil)
+    }
+
+    pub fn as_um(self) -> f64 {
+        self.in_unit(Unit::Um)
+    }
+
+    pub fn abs(self) -> Length {
+        Length(self.0.abs())
+    }
+}
+
+impl fmt::Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}mm", self.as_mm())
+    }
+}
+
+impl Add for Length {
+    type Output = Length;
+    fn add(self, rhs: Length) -> Length {
+        Length(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Length {
+    fn add_assign(&mut self, rhs: Length) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Length {
+    type Output = Length;
+    fn sub(self, rhs: Length) -> Length {
+        Length(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Length {
+    fn sub_assign(&mut self, rhs: Length) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Neg for Length {
+    type Output = Length;
+    fn neg(self) -> Length {
+        Length(-self.0)
+    }
+}
+
+impl Mul<i64> for Length {
+    type Output = Length;
+    fn mul(self, rhs: i64) -> Length {
+        Length(self.0 * rhs)
+    }
+}
+
+impl Div<i64> for Length {
+    type Output = Length;
+    fn div(self, rhs: i64) -> Length {
+        Length(self.0 / rhs)
+    }
+}
+
+impl Sum for Length {
+    fn sum<I: Iterator<Item = Length>>(iter: I) -> Length {
+        iter.fold(Length::ZERO, Add::add)
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{
+    copper::covered_layers,
+    diagnostics::{Diagnostic, ScopePath},
+    dsn_error::{DsnError, DsnErrorKind},
+    dsn_struct::{Layer, PadStack, Shape},
+    geometry::Point,
+    units::{Length, Unit},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViaKind {
+    /// Spans the whole layer stack.
+    Through,
+    /// Starts or ends on an outer layer, but not both.
+    Blind,
+    /// Connects inner layers only.
+    Buried,
+}
+
+/// Pad of a via on one layer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ViaPad {
+    pub layer: String,
+    /// Diameter of a circle; for a rect or polygon pad, the larger side of
+    /// its bounding box.
+    pub diameter: Length,
+}
+
+/// Size of the pad: the diameter of a circle, or the larger side of the
+/// bounding box of a rect or polygon, including the polygon's aperture.
+fn pad_size(shape: &Shape) -> Length {
+    match shape {
+        Shape::Circle { diameter, .. } => *diameter,
+        Shape::Rect {
+            x_min,
+            y_min,
+            x_max,
+            y_max,
+        } => (*x_max - *x_min).max(*y_max - *y_min),
+        Shape::Polygon {
+            aperture_width,
+            vertices,
+        } => {
+            let extent = |coordinate: fn(&Point) -> Length| {
+                let values = vertices.iter().map(coordinate);
+                values.clone().max().unwrap_or(Length::ZERO) - values.min().unwrap_or(Length::ZERO)
+            };
+            extent(|point| point.x).max(extent(|point| point.y)) + *aperture_width
+        }
+    }
+}
+
+/// What a KiCad via padstack name such as `Via[0-3]_600:300_um` encodes:
+/// layer span, pad diameter and drill diameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ViaName {
+    pub start_layer: usize,
+    pub end_layer: usize,
+    pub diameter: Length,
+    pub drill: Length,
+}
+
+impl ViaName {
+    /// Returns `None` for names that do not follow the KiCad pattern.
+    pub fn parse(name: &str) -> Option<ViaName> {
+        let rest = name.strip_prefix("Via[")?;
+        let (span, rest) = rest.split_once(']')?;
+        let (start_layer, end_layer) = span.split_once('-')?;
+        let (sizes, unit) = rest.strip_prefix('_')?.rsplit_once('_')?;
+        let (diameter, drill) = sizes.split_once(':')?;
+        let unit = Unit::from_keyword(unit)?;
+        Some(ViaName {
+            start_layer: start_layer.parse().ok()?,
+            end_layer: end_layer.parse().ok()?,
+            diameter: Length::checked_from_unit(diameter.parse().ok()?, unit)?,
+            drill: Length::checked_from_unit(drill.parse().ok()?, unit)?,
+        })
+    }
+}
+
+/// A via padstack resolved against the layer stack of the structure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Via {
+    pub name: String,
+    /// Index of the first layer in the structure layer list.
+    pub start_layer: usize,
+    /// Index of the last layer in the structure layer list.
+    pub end_layer: usize,
+    pub pads: Vec<ViaPad>,
+    /// Drill diameter, known only when the padstack name encodes it.
+    pub drill: Option<Length>,
+    pub kind: ViaKind,
+}
+
+impl Via {
+    /// Derives the via from the shapes of `padstack`, which must be on layers
+    /// of `layers`. A KiCad style name that disagrees with the shapes adds a
+    /// warning to `warnings`; the shapes are used and the drill stays unknown.
+    pub fn from_padstack(
+        padstack: &PadStack,
+        layers: &[Layer],
+        warnings: &mut Vec<Diagnostic>,
+    ) -> Result<Via, DsnError> {
+        let context = ScopePath::from_segments(vec![
+            "pcb".to_string(),
+            "library".to_string(),
+            ScopePath::segment("padstack", &padstack.name),
+        ]);
+        let error = |kind| DsnError::new(kind, context.clone());
+
+        let mut pads = Vec::new();
+        let mut span: Option<(usize, usize)> = None;
+        for layer_shape in &padstack.shapes {
+            for name in covered_layers(&layer_shape.layer, layers) {
+                let index = layers
+                    .iter()
+                    .position(|layer| layer.name == name)
+                    .ok_or_else(|| {
+                        error(DsnErrorKind::DanglingReference {
+                            kind: "layer".to_string(),
+                            name: name.to_string(),
+                        })
+                    })?;
+                span = Some(match span {
+                    Some((first, last)) => (first.min(index), last.max(index)),
+                    None => (index, index),
+                });
+            }
+            pads.push(ViaPad {
+                layer: layer_shape.layer.clone(),
+                diameter: pad_size(&layer_shape.shape),
+            });
+        }
+        let (start_layer, end_layer) = span.ok_or_else(|| {
+            error(DsnErrorKind::MissingField {
+                field: "padstack shape".to_string(),
+            })
+        })?;
+
+        let encoded = ViaName::parse(&padstack.name).filter(|encoded| {
+            let agrees = encoded.start_layer == start_layer
+                && encoded.end_layer == end_layer
+                && pads.iter().all(|pad| pad.diameter == encoded.diameter);
+            if !agrees {
+                warnings.push(Diagnostic {
+                    location: context.clone(),
+                    message: format!(
+                        "via name {} disagrees with the padstack shapes, which are used",
+                        padstack.name
+                    ),
+                });
+            }
+            agrees
+        });
+
+        let outermost = layers.len().saturating_sub(1);
+        let kind = match (start_layer == 0, end_layer == outermost) {
+            (true, true) => ViaKind::Through,
+            (true, false) | (false, true) => ViaKind::Blind,
+            (false, false) => ViaKind::Buried,
+        };
+
+        Ok(Via {
+            name: padstack.name.clone(),
+            start_layer,
+            end_layer,
+            pads,
+            drill: encoded.map(|encoded| encoded.drill),
+            kind,
+        })
+    }
+
+    /// Largest pad diameter over all layers.
+    pub fn diameter(&self) -> Length {
+        self.pads
+            .iter()
+            .map(|pad| pad.diameter)
+            .max()
+            .unwrap_or(Length::ZERO)
+    }
+
+    pub fn covers_layer(&self, layer: usize) -> bool {
+        (self.start_layer..=self.end_layer).contains(&layer)
+    }
+}
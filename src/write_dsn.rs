@@ -0,0 +1,525 @@
+use crate::{
+    dsn_struct::{
+        AutorouteSettings, ClassClass, Component, ComponentInst, Control, DsnStruct, Grid,
+        GridDirection, GridKind, Image, Keepout, KeepoutKind, Layer, LayerRule, LayerShape,
+        Library, Net, Netclass, Network, PadStack, ParserHeader, Pin, Placement,
+        PreferredDirection, Resolution, Rule, Shape, Side, Structure, UnknownScope, Wire, Wiring,
+        WiringVia,
+    },
+    geometry::Point,
+    s_expr::SExpr,
+    units::{Length, Unit},
+    write_s_expr::write_s_expr,
+};
+
+pub(crate) fn atom(value: impl Into<String>) -> SExpr {
+    SExpr::Atom(value.into())
+}
+
+/// Formats a number the way DSN files write them: no exponent and no
+/// trailing zeros.
+pub(crate) fn number(value: f64) -> SExpr {
+    let text = format!("{}", value);
+    if text == "-0" { atom("0") } else { atom(text) }
+}
+
+fn on_off(value: bool) -> SExpr {
+    atom(if value { "on" } else { "off" })
+}
+
+/// `(keyword value)`
+pub(crate) fn pair(keyword: &str, value: SExpr) -> SExpr {
+    SExpr::List(vec![atom(keyword), value])
+}
+
+/// `(resolution <unit> <value>)`
+pub(crate) fn resolution(resolution: &Resolution) -> SExpr {
+    SExpr::List(vec![
+        atom("resolution"),
+        atom(resolution.unit.keyword()),
+        number(resolution.value),
+    ])
+}
+
+/// Builds a scope from its modelled items and puts the unknown scopes back
+/// at the positions they were read from. A scope whose parent shrank in the
+/// meantime is appended instead.
+pub(crate) fn scope(mut items: Vec<SExpr>, unknown: &[UnknownScope]) -> SExpr {
+    let mut unknown: Vec<&UnknownScope> = unknown.iter().collect();
+    unknown.sort_by_key(|scope| scope.position);
+    for scope in unknown {
+        let index = scope.position.min(items.len());
+        items.insert(index, scope.expr.clone());
+    }
+    SExpr::List(items)
+}
+
+/// Writes the model back to S-expressions, with lengths in the unit of the file.
+pub(crate) struct Writer {
+    pub(crate) unit: Unit,
+    /// Resolution steps per `unit` in a session file, which writes lengths
+    /// as whole steps. DSN files write lengths in the unit itself.
+    pub(crate) steps_per_unit: Option<f64>,
+}
+
+impl Writer {
+    /// Lengths in a unit that is not a whole number of nanometres, such as
+    /// mil, are written with the fewest decimals that read back to the same
+    /// length: 15.748 mil rather than 15.747992125984252.
+    fn length(&self, length: Length) -> SExpr {
+        let value = length.in_unit(self.unit);
+        if let Some(steps) = self.steps_per_unit {
+            return number((value * steps).round());
+        }
+        for decimals in 0..9 {
+            let scale = 10f64.powi(decimals);
+            let rounded = (value * scale).round() / scale;
+            if Length::from_unit(rounded, self.unit) == length {
+                return number(rounded);
+            }
+        }
+        number(value)
+    }
+
+    fn push_point(&self, items: &mut Vec<SExpr>, point: Point) {
+        items.push(self.length(point.x));
+        items.push(self.length(point.y));
+    }
+
+    /// `(path <layer> <width> x y x y ...)`
+    fn path(&self, layer: &str, width: Length, points: &[Point]) -> SExpr {
+        let mut items = vec![atom("path"), atom(layer), self.length(width)];
+        for point in points {
+            self.push_point(&mut items, *point);
+        }
+        SExpr::List(items)
+    }
+
+    pub(crate) fn parser(&self, header: &ParserHeader) -> SExpr {
+        let mut items = vec![atom("parser")];
+        if let Some(quote) = header.string_quote {
+            items.push(pair("string_quote", atom(quote)));
+        }
+        items.push(pair(
+            "space_in_quoted_tokens",
+            on_off(header.space_in_quoted_tokens),
+        ));
+        if let Some(host_cad) = &header.host_cad {
+            items.push(pair("host_cad", atom(host_cad.as_str())));
+        }
+        if let Some(host_version) = &header.host_version {
+            items.push(pair("host_version", atom(host_version.as_str())));
+        }
+        scope(items, &header.unknown)
+    }
+
+    fn layer(&self, layer: &Layer) -> SExpr {
+        scope(
+            vec![atom("layer"), atom(layer.name.as_str())],
+            &layer.unknown,
+        )
+    }
+
+    fn rule(&self, rule: &Rule) -> SExpr {
+        let mut items = vec![atom("rule")];
+        if let Some(width) = rule.width {
+            items.push(pair("width", self.length(width)));
+        }
+        for clearance in &rule.clearances {
+            let mut clearance_items = vec![atom("clearance"), self.length(clearance.value)];
+            if !clearance.types.is_empty() {
+                let mut type_items = vec![atom("type")];
+                type_items.extend(clearance.types.iter().map(|t| atom(t.as_str())));
+                clearance_items.push(SExpr::List(type_items));
+            }
+            items.push(SExpr::List(clearance_items));
+        }
+        scope(items, &rule.unknown)
+    }
+
+    fn control(&self, control: &Control) -> SExpr {
+        let mut items = vec![atom("control")];
+        if let Some(off_grid) = control.off_grid {
+            items.push(pair("off_grid", on_off(off_grid)));
+        }
+        if let Some(via_at_smd) = control.via_at_smd {
+            items.push(pair("via_at_smd", on_off(via_at_smd)));
+        }
+        scope(items, &control.unknown)
+    }
+
+    fn grid(&self, grid: &Grid) -> SExpr {
+        let kind = match grid.kind {
+            GridKind::Via => "via",
+            GridKind::Wire => "wire",
+            GridKind::ViaKeepout => "via_keepout",
+            GridKind::Place => "place",
+            GridKind::Snap => "snap",
+        };
+        let mut items = vec![atom("grid"), atom(kind), self.length(grid.step)];
+        if let Some(direction) = grid.direction {
+            let direction = match direction {
+                GridDirection::X => "x",
+                GridDirection::Y => "y",
+            };
+            items.push(pair("direction", atom(direction)));
+        }
+        if let Some(offset) = grid.offset {
+            items.push(pair("offset", self.length(offset)));
+        }
+        scope(items, &grid.unknown)
+    }
+
+    fn layer_rule(&self, rule: &LayerRule) -> SExpr {
+        let mut items = vec![atom("layer_rule"), atom(rule.layer.as_str())];
+        if let Some(active) = rule.active {
+            items.push(pair("active", on_off(active)));
+        }
+        if let Some(direction) = rule.preferred_direction {
+            let direction = match direction {
+                PreferredDirection::Horizontal => "horizontal",
+                PreferredDirection::Vertical => "vertical",
+            };
+            items.push(pair("preferred_direction", atom(direction)));
+        }
+        if let Some(costs) = rule.preferred_direction_trace_costs {
+            items.push(pair("preferred_direction_trace_costs", number(costs)));
+        }
+        if let Some(costs) = rule.against_preferred_direction_trace_costs {
+            items.push(pair(
+                "against_preferred_direction_trace_costs",
+                number(costs),
+            ));
+        }
+        scope(items, &rule.unknown)
+    }
+
+    fn autoroute_settings(&self, settings: &AutorouteSettings) -> SExpr {
+        let mut items = vec![atom("autoroute_settings")];
+        let switches = [
+            ("fanout", settings.fanout),
+            ("autoroute", settings.autoroute),
+            ("postroute", settings.postroute),
+            ("vias", settings.vias),
+        ];
+        for (keyword, value) in switches {
+            if let Some(value) = value {
+                items.push(pair(keyword, on_off(value)));
+            }
+        }
+        let costs = [
+            ("via_costs", settings.via_costs),
+            ("plane_via_costs", settings.plane_via_costs),
+            ("start_ripup_costs", settings.start_ripup_costs),
+        ];
+        for (keyword, value) in costs {
+            if let Some(value) = value {
+                items.push(pair(keyword, number(value)));
+            }
+        }
+        if let Some(pass) = settings.start_pass_no {
+            items.push(pair("start_pass_no", atom(pass.to_string())));
+        }
+        for rule in &settings.layer_rules {
+            items.push(self.layer_rule(rule));
+        }
+        scope(items, &settings.unknown)
+    }
+
+    fn structure(&self, structure: &Structure) -> SExpr {
+        let mut items = vec![atom("structure")];
+        for layer in &structure.layers {
+            items.push(self.layer(layer));
+        }
+        for boundary in &structure.boundaries {
+            items.push(SExpr::List(vec![
+                atom("boundary"),
+                self.path(&boundary.layer, boundary.width, &boundary.points),
+            ]));
+        }
+        for keepout in &structure.keepouts {
+            items.push(self.keepout(keepout));
+        }
+        if !structure.vias.is_empty() {
+            let mut via_items = vec![atom("via")];
+            via_items.extend(structure.vias.iter().map(|via| atom(via.as_str())));
+            items.push(SExpr::List(via_items));
+        }
+        if let Some(rule) = &structure.rule {
+            items.push(self.rule(rule));
+        }
+        if let Some(control) = &structure.control {
+            items.push(self.control(control));
+        }
+        for grid in &structure.grids {
+            items.push(self.grid(grid));
+        }
+        if let Some(settings) = &structure.autoroute_settings {
+            items.push(self.autoroute_settings(settings));
+        }
+        scope(items, &structure.unknown)
+    }
+
+    fn place(&self, instance: &ComponentInst) -> SExpr {
+        let mut items = vec![atom("place"), atom(instance.reference.as_str())];
+        self.push_point(&mut items, instance.position);
+        items.push(atom(match instance.side {
+            Side::Front => "front",
+            Side::Back => "back",
+        }));
+        items.push(number(instance.rotation));
+        scope(items, &instance.unknown)
+    }
+
+    pub(crate) fn component(&self, component: &Component) -> SExpr {
+        let mut items = vec![atom("component"), atom(component.name.as_str())];
+        for instance in &component.instances {
+            items.push(self.place(instance));
+        }
+        SExpr::List(items)
+    }
+
+    fn placement(&self, placement: &Placement) -> SExpr {
+        let mut items = vec![atom("placement")];
+        for component in &placement.components {
+            items.push(self.component(component));
+        }
+        scope(items, &placement.unknown)
+    }
+
+    fn pin(&self, pin: &Pin) -> SExpr {
+        let mut items = vec![atom("pin"), atom(pin.pad_stack_name.as_str())];
+        if pin.rotation != 0.0 {
+            items.push(pair("rotate", number(pin.rotation)));
+        }
+        items.push(atom(pin.pin_id.as_str()));
+        self.push_point(&mut items, pin.position);
+        if let Some(class) = &pin.clearance_class {
+            items.push(pair("clearance_class", atom(class.as_str())));
+        }
+        scope(items, &pin.unknown)
+    }
+
+    fn image(&self, image: &Image) -> SExpr {
+        let mut items = vec![atom("image"), atom(image.name.as_str())];
+        let mut pins: Vec<&Pin> = image.pins.values().collect();
+        // shorter ids first, so that pin 10 follows pin 9 and A10 follows A9
+        pins.sort_by_key(|pin| (pin.pin_id.len(), pin.pin_id.as_str()));
+        for pin in pins {
+            items.push(self.pin(pin));
+        }
+        if let Some(class) = &image.clearance_class {
+            items.push(pair("clearance_class", atom(class.as_str())));
+        }
+        scope(items, &image.unknown)
+    }
+
+    fn keepout(&self, keepout: &Keepout) -> SExpr {
+        let keyword = match keepout.kind {
+            KeepoutKind::Keepout => "keepout",
+            KeepoutKind::ViaKeepout => "via_keepout",
+            KeepoutKind::WireKeepout => "wire_keepout",
+        };
+        let mut items = vec![atom(keyword)];
+        if let Some(name) = &keepout.name {
+            items.push(atom(name.as_str()));
+        }
+        items.push(self.layer_shape(&keepout.shape));
+        scope(items, &keepout.unknown)
+    }
+
+    /// `(circle ...)`, `(rect ...)` or `(polygon ...)`
+    fn layer_shape(&self, layer_shape: &LayerShape) -> SExpr {
+        let layer = atom(layer_shape.layer.as_str());
+        match &layer_shape.shape {
+            Shape::Circle { diameter, center } => {
+                let mut items = vec![atom("circle"), layer, self.length(*diameter)];
+                if *center != Point::ORIGIN {
+                    self.push_point(&mut items, *center);
+                }
+                SExpr::List(items)
+            }
+            Shape::Rect {
+                x_min,
+                y_min,
+                x_max,
+                y_max,
+            } => SExpr::List(vec![
+                atom("rect"),
+                layer,
+                self.length(*x_min),
+                self.length(*y_min),
+                self.length(*x_max),
+                self.length(*y_max),
+            ]),
+            Shape::Polygon {
+                aperture_width,
+                vertices,
+            } => {
+                let mut items = vec![atom("polygon"), layer, self.length(*aperture_width)];
+                for vertex in vertices {
+                    self.push_point(&mut items, *vertex);
+                }
+                SExpr::List(items)
+            }
+        }
+    }
+
+    fn shape(&self, layer_shape: &LayerShape) -> SExpr {
+        SExpr::List(vec![atom("shape"), self.layer_shape(layer_shape)])
+    }
+
+    pub(crate) fn padstack(&self, padstack: &PadStack) -> SExpr {
+        let mut items = vec![atom("padstack"), atom(padstack.name.as_str())];
+        for layer_shape in &padstack.shapes {
+            items.push(self.shape(layer_shape));
+        }
+        if let Some(attach) = padstack.attach {
+            items.push(pair("attach", on_off(attach)));
+        }
+        scope(items, &padstack.unknown)
+    }
+
+    /// Images and padstacks are kept in hash maps, so they are written sorted
+    /// by name to make the output deterministic.
+    fn library(&self, library: &Library) -> SExpr {
+        let mut items = vec![atom("library")];
+        let mut images: Vec<&Image> = library.images.values().collect();
+        images.sort_by(|a, b| a.name.cmp(&b.name));
+        for image in images {
+            items.push(self.image(image));
+        }
+        let mut padstacks: Vec<&PadStack> = library.pad_stacks.values().collect();
+        padstacks.sort_by(|a, b| a.name.cmp(&b.name));
+        for padstack in padstacks {
+            items.push(self.padstack(padstack));
+        }
+        scope(items, &library.unknown)
+    }
+
+    fn net(&self, net: &Net) -> SExpr {
+        let mut pins = vec![atom("pins")];
+        pins.extend(
+            net.pins
+                .iter()
+                .map(|pin| atom(format!("{}-{}", pin.component_name, pin.pin_id))),
+        );
+        let mut items = vec![atom("net"), atom(net.name.as_str()), SExpr::List(pins)];
+        if let Some(rule) = &net.rule {
+            items.push(self.rule(rule));
+        }
+        scope(items, &net.unknown)
+    }
+
+    fn netclass(&self, netclass: &Netclass) -> SExpr {
+        let mut items = vec![atom("class"), atom(netclass.net_class_name.as_str())];
+        items.extend(netclass.net_names.iter().map(|net| atom(net.as_str())));
+        if !netclass.via_name.is_empty() || !netclass.circuit_unknown.is_empty() {
+            let mut circuit = vec![atom("circuit")];
+            if !netclass.via_name.is_empty() {
+                circuit.push(pair("use_via", atom(netclass.via_name.as_str())));
+            }
+            items.push(scope(circuit, &netclass.circuit_unknown));
+        }
+        if let Some(rule) = &netclass.rule {
+            items.push(self.rule(rule));
+        }
+        scope(items, &netclass.unknown)
+    }
+
+    fn class_class(&self, class_class: &ClassClass) -> SExpr {
+        let mut classes = vec![atom("classes")];
+        classes.extend(class_class.classes.iter().map(|class| atom(class.as_str())));
+        scope(
+            vec![
+                atom("class_class"),
+                SExpr::List(classes),
+                self.rule(&class_class.rule),
+            ],
+            &class_class.unknown,
+        )
+    }
+
+    fn network(&self, network: &Network) -> SExpr {
+        let mut items = vec![atom("network")];
+        for net in &network.nets {
+            items.push(self.net(net));
+        }
+        let mut netclasses: Vec<&Netclass> = network.netclasses.values().collect();
+        netclasses.sort_by(|a, b| a.net_class_name.cmp(&b.net_class_name));
+        for netclass in netclasses {
+            items.push(self.netclass(netclass));
+        }
+        for class_class in &network.class_classes {
+            items.push(self.class_class(class_class));
+        }
+        scope(items, &network.unknown)
+    }
+
+    pub(crate) fn wire(&self, wire: &Wire) -> SExpr {
+        let mut items = vec![
+            atom("wire"),
+            self.path(&wire.layer, wire.width, &wire.points),
+        ];
+        if let Some(net) = &wire.net {
+            items.push(pair("net", atom(net.as_str())));
+        }
+        scope(items, &wire.unknown)
+    }
+
+    pub(crate) fn wiring_via(&self, via: &WiringVia) -> SExpr {
+        let mut items = vec![atom("via"), atom(via.padstack_name.as_str())];
+        self.push_point(&mut items, via.position);
+        if let Some(net) = &via.net {
+            items.push(pair("net", atom(net.as_str())));
+        }
+        scope(items, &via.unknown)
+    }
+
+    fn wiring(&self, wiring: &Wiring) -> SExpr {
+        let mut items = vec![atom("wiring")];
+        for wire in &wiring.wires {
+            items.push(self.wire(wire));
+        }
+        for via in &wiring.vias {
+            items.push(self.wiring_via(via));
+        }
+        scope(items, &wiring.unknown)
+    }
+}
+
+/// Converts the design back to the S-expression tree of a DSN file.
+///
+/// Scopes are written in the order KiCad exports them. Lengths are written in
+/// the unit of the design, and scopes the parser kept verbatim are put back
+/// where they were read from.
+pub fn dsn_struct_to_s_expr(dsn: &DsnStruct) -> SExpr {
+    let writer = Writer {
+        unit: dsn.unit,
+        steps_per_unit: None,
+    };
+    let mut items = vec![atom("pcb"), atom(dsn.design.as_str())];
+    if let Some(parser) = &dsn.parser {
+        items.push(writer.parser(parser));
+    }
+    items.push(resolution(&dsn.resolution));
+    items.push(pair("unit", atom(dsn.unit.keyword())));
+    items.push(writer.structure(&dsn.structure));
+    items.push(writer.placement(&dsn.placement));
+    items.push(writer.library(&dsn.library));
+    items.push(writer.network(&dsn.network));
+    if let Some(wiring) = &dsn.wiring {
+        items.push(writer.wiring(wiring));
+    }
+    scope(items, &dsn.unknown)
+}
+
+/// Writes the design as the text of a Specctra DSN file.
+pub fn write_dsn(dsn: &DsnStruct) -> String {
+    let quote = dsn
+        .parser
+        .as_ref()
+        .and_then(|parser| parser.string_quote)
+        .unwrap_or('"');
+    write_s_expr(&dsn_struct_to_s_expr(dsn), quote)
+}
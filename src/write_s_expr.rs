@@ -0,0 +1,122 @@
+use crate::s_expr::SExpr;
+
+/// Lines are wrapped after this many columns where the layout allows it.
+const LINE_WIDTH: usize = 80;
+
+/// Whether `atom` has to be written between quotes to read back as one token.
+///
+/// Specctra has no escape sequences, so an atom containing the quote
+/// character itself cannot be represented and is written as is.
+fn needs_quotes(atom: &str, quote: char) -> bool {
+    atom.is_empty()
+        || atom
+            .chars()
+            .any(|c| c.is_whitespace() || c == '(' || c == ')' || c == quote)
+}
+
+fn write_atom(atom: &str, quote: char, out: &mut String) {
+    if needs_quotes(atom, quote) && !atom.contains(quote) {
+        out.push(quote);
+        out.push_str(atom);
+        out.push(quote);
+    } else {
+        out.push_str(atom);
+    }
+}
+
+/// `(string_quote ")` names the quote character, which must stay bare.
+fn is_string_quote(list: &[SExpr]) -> bool {
+    list.first().and_then(|x| x.as_atom()).map(|s| s.as_str()) == Some("string_quote")
+}
+
+fn write_inline(expr: &SExpr, quote: char, out: &mut String) {
+    match expr {
+        SExpr::Atom(atom) => write_atom(atom, quote, out),
+        SExpr::List(list) if is_string_quote(list) => {
+            out.push('(');
+            for (i, item) in list.iter().enumerate() {
+                if i > 0 {
+                    out.push(' ');
+                }
+                match item {
+                    SExpr::Atom(atom) => out.push_str(atom),
+                    other => write_inline(other, quote, out),
+                }
+            }
+            out.push(')');
+        }
+        SExpr::List(list) => {
+            out.push('(');
+            for (i, item) in list.iter().enumerate() {
+                if i > 0 {
+                    out.push(' ');
+                }
+                write_inline(item, quote, out);
+            }
+            out.push(')');
+        }
+    }
+}
+
+fn write_indented(expr: &SExpr, quote: char, indent: usize, out: &mut String) {
+    let mut inline = String::new();
+    write_inline(expr, quote, &mut inline);
+    let list = match expr {
+        SExpr::List(list) => list,
+        SExpr::Atom(_) => {
+            out.push_str(&inline);
+            return;
+        }
+    };
+    // Short lists with at most one nested list stay on one line, like
+    // `(shape (circle F.Cu 600))`.
+    let nested = list.iter().filter(|item| item.is_list()).count();
+    if nested <= 1 && indent + inline.len() <= LINE_WIDTH {
+        out.push_str(&inline);
+        return;
+    }
+
+    let child_indent = indent + 2;
+    let mut column = indent + 1;
+    out.push('(');
+    let mut first = true;
+    for item in list {
+        match item {
+            SExpr::Atom(_) => {
+                let mut atom = String::new();
+                write_inline(item, quote, &mut atom);
+                if first {
+                    first = false;
+                } else if column + 1 + atom.len() > LINE_WIDTH {
+                    out.push('\n');
+                    out.push_str(&" ".repeat(child_indent));
+                    column = child_indent;
+                } else {
+                    out.push(' ');
+                    column += 1;
+                }
+                out.push_str(&atom);
+                column += atom.len();
+            }
+            SExpr::List(_) => {
+                out.push('\n');
+                out.push_str(&" ".repeat(child_indent));
+                write_indented(item, quote, child_indent, out);
+                first = false;
+                // atoms after a nested list start on a fresh line
+                column = LINE_WIDTH;
+            }
+        }
+    }
+    out.push('\n');
+    out.push_str(&" ".repeat(indent));
+    out.push(')');
+}
+
+/// Writes `expr` as indented Specctra text, quoting atoms with `quote` where needed.
+pub fn write_s_expr(expr: &SExpr, quote: char) -> String {
+    let mut out = String::new();
+    write_indented(expr, quote, 0, &mut out);
+    out.push('\n');
+    out
+}
@@ -0,0 +1,166 @@
+use crate::{
+    diagnostics::ScopePath,
+    dsn_error::{DsnError, DsnErrorKind},
+    dsn_struct::{DsnStruct, PadStack, Pin2, Resolution},
+    s_expr::SExpr,
+    session_struct::{Routes, SessionNet, SessionPlacement, SessionStruct, WasIs},
+    write_dsn::{Writer, atom, pair, resolution, scope},
+    write_s_expr::write_s_expr,
+};
+
+/// Writer for a scope with its own `(resolution ...)`.
+fn writer(resolution: &Resolution) -> Writer {
+    Writer {
+        unit: resolution.unit,
+        steps_per_unit: Some(resolution.value),
+    }
+}
+
+fn placement(placement: &SessionPlacement) -> SExpr {
+    let writer = writer(&placement.resolution);
+    let mut items = vec![atom("placement"), resolution(&placement.resolution)];
+    for component in &placement.components {
+        items.push(writer.component(component));
+    }
+    scope(items, &placement.unknown)
+}
+
+fn pin_reference(pin: &Pin2) -> SExpr {
+    atom(format!("{}-{}", pin.component_name, pin.pin_id))
+}
+
+fn was_is(was_is: &WasIs) -> SExpr {
+    let mut items = vec![atom("was_is")];
+    for swap in &was_is.pin_swaps {
+        items.push(SExpr::List(vec![
+            atom("pins"),
+            pin_reference(&swap.was),
+            pin_reference(&swap.is),
+        ]));
+    }
+    scope(items, &was_is.unknown)
+}
+
+fn net(writer: &Writer, net: &SessionNet) -> SExpr {
+    let mut items = vec![atom("net"), atom(net.name.as_str())];
+    for wire in &net.wires {
+        items.push(writer.wire(wire));
+    }
+    for via in &net.vias {
+        items.push(writer.wiring_via(via));
+    }
+    scope(items, &net.unknown)
+}
+
+fn routes(routes: &Routes) -> SExpr {
+    let writer = writer(&routes.resolution);
+    let mut items = vec![atom("routes"), resolution(&routes.resolution)];
+    if let Some(parser) = &routes.parser {
+        items.push(writer.parser(parser));
+    }
+    let mut library_out = vec![atom("library_out")];
+    for padstack in &routes.library_out {
+        library_out.push(writer.padstack(padstack));
+    }
+    items.push(scope(library_out, &routes.library_out_unknown));
+    let mut network_out = vec![atom("network_out")];
+    for session_net in &routes.network_out {
+        network_out.push(net(&writer, session_net));
+    }
+    items.push(scope(network_out, &routes.network_out_unknown));
+    scope(items, &routes.unknown)
+}
+
+/// Converts the session to the S-expression tree of a SES file. Lengths are
+/// written as whole steps of the resolution of their scope.
+pub fn session_struct_to_s_expr(session: &SessionStruct) -> SExpr {
+    let mut items = vec![
+        atom("session"),
+        atom(session.name.as_str()),
+        pair("base_design", atom(session.base_design.as_str())),
+    ];
+    if let Some(session_placement) = &session.placement {
+        items.push(placement(session_placement));
+    }
+    if let Some(session_was_is) = &session.was_is {
+        items.push(was_is(session_was_is));
+    }
+    if let Some(session_routes) = &session.routes {
+        items.push(routes(session_routes));
+    }
+    scope(items, &session.unknown)
+}
+
+/// Writes the session as the text of a Specctra SES file.
+pub fn write_session(session: &SessionStruct) -> String {
+    let quote = session
+        .routes
+        .as_ref()
+        .and_then(|routes| routes.parser.as_ref())
+        .and_then(|parser| parser.string_quote)
+        .unwrap_or('"');
+    write_s_expr(&session_struct_to_s_expr(session), quote)
+}
+
+/// Builds the session a router hands back for `dsn`: the placement of the
+/// design unchanged and `nets` as the routed copper.
+///
+/// The padstacks of the vias are looked up in the library of the design and
+/// listed in `library_out`, where KiCad's session import finds them.
+pub fn routed_session(dsn: &DsnStruct, nets: Vec<SessionNet>) -> Result<SessionStruct, DsnError> {
+    let mut library_out: Vec<PadStack> = Vec::new();
+    for session_net in &nets {
+        for via in &session_net.vias {
+            if library_out
+                .iter()
+                .any(|padstack| padstack.name == via.padstack_name)
+            {
+                continue;
+            }
+            let padstack = dsn
+                .library
+                .pad_stacks
+                .get(&via.padstack_name)
+                .ok_or_else(|| {
+                    DsnError::new(
+                        DsnErrorKind::DanglingReference {
+                            kind: "padstack".to_string(),
+                            name: via.padstack_name.clone(),
+                        },
+                        ScopePath::from_segments(vec![
+                            "session".to_string(),
+                            "routes".to_string(),
+                            "network_out".to_string(),
+                            ScopePath::segment("net", &session_net.name),
+                        ]),
+                    )
+                })?;
+            library_out.push(padstack.clone());
+        }
+    }
+
+    let name = dsn.design_name().to_string();
+    Ok(SessionStruct {
+        name: name.clone(),
+        base_design: name,
+        placement: Some(SessionPlacement {
+            resolution: dsn.resolution.clone(),
+            components: dsn.placement.components.clone(),
+            unknown: Vec::new(),
+        }),
+        was_is: Some(WasIs {
+            pin_swaps: Vec::new(),
+            unknown: Vec::new(),
+        }),
+        routes: Some(Routes {
+            resolution: dsn.resolution.clone(),
+            parser: dsn.parser.clone(),
+            library_out,
+            library_out_unknown: Vec::new(),
+            network_out: nets,
+            network_out_unknown: Vec::new(),
+            unknown: Vec::new(),
+        }),
+        unknown: Vec::new(),
+    })
+}
@@ -0,0 +1,149 @@
+//! Builds the small boards the tests read. A board starts as a 20 mm square
+//! on F.Cu and B.Cu, in um; each test adds the parts, nets and copper it is
+//! about.
+
+// every test file uses a different part of the builder
+#![allow(dead_code)]
+
+use pcb_parsing::dsn_struct::DsnStruct;
+use pcb_parsing::parse_to_s_expr::parse_dsn_to_s_expr;
+use pcb_parsing::parse_to_struct::parse_s_expr_to_struct;
+
+pub struct Board {
+    name: String,
+    resolution: String,
+    layers: Vec<String>,
+    outline: String,
+    structure: Vec<String>,
+    vias: Vec<String>,
+    /// Placements grouped by image, in the order the images were first used.
+    components: Vec<(String, Vec<String>)>,
+    library: Vec<String>,
+    network: Vec<String>,
+    wiring: Vec<String>,
+}
+
+impl Board {
+    pub fn new(name: &str) -> Board {
+        Board {
+            name: name.to_string(),
+            resolution: "um 10".to_string(),
+            layers: vec!["F.Cu".to_string(), "B.Cu".to_string()],
+            outline: "0 0 20000 0 20000 -20000 0 -20000 0 0".to_string(),
+            structure: Vec::new(),
+            vias: Vec::new(),
+            components: Vec::new(),
+            library: Vec::new(),
+            network: Vec::new(),
+            wiring: Vec::new(),
+        }
+    }
+
+    /// Replaces the `um 10` resolution, e.g. with `mil 1`.
+    pub fn resolution(mut self, resolution: &str) -> Board {
+        self.resolution = resolution.to_string();
+        self
+    }
+
+    /// Replaces the signal layers, from top to bottom.
+    pub fn layers(mut self, layers: &[&str]) -> Board {
+        self.layers = layers.iter().map(|layer| layer.to_string()).collect();
+        self
+    }
+
+    /// Replaces the points of the square board outline.
+    pub fn outline(mut self, points: &str) -> Board {
+        self.outline = points.to_string();
+        self
+    }
+
+    /// Adds a scope at the end of the structure, e.g. a keepout.
+    pub fn structure(mut self, item: &str) -> Board {
+        self.structure.push(item.to_string());
+        self
+    }
+
+    /// Makes `name` a via of the structure, with a padstack of `shapes`.
+    pub fn via(mut self, name: &str, shapes: &str) -> Board {
+        self.vias.push(format!("\"{}\"", name));
+        self.library
+            .push(format!("(padstack \"{}\" {})", name, shapes));
+        self
+    }
+
+    /// Places `image`, e.g. `place("R", "R1 5000 -5000 front 0")`.
+    pub fn place(mut self, image: &str, place: &str) -> Board {
+        let place = format!("(place {})", place);
+        match self.components.iter_mut().find(|(name, _)| name == image) {
+            Some((_, places)) => places.push(place),
+            None => self.components.push((image.to_string(), vec![place])),
+        }
+        self
+    }
+
+    /// Adds an image or padstack to the library.
+    pub fn library(mut self, item: &str) -> Board {
+        self.library.push(item.to_string());
+        self
+    }
+
+    /// Adds a net, class or class_class to the network.
+    pub fn network(mut self, item: &str) -> Board {
+        self.network.push(item.to_string());
+        self
+    }
+
+    /// Adds a wire or via to the wiring.
+    pub fn wiring(mut self, item: &str) -> Board {
+        self.wiring.push(item.to_string());
+        self
+    }
+
+    pub fn text(&self) -> String {
+        let mut text = format!(
+            "(pcb {}\n  (resolution {})\n  (unit um)\n  (structure\n",
+            self.name, self.resolution
+        );
+        for layer in &self.layers {
+            text += &format!("    (layer {} (type signal))\n", layer);
+        }
+        text += &format!("    (boundary (path pcb 0 {}))\n", self.outline);
+        if !self.vias.is_empty() {
+            text += &format!("    (via {})\n", self.vias.join(" "));
+        }
+        section(&mut text, &self.structure);
+        text += "  )\n  (placement\n";
+        for (image, places) in &self.components {
+            text += &format!("    (component {}\n", image);
+            for place in places {
+                text += &format!("      {}\n", place);
+            }
+            text += "    )\n";
+        }
+        text += "  )\n  (library\n";
+        section(&mut text, &self.library);
+        text += "  )\n  (network\n";
+        section(&mut text, &self.network);
+        text += "  )\n";
+        if !self.wiring.is_empty() {
+            text += "  (wiring\n";
+            section(&mut text, &self.wiring);
+            text += "  )\n";
+        }
+        text + ")"
+    }
+
+    pub fn parse(&self) -> DsnStruct {
+        parse(&self.text())
+    }
+}
+
+fn section(text: &mut String, items: &[String]) {
+    for item in items {
+        *text += &format!("    {}\n", item);
+    }
+}
+
+pub fn parse(input: &str) -> DsnStruct {
+    parse_s_expr_to_struct(&parse_dsn_to_s_expr(input).unwrap()).unwrap()
+}
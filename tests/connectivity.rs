@@ -0,0 +1,85 @@
+//! Vias and through-hole pads join the copper of every layer they span,
+//! including when their shapes are on `signal`.
+
+use pcb_parsing::connectivity::{NetCompletion, completeness_report, route_completeness};
+
+mod common;
+use common::Board;
+
+/// Net A runs from a top pad to a bottom pad, changing layers at a via in
+/// the middle. Net B reaches a through-hole pad on the bottom layer only.
+fn board(via_shapes: &str) -> Board {
+    Board::new("connectivity")
+        .via("V", via_shapes)
+        .place("Top", "J1 2000 -5000 front 0")
+        .place("Bottom", "J2 18000 -5000 front 0")
+        .place("Bottom", "J4 18000 -12000 front 0")
+        .place("Through", "J3 10000 -12000 front 0")
+        .library("(image Top (pin TopPad 1 0 0))")
+        .library("(image Bottom (pin BottomPad 1 0 0))")
+        .library("(image Through (pin ThroughPad 1 0 0))")
+        .library("(padstack TopPad (shape (rect F.Cu -500 -500 500 500)))")
+        .library("(padstack BottomPad (shape (rect B.Cu -500 -500 500 500)))")
+        .library("(padstack ThroughPad (shape (circle signal 1000)))")
+        .network("(net A (pins J1-1 J2-1))")
+        .network("(net B (pins J3-1 J4-1))")
+        .network("(class default A B (circuit (use_via V)))")
+        .wiring("(wire (path F.Cu 250 2000 -5000 10000 -5000) (net A))")
+        .wiring("(wire (path B.Cu 250 10000 -5000 18000 -5000) (net A))")
+        .wiring("(wire (path B.Cu 250 10000 -12000 18000 -12000) (net B))")
+}
+
+/// The board with the via on `signal` placed.
+fn routed() -> Board {
+    board("(shape (circle signal 600))").wiring("(via V 10000 -5000 (net A))")
+}
+
+fn completions(board: &Board) -> Vec<NetCompletion> {
+    route_completeness(&board.parse()).unwrap()
+}
+
+fn net_a(board: &Board) -> NetCompletion {
+    completions(board).remove(0)
+}
+
+#[test]
+fn via_on_signal_joins_both_layers() {
+    let completions = completions(&routed());
+    assert_eq!(
+        completeness_report(&completions),
+        "net A: 100% routed, 0 of 1 connections open\n\
+         net B: 100% routed, 0 of 1 connections open\n\
+         2 of 2 nets complete, 0 of 2 connections open\n"
+    );
+}
+
+#[test]
+fn via_with_a_pad_per_layer_joins_both_layers() {
+    let board = board("(shape (circle F.Cu 600)) (shape (circle B.Cu 600))")
+        .wiring("(via V 10000 -5000 (net A))");
+    assert!(net_a(&board).is_complete());
+}
+
+#[test]
+fn via_on_one_layer_leaves_the_net_open() {
+    let board = board("(shape (circle F.Cu 600))").wiring("(via V 10000 -5000 (net A))");
+    let net = net_a(&board);
+    assert_eq!(
+        net.groups,
+        [vec!["J1-1".to_string()], vec!["J2-1".to_string()]]
+    );
+    assert_eq!(net.completion(), 0.0);
+}
+
+#[test]
+fn missing_via_leaves_the_net_open() {
+    let board = board("(shape (circle signal 600))");
+    assert_eq!(net_a(&board).unrouted(), 1);
+}
+
+#[test]
+fn through_hole_pad_on_signal_meets_a_bottom_wire() {
+    let net_b = completions(&routed()).remove(1);
+    assert_eq!(net_b.net, "B");
+    assert!(net_b.is_complete());
+}
@@ -0,0 +1,164 @@
+//! Control switches, grids and autoroute settings are read into the
+//! structure and written back as they were.
+
+use pcb_parsing::dsn_error::DsnErrorKind;
+use pcb_parsing::dsn_struct::{GridDirection, GridKind, PreferredDirection};
+use pcb_parsing::parse_to_s_expr::parse_dsn_to_s_expr;
+use pcb_parsing::parse_to_struct::parse_s_expr_to_struct;
+use pcb_parsing::units::Length;
+use pcb_parsing::write_dsn::write_dsn;
+
+mod common;
+use common::{Board, parse};
+
+/// The settings Freerouting writes for a two-layer board.
+fn board() -> Board {
+    Board::new("control")
+        .structure("(control (off_grid on) (via_at_smd off))")
+        .structure("(grid via 50)")
+        .structure("(grid wire 25 (direction x) (offset 10))")
+        .structure(
+            "(autoroute_settings
+      (fanout off)
+      (autoroute on)
+      (postroute on)
+      (vias on)
+      (via_costs 50)
+      (plane_via_costs 5)
+      (start_ripup_costs 100)
+      (start_pass_no 3)
+      (layer_rule F.Cu
+        (active on)
+        (preferred_direction horizontal)
+        (preferred_direction_trace_costs 1.0)
+        (against_preferred_direction_trace_costs 2.7)
+      )
+      (layer_rule B.Cu
+        (active on)
+        (preferred_direction vertical)
+      )
+    )",
+        )
+}
+
+#[test]
+fn control_switches_are_read() {
+    let dsn = board().parse();
+    let control = dsn.structure.control.unwrap();
+    assert_eq!(control.off_grid, Some(true));
+    assert_eq!(control.via_at_smd, Some(false));
+
+    // switches absent from the file stay unset
+    let dsn = Board::new("control")
+        .structure("(control (via_at_smd on))")
+        .parse();
+    assert_eq!(dsn.structure.control.unwrap().off_grid, None);
+    assert_eq!(Board::new("control").parse().structure.control, None);
+}
+
+#[test]
+fn grids_are_read_in_file_order() {
+    let dsn = board().parse();
+    let grids = &dsn.structure.grids;
+    assert_eq!(grids.len(), 2);
+    assert_eq!(
+        (
+            grids[0].kind,
+            grids[0].step,
+            grids[0].direction,
+            grids[0].offset
+        ),
+        (GridKind::Via, Length::from_um(50.0), None, None)
+    );
+    assert_eq!(
+        (
+            grids[1].kind,
+            grids[1].step,
+            grids[1].direction,
+            grids[1].offset
+        ),
+        (
+            GridKind::Wire,
+            Length::from_um(25.0),
+            Some(GridDirection::X),
+            Some(Length::from_um(10.0))
+        )
+    );
+}
+
+#[test]
+fn autoroute_settings_are_read() {
+    let dsn = board().parse();
+    let settings = dsn.structure.autoroute_settings.unwrap();
+    assert_eq!(
+        (
+            settings.fanout,
+            settings.autoroute,
+            settings.postroute,
+            settings.vias
+        ),
+        (Some(false), Some(true), Some(true), Some(true))
+    );
+    assert_eq!(settings.via_costs, Some(50.0));
+    assert_eq!(settings.plane_via_costs, Some(5.0));
+    assert_eq!(settings.start_ripup_costs, Some(100.0));
+    assert_eq!(settings.start_pass_no, Some(3));
+
+    let front = &settings.layer_rules[0];
+    assert_eq!(front.layer, "F.Cu");
+    assert_eq!(front.active, Some(true));
+    assert_eq!(
+        front.preferred_direction,
+        Some(PreferredDirection::Horizontal)
+    );
+    assert_eq!(front.preferred_direction_trace_costs, Some(1.0));
+    assert_eq!(front.against_preferred_direction_trace_costs, Some(2.7));
+    let back = &settings.layer_rules[1];
+    assert_eq!(back.preferred_direction, Some(PreferredDirection::Vertical));
+    assert_eq!(back.preferred_direction_trace_costs, None);
+}
+
+#[test]
+fn settings_round_trip() {
+    let dsn = board().parse();
+    let written = write_dsn(&dsn);
+    for scope in [
+        "(off_grid on)",
+        "(grid via 50)",
+        "(direction x)",
+        "(against_preferred_direction_trace_costs 2.7)",
+    ] {
+        assert!(
+            written.contains(scope),
+            "{} missing from\n{}",
+            scope,
+            written
+        );
+    }
+    assert_eq!(parse(&written), dsn);
+}
+
+#[test]
+fn unknown_setting_values_are_rejected() {
+    for (from, to, field, value) in [
+        ("(grid via 50)", "(grid hex 50)", "grid kind", "hex"),
+        ("(direction x)", "(direction z)", "grid direction", "z"),
+        ("(off_grid on)", "(off_grid yes)", "off_grid", "yes"),
+        (
+            "(preferred_direction vertical)",
+            "(preferred_direction diagonal)",
+            "preferred direction",
+            "diagonal",
+        ),
+    ] {
+        let input = board().text().replace(from, to);
+        let error = parse_s_expr_to_struct(&parse_dsn_to_s_expr(&input).unwrap()).unwrap_err();
+        assert_eq!(
+            error.kind,
+            DsnErrorKind::InvalidValue {
+                field: field.to_string(),
+                value: value.to_string(),
+            }
+        );
+    }
+}
@@ -0,0 +1,104 @@
+//! The diff reports what changed in the design, not in the text.
+
+use pcb_parsing::design_diff::{Change, diff_designs, diff_report};
+
+mod common;
+use common::Board;
+
+const R1: &str = "R1 5000 -5000 front 0";
+const R2: &str = "R2 15000 -5000 front 90";
+const CLASS_CLASS: &str = "(class_class (classes Power Signal) (rule (clearance 400)))";
+
+/// The library both versions of the design use.
+fn parts(resolution: &str) -> Board {
+    Board::new("diff")
+        .resolution(resolution)
+        .layers(&["F.Cu"])
+        .via("Via_600", "(shape (circle F.Cu 600))")
+        .library("(image R (pin Round 1 -800 0) (pin Round 2 800 0))")
+        .library("(image C (pin Round 1 -800 0) (pin Round 2 800 0))")
+        .library("(padstack Round (shape (circle F.Cu 800)))")
+}
+
+/// The old design with R1 and R2 placed as given and no class_class rule.
+fn old_with(r1: &str, r2: &str) -> Board {
+    parts("um 10")
+        .place("R", r1)
+        .place("R", r2)
+        .place("C", "C1 5000 -15000 front 0")
+        .network("(net VCC (pins R1-1 C1-1))")
+        .network("(net OUT (pins R1-2 R2-1))")
+        .network("(class Power VCC (circuit (use_via Via_600)) (rule (width 500)))")
+        .network("(class Signal OUT (circuit (use_via Via_600)) (rule (width 200)))")
+}
+
+fn old() -> Board {
+    old_with(R1, R2).network(CLASS_CLASS)
+}
+
+/// The old design with R1 turned a full circle, which is no change, and a
+/// little of everything else changed.
+fn new() -> Board {
+    parts("mil 1")
+        .place("R", "R2 16000 -5000 front -180")
+        .place("R", "R1 5000 -5000 front 360")
+        .place("C", "R3 5000 -15000 back 0")
+        .network("(net OUT (pins R1-2 R2-1 R3-2))")
+        .network("(net VCC (pins R1-1))")
+        .network("(net GND (pins R3-1))")
+        .network("(class Power VCC OUT (circuit (use_via Via_600)) (rule (width 500)))")
+        .network("(class Ground GND (circuit (use_via Via_600)) (rule (width 300)))")
+        .network("(class_class (classes Signal Power) (rule (clearance 500)))")
+        .network("(class_class (classes Ground Power) (rule (clearance 600)))")
+}
+
+#[test]
+fn identical_designs_have_no_changes() {
+    let changes = diff_designs(&old().parse(), &old().parse());
+    assert!(changes.is_empty());
+    assert_eq!(diff_report(&changes), "no changes\n");
+}
+
+#[test]
+fn whole_turns_are_not_rotations() {
+    let turned =
+        old_with("R1 5000 -5000 front 360", "R2 15000 -5000 front -270").network(CLASS_CLASS);
+    assert!(diff_designs(&old().parse(), &turned.parse()).is_empty());
+}
+
+#[test]
+fn report_lists_every_change() {
+    let changes = diff_designs(&old().parse(), &new().parse());
+    let report = diff_report(&changes);
+    assert_eq!(
+        report,
+        "component C1 removed (C)\n\
+         component R2 moved from (15mm, -5mm) to (16mm, -5mm)\n\
+         component R2 rotated from 90° to -180°\n\
+         component R3 added (C)\n\
+         net OUT pins changed: +R3-2\n\
+         net VCC pins changed: -C1-1\n\
+         net GND added\n\
+         net OUT moved from class Signal to class Power\n\
+         class Signal removed\n\
+         class Ground added\n\
+         class_class Power Signal rule changed from clearance 0.4mm to clearance 0.5mm\n\
+         class_class Ground Power added\n",
+        "{:#?}",
+        changes
+    );
+}
+
+#[test]
+fn class_class_changes_ignore_class_order() {
+    let reordered =
+        old_with(R1, R2).network("(class_class (classes Signal Power) (rule (clearance 400)))");
+    assert!(diff_designs(&old().parse(), &reordered.parse()).is_empty());
+
+    assert_eq!(
+        diff_designs(&old().parse(), &old_with(R1, R2).parse()),
+        [Change::ClassClassRemoved {
+            classes: vec!["Power".to_string(), "Signal".to_string()],
+        }]
+    );
+}
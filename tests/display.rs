@@ -0,0 +1,47 @@
+//! Pads are drawn where their copper is, which need not be the pin origin.
+
+use pcb_parsing::geometry::Point;
+use pcb_parsing::parse_to_display::dsn_to_display;
+use pcb_parsing::pcb_problem::NetName;
+use pcb_parsing::units::Length;
+
+mod common;
+use common::Board;
+
+/// J1 is turned a quarter turn. Its pin 1 has a rect pad reaching 2 mm to
+/// the right of the pin, pin 2 is itself turned a quarter turn and has a
+/// circle 0.5 mm to the right of the pin.
+fn board() -> Board {
+    Board::new("display")
+        .layers(&["F.Cu"])
+        .via("V", "(shape (circle signal 600))")
+        .place("Offset", "J1 10000 -10000 front 90")
+        .library("(image Offset (pin RectPad 1 1000 0) (pin CirclePad (rotate 90) 2 -1000 0))")
+        .library("(padstack RectPad (shape (rect F.Cu 0 -500 2000 500)))")
+        .library("(padstack CirclePad (shape (circle F.Cu 400 500 0)))")
+        .network("(net A (pins J1-1 J1-2))")
+        .network("(class default A (circuit (use_via V)))")
+}
+
+fn um(x: f64, y: f64) -> Point {
+    Point::new(Length::from_um(x), Length::from_um(y))
+}
+
+#[test]
+fn offset_pad_shapes_follow_the_pin_transform() {
+    let (display, _) = dsn_to_display(board().parse()).unwrap();
+    let pads = &display.nets[&NetName("A".to_string())].pads;
+    let positions: Vec<(&str, Point)> = pads
+        .iter()
+        .map(|pad| (pad.name.0.as_str(), pad.position))
+        .collect();
+    assert_eq!(
+        positions,
+        [
+            // pin (1, 0) + centre (1, 0), turned to (0, 2)
+            ("J1-1", um(10000.0, -8000.0)),
+            // pin (-1, 0) + centre turned to (0, 0.5), turned to (-0.5, -1)
+            ("J1-2", um(9500.0, -11000.0)),
+        ]
+    );
+}
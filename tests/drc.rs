@@ -0,0 +1,102 @@
+//! Each rule check finds its own kind of violation, and a clean board has
+//! none.
+
+use pcb_parsing::drc::check_design;
+
+mod common;
+use common::Board;
+
+const VCC_WIRE: &str = "(wire (path F.Cu 250 2000 -5000 18000 -5000) (net VCC))";
+const GND_WIRE: &str = "(wire (path F.Cu 250 2000 -15000 18000 -15000) (net GND))";
+
+/// Two nets across a 20 mm board, VCC at y = -5 mm and GND at y = -15 mm,
+/// not routed yet.
+fn unrouted() -> Board {
+    Board::new("drc")
+        .via("V", "(shape (circle signal 600))")
+        .structure("(rule (width 250) (clearance 200))")
+        .place("Top", "J1 2000 -5000 front 0")
+        .place("Top", "J2 18000 -5000 front 0")
+        .place("Top", "J3 2000 -15000 front 0")
+        .place("Top", "J4 18000 -15000 front 0")
+        .library("(image Top (pin TopPad 1 0 0))")
+        .library("(padstack TopPad (shape (rect F.Cu -500 -500 500 500)))")
+        .network("(net VCC (pins J1-1 J2-1))")
+        .network("(net GND (pins J3-1 J4-1))")
+        .network("(class default VCC GND (circuit (use_via V)))")
+}
+
+/// Both nets routed straight across. Each test adds to it.
+fn board() -> Board {
+    unrouted().wiring(VCC_WIRE).wiring(GND_WIRE)
+}
+
+fn violations(board: Board) -> Vec<String> {
+    check_design(&board.parse())
+        .unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn routed_board_is_clean() {
+    assert_eq!(violations(board()), Vec::<String>::new());
+}
+
+#[test]
+fn narrow_wire_breaks_the_width_rule() {
+    let board = unrouted()
+        .wiring("(wire (path F.Cu 150 2000 -5000 18000 -5000) (net VCC))")
+        .wiring(GND_WIRE);
+    assert_eq!(
+        violations(board),
+        ["wire #0 of net VCC at (2mm, -5mm) is 0.15mm wide, minimum 0.25mm"]
+    );
+}
+
+#[test]
+fn close_wires_of_different_nets_break_the_clearance() {
+    let board = board().wiring("(wire (path F.Cu 250 8000 -5300 12000 -5300) (net GND))");
+    assert_eq!(
+        violations(board),
+        ["wire #0 and wire #2 on F.Cu at (8mm, -5.15mm) are 0.05mm apart, minimum 0.2mm"]
+    );
+}
+
+#[test]
+fn via_off_the_board_is_outside() {
+    let board = board().wiring("(via V 25000 -10000 (net VCC))");
+    assert_eq!(
+        violations(board),
+        ["via #0 at (25mm, -10mm) is outside the board"]
+    );
+}
+
+#[test]
+fn wire_through_a_keepout_is_reported() {
+    let board = board().structure("(keepout k1 (rect F.Cu 9000 -6000 11000 -4000))");
+    assert_eq!(
+        violations(board),
+        ["wire #0 on F.Cu at (11mm, -5mm) is inside keepout k1"]
+    );
+}
+
+#[test]
+fn wire_through_a_pcb_hole_reaches_into_a_cutout() {
+    let board = board().structure(
+        "(boundary (path pcb 0 9000 -4000 11000 -4000 11000 -6000 9000 -6000 9000 -4000))",
+    );
+    assert_eq!(
+        violations(board),
+        ["wire #0 on F.Cu at (11mm, -5mm) reaches into a board cutout"]
+    );
+}
+
+#[test]
+fn signal_boundary_is_not_a_cutout() {
+    let board = board().structure(
+        "(boundary (path signal 0 500 -500 19500 -500 19500 -19500 500 -19500 500 -500))",
+    );
+    assert_eq!(violations(board), Vec::<String>::new());
+}
@@ -0,0 +1,150 @@
+//! Malformed input fails with the kind of error that describes it, in the
+//! scope it was found in.
+
+use pcb_parsing::dsn_error::{DsnError, DsnErrorKind};
+use pcb_parsing::parse_to_s_expr::parse_dsn_to_s_expr;
+use pcb_parsing::parse_to_struct::parse_s_expr_to_struct;
+
+mod common;
+use common::Board;
+
+/// One resistor pin on one net.
+fn board() -> Board {
+    Board::new("errors")
+        .place("R", "R1 5000 -5000 front 0")
+        .library("(image R (pin Round 1 -800 0))")
+        .library("(padstack Round (shape (circle F.Cu 800)))")
+        .network("(net A (pins R1-1))")
+}
+
+/// The error of the board text with `from` replaced by `to`.
+fn error(from: &str, to: &str) -> DsnError {
+    let input = board().text().replace(from, to);
+    parse_s_expr_to_struct(&parse_dsn_to_s_expr(&input).unwrap()).unwrap_err()
+}
+
+fn invalid_value(field: &str, value: &str) -> DsnErrorKind {
+    DsnErrorKind::InvalidValue {
+        field: field.to_string(),
+        value: value.to_string(),
+    }
+}
+
+#[test]
+fn well_formed_board_parses() {
+    board().parse();
+}
+
+#[test]
+fn session_is_not_a_design() {
+    let error = error("(pcb errors", "(session errors");
+    assert_eq!(
+        error.kind,
+        DsnErrorKind::UnexpectedKeyword {
+            expected: "pcb".to_string(),
+            found: "session".to_string(),
+        }
+    );
+    // nothing is entered yet, so there is no scope to name
+    assert_eq!(error.to_string(), "expected 'pcb', found 'session'");
+}
+
+#[test]
+fn missing_resolution_is_reported_on_the_design() {
+    let error = error("  (resolution um 10)\n", "");
+    assert_eq!(
+        error.kind,
+        DsnErrorKind::MissingField {
+            field: "resolution".to_string(),
+        }
+    );
+    assert_eq!(error.to_string(), "pcb: missing resolution");
+}
+
+#[test]
+fn short_pin_misses_its_position() {
+    let error = error("(pin Round 1 -800 0)", "(pin Round 1 -800)");
+    assert_eq!(
+        error.to_string(),
+        "pcb > library > image R > pin 1: missing pin position y"
+    );
+}
+
+#[test]
+fn bad_numbers_and_values_name_their_field() {
+    let place = "R1 5000 -5000 front 0";
+    assert_eq!(
+        error(place, "R1 5000 abc front 0").kind,
+        DsnErrorKind::InvalidNumber {
+            field: "position y".to_string(),
+            value: "abc".to_string(),
+        }
+    );
+    let invalid_values = [
+        error(place, "R1 5000 -5000 middle 0").kind,
+        error("(unit um)", "(unit furlong)").kind,
+        error("(pins R1-1)", "(pins R1)").kind,
+    ];
+    assert_eq!(
+        invalid_values,
+        [
+            invalid_value("side", "middle"),
+            invalid_value("unit", "furlong"),
+            invalid_value("pin reference", "R1"),
+        ]
+    );
+}
+
+#[test]
+fn context_is_the_innermost_scope() {
+    let error = error("R1 5000 -5000 front 0", "R1 5000 -5000 middle 0");
+    assert_eq!(
+        error.context.segments(),
+        ["pcb", "placement", "component R", "place R1"]
+    );
+    assert_eq!(
+        error.to_string(),
+        "pcb > placement > component R > place R1: invalid value for side: 'middle'"
+    );
+}
+
+#[test]
+fn atom_where_a_scope_belongs_is_unexpected() {
+    let error = error("(shape (circle F.Cu 800))", "(shape circle)");
+    assert_eq!(
+        error.kind,
+        DsnErrorKind::UnexpectedExpr {
+            expected: "a list".to_string(),
+            found: "atom 'circle'".to_string(),
+        }
+    );
+    assert_eq!(
+        error.context.to_string(),
+        "pcb > library > padstack Round > shape circle"
+    );
+}
+
+#[test]
+fn polygon_with_a_dangling_coordinate_is_rejected_like_a_path() {
+    let shape = "(shape (circle F.Cu 800))";
+    let polygon = error(shape, "(shape (polygon F.Cu 0 -400 0 400 0 0 400 0))");
+    assert_eq!(
+        polygon.to_string(),
+        "pcb > library > padstack Round > shape: \
+         invalid value for polygon vertex coordinates: '7 numbers'"
+    );
+    let path = error("(path pcb 0 0 0 20000 0", "(path pcb 0 0 0 20000 0 20000");
+    assert_eq!(
+        path.kind,
+        invalid_value("path point coordinates", "11 numbers")
+    );
+}
+
+#[test]
+fn empty_polygon_is_rejected() {
+    let error = error("(shape (circle F.Cu 800))", "(shape (polygon F.Cu 0))");
+    assert_eq!(
+        error.to_string(),
+        "pcb > library > padstack Round > shape: missing polygon vertices"
+    );
+}
@@ -0,0 +1,90 @@
+//! The index resolves pins, nets, classes and vias by name, and refuses a
+//! design whose scopes refer to things it does not define.
+
+use pcb_parsing::dsn_index::DsnIndex;
+use pcb_parsing::units::Length;
+
+mod common;
+use common::Board;
+
+/// R1 has one pin on net VCC in class Power and one free pin, and net GND
+/// has no pins and no class.
+fn board() -> Board {
+    Board::new("index")
+        .via("V", "(shape (circle signal 600))")
+        .place("R", "R1 5000 -5000 front 0")
+        .library("(image R (pin Round 1 -800 0) (pin Square 2 800 0))")
+        .library("(padstack Round (shape (circle F.Cu 800)))")
+        .library("(padstack Square (shape (rect F.Cu -400 -400 400 400)))")
+        .network("(net VCC (pins R1-1))")
+        .network("(net GND (pins))")
+        .network("(class Power VCC (circuit (use_via V)))")
+}
+
+/// Every error building the index of `board` reports, sorted.
+fn errors(board: Board) -> Vec<String> {
+    let dsn = board.parse();
+    let Err(errors) = DsnIndex::new(&dsn) else {
+        panic!("index built despite dangling references");
+    };
+    let mut errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+    errors.sort();
+    errors
+}
+
+#[test]
+fn pins_resolve_to_their_instance_padstack_and_net() {
+    let dsn = board().parse();
+    let index = DsnIndex::new(&dsn).unwrap();
+
+    assert_eq!(index.instance("R1").unwrap().reference, "R1");
+    assert_eq!(index.image("R1").unwrap().name, "R");
+    assert_eq!(index.pin("R1", "2").unwrap().pad_stack_name, "Square");
+    assert_eq!(index.padstack("R1", "1").unwrap().name, "Round");
+    assert_eq!(index.net("R1", "1").unwrap().name, "VCC");
+    assert!(index.net("R1", "2").is_none());
+    assert!(index.instance("R2").is_none());
+    assert!(index.pin("R1", "3").is_none());
+}
+
+#[test]
+fn nets_resolve_to_their_class_and_via() {
+    let dsn = board().parse();
+    let index = DsnIndex::new(&dsn).unwrap();
+
+    assert_eq!(index.net_class("VCC").unwrap().net_class_name, "Power");
+    // a net no class lists has none
+    assert!(index.net_by_name("GND").is_some());
+    assert!(index.net_class("GND").is_none());
+    assert_eq!(index.via("V").unwrap().diameter(), Length::from_um(600.0));
+    assert!(index.via("W").is_none());
+}
+
+#[test]
+fn every_dangling_reference_is_reported() {
+    let board = board()
+        .place("Missing", "U1 10000 -10000 front 0")
+        .library("(image Q (pin Nowhere 1 0 0))")
+        .network("(net OUT (pins R2-1 R1-3))")
+        .network("(class Signal OUT NC (circuit (use_via W)))");
+    assert_eq!(
+        errors(board),
+        [
+            "pcb > library > image Q > pin 1: reference to undefined padstack 'Nowhere'",
+            "pcb > network > class Signal: reference to undefined net 'NC'",
+            "pcb > network > class Signal: reference to undefined padstack 'W'",
+            "pcb > network > net OUT: reference to undefined component 'R2'",
+            "pcb > network > net OUT: reference to undefined pin 'R1-3'",
+            "pcb > placement > component Missing: reference to undefined image 'Missing'",
+        ]
+    );
+}
+
+#[test]
+fn structure_via_without_a_padstack_is_dangling() {
+    let board = board().structure("(via Gone)");
+    assert_eq!(
+        errors(board),
+        ["pcb > structure > via: reference to undefined padstack 'Gone'"]
+    );
+}
@@ -0,0 +1,195 @@
+//! Edits must keep the design consistent, and a rejected edit must leave it
+//! unchanged.
+
+use pcb_parsing::dsn_error::{DsnError, DsnErrorKind};
+use pcb_parsing::dsn_struct::{ComponentInst, DsnStruct, Pin2, Side};
+use pcb_parsing::geometry::Point;
+use pcb_parsing::units::Length;
+
+mod common;
+use common::Board;
+
+fn board() -> DsnStruct {
+    Board::new("edit")
+        .layers(&["F.Cu"])
+        .via("Via_600", "(shape (circle F.Cu 600))")
+        .place("R", "R1 5000 -5000 front 0")
+        .place("R", "R2 15000 -5000 front 90")
+        .place("Q", "Q1 10000 -15000 back 0")
+        .library("(image R (pin Round 1 -800 0) (pin Round 2 800 0))")
+        .library("(image Q (pin Round 1 -900 900) (pin Round 2 -900 -900) (pin Round 3 900 0))")
+        .library("(padstack Round (shape (circle F.Cu 800)))")
+        .network("(net VCC (pins R1-1 Q1-3))")
+        .network("(net OUT (pins R1-2 R2-1 Q1-1))")
+        .network("(class Power VCC (circuit (use_via Via_600)))")
+        .network("(class Signal OUT (circuit (use_via Via_600)))")
+        .parse()
+}
+
+fn pin(component_name: &str, pin_id: &str) -> Pin2 {
+    Pin2 {
+        component_name: component_name.to_string(),
+        pin_id: pin_id.to_string(),
+    }
+}
+
+/// Runs `edit` on a copy of the board and checks that it fails without
+/// touching the copy.
+fn assert_rejected(edit: impl FnOnce(&mut DsnStruct) -> Result<(), DsnError>) -> DsnErrorKind {
+    let mut dsn = board();
+    let error = edit(&mut dsn).unwrap_err();
+    assert_eq!(dsn, board(), "a rejected edit changed the design");
+    error.kind
+}
+
+fn instance<'a>(dsn: &'a DsnStruct, reference: &str) -> &'a ComponentInst {
+    dsn.placement
+        .components
+        .iter()
+        .flat_map(|component| &component.instances)
+        .find(|instance| instance.reference == reference)
+        .unwrap()
+}
+
+#[test]
+fn move_rotate_and_flip_change_only_the_instance() {
+    let mut dsn = board();
+    let position = Point::new(Length::from_mm(1.0), Length::from_mm(-2.0));
+    dsn.move_component("R2", position).unwrap();
+    dsn.rotate_component("R2", -90.0).unwrap();
+    dsn.flip_component("R2").unwrap();
+
+    let r2 = instance(&dsn, "R2");
+    assert_eq!(r2.position, position);
+    assert_eq!(r2.rotation, 270.0);
+    assert_eq!(r2.side, Side::Back);
+    assert_eq!(instance(&dsn, "R1"), instance(&board(), "R1"));
+    assert_eq!(dsn.network, board().network);
+}
+
+#[test]
+fn placement_edits_reject_unknown_components() {
+    let kind = assert_rejected(|dsn| dsn.move_component("R9", Point::ORIGIN));
+    assert!(matches!(kind, DsnErrorKind::DanglingReference { .. }));
+    let kind = assert_rejected(|dsn| dsn.flip_component("R9"));
+    assert!(matches!(kind, DsnErrorKind::DanglingReference { .. }));
+    let kind = assert_rejected(|dsn| dsn.rotate_component("R1", f64::NAN));
+    assert!(matches!(kind, DsnErrorKind::InvalidValue { .. }));
+}
+
+#[test]
+fn rename_updates_net_pin_references() {
+    let mut dsn = board();
+    dsn.rename_reference("Q1", "Q7").unwrap();
+    assert_eq!(
+        instance(&dsn, "Q7").position,
+        instance(&board(), "Q1").position
+    );
+    assert_eq!(dsn.network.nets[0].pins, [pin("R1", "1"), pin("Q7", "3")]);
+    assert_eq!(
+        dsn.network.nets[1].pins,
+        [pin("R1", "2"), pin("R2", "1"), pin("Q7", "1")]
+    );
+    let references = dsn
+        .network
+        .nets
+        .iter()
+        .flat_map(|net| &net.pins)
+        .filter(|pin| pin.component_name == "Q1")
+        .count();
+    assert_eq!(references, 0);
+}
+
+#[test]
+fn rename_rejects_duplicate_dangling_and_invalid_references() {
+    let kind = assert_rejected(|dsn| dsn.rename_reference("R1", "R2"));
+    assert!(matches!(kind, DsnErrorKind::DuplicateName { .. }));
+    let kind = assert_rejected(|dsn| dsn.rename_reference("R9", "R3"));
+    assert!(matches!(kind, DsnErrorKind::DanglingReference { .. }));
+    let kind = assert_rejected(|dsn| dsn.rename_reference("R1", "R-3"));
+    assert!(matches!(kind, DsnErrorKind::InvalidValue { .. }));
+}
+
+#[test]
+fn swap_image_moves_the_instance_to_the_new_image() {
+    let mut dsn = board();
+    // R2 only uses pin 1, which Q has as well
+    dsn.swap_image("R2", "Q").unwrap();
+    let components: Vec<(&str, Vec<&str>)> = dsn
+        .placement
+        .components
+        .iter()
+        .map(|component| {
+            let references = component
+                .instances
+                .iter()
+                .map(|instance| instance.reference.as_str())
+                .collect();
+            (component.name.as_str(), references)
+        })
+        .collect();
+    assert_eq!(components, [("R", vec!["R1"]), ("Q", vec!["Q1", "R2"])]);
+}
+
+#[test]
+fn swap_image_rejects_missing_images_and_pins() {
+    let kind = assert_rejected(|dsn| dsn.swap_image("R1", "C"));
+    assert!(matches!(kind, DsnErrorKind::DanglingReference { .. }));
+    // Q1-3 is on VCC, and R has no pin 3
+    let kind = assert_rejected(|dsn| dsn.swap_image("Q1", "R"));
+    assert_eq!(
+        kind,
+        DsnErrorKind::DanglingReference {
+            kind: "pin".to_string(),
+            name: "Q1-3".to_string(),
+        }
+    );
+}
+
+#[test]
+fn move_net_to_class_leaves_one_class_per_net() {
+    let mut dsn = board();
+    dsn.move_net_to_class("OUT", "Power").unwrap();
+    let netclasses = &dsn.network.netclasses;
+    assert_eq!(netclasses["Power"].net_names, ["VCC", "OUT"]);
+    assert!(netclasses["Signal"].net_names.is_empty());
+
+    let kind = assert_rejected(|dsn| dsn.move_net_to_class("GND", "Power"));
+    assert!(matches!(kind, DsnErrorKind::DanglingReference { .. }));
+    let kind = assert_rejected(|dsn| dsn.move_net_to_class("OUT", "Ground"));
+    assert!(matches!(kind, DsnErrorKind::DanglingReference { .. }));
+}
+
+#[test]
+fn add_net_connects_free_pins() {
+    let mut dsn = board();
+    dsn.add_net("GND", vec![pin("R2", "2"), pin("Q1", "2")], Some("Power"))
+        .unwrap();
+    let net = dsn.network.nets.last().unwrap();
+    assert_eq!(net.name, "GND");
+    assert_eq!(net.pins, [pin("R2", "2"), pin("Q1", "2")]);
+    assert_eq!(dsn.network.netclasses["Power"].net_names, ["VCC", "GND"]);
+}
+
+#[test]
+fn add_net_rejects_bad_nets() {
+    let kind = assert_rejected(|dsn| dsn.add_net("VCC", vec![pin("R2", "2")], None));
+    assert!(matches!(kind, DsnErrorKind::DuplicateName { .. }));
+    let kind = assert_rejected(|dsn| dsn.add_net("GND", vec![pin("R2", "2")], Some("Ground")));
+    assert!(matches!(kind, DsnErrorKind::DanglingReference { .. }));
+    let kind = assert_rejected(|dsn| dsn.add_net("GND", vec![pin("R9", "1")], None));
+    assert!(matches!(kind, DsnErrorKind::DanglingReference { .. }));
+    let kind = assert_rejected(|dsn| dsn.add_net("GND", vec![pin("R2", "3")], None));
+    assert!(matches!(kind, DsnErrorKind::DanglingReference { .. }));
+    let kind = assert_rejected(|dsn| dsn.add_net("GND", vec![pin("R1", "1")], None));
+    assert_eq!(
+        kind,
+        DsnErrorKind::AlreadyConnected {
+            pin: "R1-1".to_string(),
+            net: "VCC".to_string(),
+        }
+    );
+    let twice = vec![pin("R2", "2"), pin("R2", "2")];
+    let kind = assert_rejected(|dsn| dsn.add_net("GND", twice, None));
+    assert!(matches!(kind, DsnErrorKind::AlreadyConnected { .. }));
+}
@@ -0,0 +1,148 @@
+//! Malformed input must come back as an error, never as a panic.
+//!
+//! Inputs that once broke the parsers live in `tests/corpus`; add a file there
+//! whenever proptest finds a new one.
+
+use std::fs;
+use std::path::Path;
+
+use pcb_parsing::connectivity::route_completeness;
+use pcb_parsing::drc::check_design;
+use pcb_parsing::dsn_error::DsnErrorKind;
+use pcb_parsing::dsn_index::DsnIndex;
+use pcb_parsing::dsn_struct::DsnStruct;
+use pcb_parsing::parse_to_display::dsn_to_display;
+use pcb_parsing::parse_to_s_expr::{MAX_DEPTH, parse_dsn_to_s_expr};
+use pcb_parsing::parse_to_struct::{ParseMode, parse_s_expr_to_struct_with_mode};
+use pcb_parsing::route_stats::route_stats;
+use pcb_parsing::s_expr::SExpr;
+use pcb_parsing::shorts::find_shorts;
+use proptest::prelude::*;
+
+const SAMPLE: &str = include_str!("../specctra_test.dsn");
+
+/// Runs the text through both parse stages in both modes. Only panics matter here.
+fn parse_all(input: &str) {
+    if let Ok(s_expr) = parse_dsn_to_s_expr(input) {
+        parse_struct(&s_expr);
+    }
+}
+
+/// Whatever parses must also survive the analyses built on the model.
+fn analyse(dsn: &DsnStruct) {
+    let _ = DsnIndex::new(dsn);
+    let _ = route_completeness(dsn);
+    let _ = find_shorts(dsn);
+    let _ = check_design(dsn);
+    let _ = route_stats(dsn);
+    let _ = dsn_to_display(dsn.clone());
+}
+
+fn parse_struct(s_expr: &SExpr) {
+    let _ = parse_s_expr_to_struct_with_mode(s_expr, ParseMode::Strict);
+    if let Ok((dsn, _)) = parse_s_expr_to_struct_with_mode(s_expr, ParseMode::Lenient) {
+        analyse(&dsn);
+    }
+}
+
+#[test]
+fn corpus_does_not_panic() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let mut count = 0;
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let input = fs::read_to_string(&path).unwrap();
+        parse_all(&input);
+        count += 1;
+    }
+    assert!(count > 0, "regression corpus is empty");
+}
+
+#[test]
+fn sample_parses() {
+    let s_expr = parse_dsn_to_s_expr(SAMPLE).unwrap();
+    parse_s_expr_to_struct_with_mode(&s_expr, ParseMode::Lenient).unwrap();
+}
+
+#[test]
+fn absurd_numbers_are_rejected() {
+    let place = "(place U1 63500.000000 -83820.000000 back 0.000000 (PN LM555xM))";
+    assert!(SAMPLE.contains(place));
+    for value in ["inf", "-inf", "nan", "1e400", "1e30"] {
+        let places = [
+            format!("(place U1 {} -83820 back 0 (PN LM555xM))", value),
+            format!("(place U1 63500 -83820 back {} (PN LM555xM))", value),
+        ];
+        for absurd in places {
+            // 1e30 is a valid rotation, only far too long as a coordinate
+            if value == "1e30" && absurd.contains("back 1e30") {
+                continue;
+            }
+            let s_expr = parse_dsn_to_s_expr(&SAMPLE.replace(place, &absurd)).unwrap();
+            let error = parse_s_expr_to_struct_with_mode(&s_expr, ParseMode::Lenient).unwrap_err();
+            assert!(
+                matches!(error.kind, DsnErrorKind::InvalidNumber { .. }),
+                "{} was not rejected: {}", absurd, error
+            );
+        }
+    }
+}
+
+#[test]
+fn nesting_beyond_limit_is_an_error() {
+    let input = format!("{}{}", "(".repeat(MAX_DEPTH + 1), ")".repeat(MAX_DEPTH + 1));
+    assert!(parse_dsn_to_s_expr(&input).is_err());
+    let input = format!("{}{}", "(".repeat(MAX_DEPTH), ")".repeat(MAX_DEPTH));
+    assert!(parse_dsn_to_s_expr(&input).is_ok());
+}
+
+/// Atoms that steer the generated trees into the parser's known scopes.
+fn atom() -> impl Strategy<Value = SExpr> {
+    prop_oneof![
+        prop::sample::select(vec![
+            "pcb", "resolution", "unit", "um", "mm", "mil", "structure", "layer", "boundary",
+            "path", "placement", "component", "place", "front", "back", "library", "image", "pin",
+            "outline", "padstack", "shape", "circle", "rect", "polygon", "attach", "network",
+            "net", "pins", "class", "circuit", "use_via", "rule", "width", "clearance", "U1-1",
+        ])
+        .prop_map(|keyword| SExpr::Atom(keyword.to_string())),
+        any::<i32>().prop_map(|n| SExpr::Atom(n.to_string())),
+        "[ -~]{0,6}".prop_map(SExpr::Atom),
+    ]
+}
+
+fn s_expr() -> impl Strategy<Value = SExpr> {
+    atom().prop_recursive(6, 128, 8, |inner| {
+        prop::collection::vec(inner, 0..8).prop_map(SExpr::List)
+    })
+}
+
+proptest! {
+    #[test]
+    fn arbitrary_text_does_not_panic(input in "[()\" a-z0-9.\\-\n]{0,200}") {
+        parse_all(&input);
+    }
+
+    #[test]
+    fn truncated_sample_does_not_panic(end in 0..SAMPLE.len()) {
+        parse_all(&SAMPLE[..end]);
+    }
+
+    #[test]
+    fn mutated_sample_does_not_panic(
+        start in 0..SAMPLE.len(),
+        removed in 0usize..64,
+        inserted in "[()\" a-z0-9.\\-]{0,8}",
+    ) {
+        let end = (start + removed).min(SAMPLE.len());
+        let input = format!("{}{}{}", &SAMPLE[..start], inserted, &SAMPLE[end..]);
+        parse_all(&input);
+    }
+
+    #[test]
+    fn generated_trees_do_not_panic(items in prop::collection::vec(s_expr(), 0..8)) {
+        let mut list = vec![SExpr::Atom("pcb".to_string())];
+        list.extend(items);
+        parse_struct(&SExpr::List(list));
+    }
+}
@@ -0,0 +1,35 @@
+//! Quarter turns move points exactly; only other angles are rounded.
+
+use pcb_parsing::geometry::{Point, Transform};
+use pcb_parsing::units::Length;
+
+fn nm(x: i64, y: i64) -> Point {
+    Point::new(Length::from_nm(x), Length::from_nm(y))
+}
+
+#[test]
+fn quarter_turns_are_exact() {
+    let point = nm(1_000_001, -3);
+    assert_eq!(point.rotated(0.0), point);
+    assert_eq!(point.rotated(90.0), nm(3, 1_000_001));
+    assert_eq!(point.rotated(180.0), nm(-1_000_001, 3));
+    assert_eq!(point.rotated(270.0), nm(-3, -1_000_001));
+    assert_eq!(point.rotated(-90.0), nm(-3, -1_000_001));
+    assert_eq!(point.rotated(450.0), nm(3, 1_000_001));
+}
+
+#[test]
+fn other_angles_round_to_the_nearest_nanometre() {
+    assert_eq!(nm(1_000_000, 0).rotated(45.0), nm(707_107, 707_107));
+}
+
+#[test]
+fn back_side_mirrors_before_turning() {
+    let transform = Transform {
+        offset: nm(100, 200),
+        rotation: 90.0,
+        mirrored: true,
+    };
+    assert_eq!(transform.apply(nm(10, 0)), nm(100, 190));
+    assert_eq!(transform.apply_rotation(30.0), 60.0);
+}
@@ -0,0 +1,82 @@
+//! The `(parser ...)` header says which tool wrote the file and how it
+//! quotes strings, and is written back unchanged.
+
+use pcb_parsing::dsn_error::DsnErrorKind;
+use pcb_parsing::parse_to_s_expr::parse_dsn_to_s_expr;
+use pcb_parsing::parse_to_struct::parse_s_expr_to_struct;
+use pcb_parsing::write_dsn::write_dsn;
+
+mod common;
+use common::{Board, parse};
+
+/// The header KiCad writes.
+const KICAD_HEADER: &str = r#"(parser
+    (string_quote ")
+    (space_in_quoted_tokens on)
+    (host_cad "KiCad's Pcbnew")
+    (host_version "8.0.1")
+  )"#;
+
+/// A board with `header` in front of its resolution.
+fn with_header(header: &str) -> String {
+    Board::new("header")
+        .text()
+        .replace("(resolution", &format!("{}\n  (resolution", header))
+}
+
+#[test]
+fn kicad_header_is_read() {
+    let dsn = parse(&with_header(KICAD_HEADER));
+    let parser = dsn.parser.unwrap();
+    assert_eq!(parser.string_quote, Some('"'));
+    assert!(parser.space_in_quoted_tokens);
+    assert_eq!(parser.host_cad.as_deref(), Some("KiCad's Pcbnew"));
+    assert_eq!(parser.host_version.as_deref(), Some("8.0.1"));
+    assert!(parser.unknown.is_empty());
+}
+
+#[test]
+fn header_is_optional_and_defaults_to_no_quoting() {
+    assert_eq!(Board::new("header").parse().parser, None);
+
+    let dsn = parse(&with_header("(parser (host_cad Other))"));
+    let parser = dsn.parser.unwrap();
+    assert_eq!(parser.string_quote, None);
+    assert!(!parser.space_in_quoted_tokens);
+    assert_eq!(parser.host_version, None);
+}
+
+#[test]
+fn header_round_trips() {
+    let dsn = parse(&with_header(KICAD_HEADER));
+    let written = write_dsn(&dsn);
+    assert!(
+        written.contains("(host_cad \"KiCad's Pcbnew\")"),
+        "{}",
+        written
+    );
+    assert_eq!(parse(&written), dsn);
+}
+
+#[test]
+fn malformed_header_values_are_rejected() {
+    for (header, field, value) in [
+        ("(parser (string_quote ab))", "string quote", "ab"),
+        (
+            "(parser (space_in_quoted_tokens maybe))",
+            "space_in_quoted_tokens",
+            "maybe",
+        ),
+    ] {
+        let input = with_header(header);
+        let error = parse_s_expr_to_struct(&parse_dsn_to_s_expr(&input).unwrap()).unwrap_err();
+        assert_eq!(
+            error.kind,
+            DsnErrorKind::InvalidValue {
+                field: field.to_string(),
+                value: value.to_string(),
+            }
+        );
+        assert_eq!(error.context.to_string(), "pcb > parser");
+    }
+}
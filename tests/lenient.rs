@@ -0,0 +1,158 @@
+//! Lenient mode must read files from newer exporters, keeping what it does
+//! not understand instead of failing on it.
+
+use pcb_parsing::dsn_error::DsnErrorKind;
+use pcb_parsing::parse_to_s_expr::parse_dsn_to_s_expr;
+use pcb_parsing::parse_to_session::parse_s_expr_to_session_with_mode;
+use pcb_parsing::parse_to_struct::{ParseMode, parse_s_expr_to_struct_with_mode};
+use pcb_parsing::write_dsn::write_dsn;
+use pcb_parsing::write_session::write_session;
+
+/// A board with an oval pad, which KiCad exports as a `path` shape, and a
+/// stray atom in the structure.
+const OVAL_PAD: &str = r#"(pcb oval
+  (resolution um 10)
+  (unit um)
+  (structure
+    (layer F.Cu (type signal))
+    (boundary (path pcb 0 0 0 10000 0 10000 -10000 0 -10000 0 0))
+    stray
+  )
+  (placement
+    (component J
+      (place J1 5000 -5000 front 0)
+    )
+  )
+  (library
+    (image J
+      (pin Oval 1 -1000 0)
+      (pin Round 2 1000 0)
+    )
+    (padstack Oval
+      (shape (path F.Cu 1000 -250 0 250 0))
+      (attach off)
+    )
+    (padstack Round
+      (shape (circle F.Cu 1000))
+    )
+  )
+  (network
+    (net A (pins J1-1 J1-2))
+  )
+)"#;
+
+#[test]
+fn oval_padstack_is_kept_in_lenient_mode() {
+    let s_expr = parse_dsn_to_s_expr(OVAL_PAD).unwrap();
+    let (dsn, diagnostics) = parse_s_expr_to_struct_with_mode(&s_expr, ParseMode::Lenient).unwrap();
+
+    let locations: Vec<String> = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.location.to_string())
+        .collect();
+    assert_eq!(
+        locations,
+        ["pcb > structure", "pcb > library > padstack Oval > shape"]
+    );
+    assert_eq!(diagnostics[1].message, "unknown keyword 'path'");
+
+    let oval = &dsn.library.pad_stacks["Oval"];
+    assert!(oval.shapes.is_empty());
+    assert_eq!(oval.attach, Some(false));
+    assert_eq!(oval.unknown.len(), 1);
+    assert_eq!(dsn.structure.unknown.len(), 1);
+
+    // what was skipped is written back where it was
+    let written = write_dsn(&dsn);
+    assert!(written.contains("(shape (path F.Cu 1000 -250 0 250 0))"));
+    assert!(written.contains("stray"));
+}
+
+#[test]
+fn oval_padstack_fails_in_strict_mode() {
+    let s_expr = parse_dsn_to_s_expr(&OVAL_PAD.replace("    stray\n", "")).unwrap();
+    let error = parse_s_expr_to_struct_with_mode(&s_expr, ParseMode::Strict).unwrap_err();
+    assert!(matches!(error.kind, DsnErrorKind::UnknownKeyword { .. }));
+    assert_eq!(
+        error.context.to_string(),
+        "pcb > library > padstack Oval > shape"
+    );
+}
+
+#[test]
+fn unknown_scopes_are_kept_silently_in_strict_mode() {
+    let input = OVAL_PAD
+        .replace("    stray\n", "    (snap_angle fortyfive_degree)\n")
+        .replace(
+            "(shape (path F.Cu 1000 -250 0 250 0))",
+            "(shape (circle F.Cu 500))",
+        );
+    let s_expr = parse_dsn_to_s_expr(&input).unwrap();
+    let (dsn, diagnostics) = parse_s_expr_to_struct_with_mode(&s_expr, ParseMode::Strict).unwrap();
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    assert_eq!(dsn.structure.unknown.len(), 1);
+    assert_eq!(dsn.structure.unknown[0].position, 3);
+
+    // written back after the boundary, where it was
+    let written = write_dsn(&dsn);
+    let boundary = written.find("(boundary").unwrap();
+    let snap_angle = written.find("(snap_angle fortyfive_degree)").unwrap();
+    assert!(boundary < snap_angle, "{}", written);
+    let reparsed = parse_s_expr_to_struct_with_mode(
+        &parse_dsn_to_s_expr(&written).unwrap(),
+        ParseMode::Strict,
+    )
+    .unwrap()
+    .0;
+    assert_eq!(reparsed, dsn);
+}
+
+/// A session with a stray atom among the via padstacks and a scope the
+/// model lacks among the nets.
+const ODD_ROUTES: &str = r#"(session odd
+  (base_design odd)
+  (routes
+    (resolution um 10)
+    (library_out
+      (padstack V (shape (circle F.Cu 6000 0 0)))
+      stray
+    )
+    (network_out
+      (net A (via V 1000 -1000))
+      (group G)
+    )
+  )
+)"#;
+
+#[test]
+fn odd_routes_items_are_kept_in_lenient_mode() {
+    let s_expr = parse_dsn_to_s_expr(ODD_ROUTES).unwrap();
+    let (session, diagnostics) =
+        parse_s_expr_to_session_with_mode(&s_expr, ParseMode::Lenient).unwrap();
+
+    let locations: Vec<String> = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.location.to_string())
+        .collect();
+    assert_eq!(
+        locations,
+        [
+            "session > routes > library_out",
+            "session > routes > network_out > group G"
+        ]
+    );
+    let routes = session.routes.as_ref().unwrap();
+    assert_eq!(routes.library_out.len(), 1);
+    assert_eq!(routes.network_out.len(), 1);
+
+    let written = write_session(&session);
+    assert!(written.contains("stray"));
+    assert!(written.contains("(group G)"));
+}
+
+#[test]
+fn odd_routes_items_fail_in_strict_mode() {
+    let s_expr = parse_dsn_to_s_expr(ODD_ROUTES).unwrap();
+    let error = parse_s_expr_to_session_with_mode(&s_expr, ParseMode::Strict).unwrap_err();
+    assert_eq!(error.context.to_string(), "session > routes > library_out");
+}
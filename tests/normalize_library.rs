@@ -0,0 +1,207 @@
+//! Identical library entries are merged and every reference follows the
+//! entry kept, in the design and in the session.
+
+use pcb_parsing::dsn_struct::DsnStruct;
+use pcb_parsing::normalize_library::{normalize_library, normalize_session_library};
+use pcb_parsing::parse_to_s_expr::parse_dsn_to_s_expr;
+use pcb_parsing::parse_to_session::parse_s_expr_to_session;
+use pcb_parsing::session_struct::SessionStruct;
+
+mod common;
+use common::Board;
+
+/// Two resistor images that differ only in the names of their identical
+/// pads, and three vias of one size, two of them drilled 0.3 mm and one
+/// drilled 0.4 mm.
+fn board() -> DsnStruct {
+    let via = "(shape (circle F.Cu 600)) (shape (circle B.Cu 600))";
+    Board::new("normalize")
+        .via("Via[0-1]_600:300_um", via)
+        .via("Via[0-1]_0.6:0.3_mm", via)
+        .via("Via[0-1]_600:400_um", via)
+        .place("R_a", "R1 5000 -5000 front 0")
+        .place("R_b", "R2 15000 -5000 front 0")
+        .library("(image R_a (pin Round_A 1 -800 0) (pin Round_A 2 800 0))")
+        .library("(image R_b (pin Round_B 1 -800 0) (pin Round_B 2 800 0))")
+        .library("(padstack Round_A (shape (circle F.Cu 800)))")
+        .library("(padstack Round_B (shape (circle F.Cu 800)))")
+        .network("(net A (pins R1-1 R2-1))")
+        .network(r#"(class default A (circuit (use_via "Via[0-1]_600:300_um")))"#)
+        .wiring(r#"(via "Via[0-1]_600:300_um" 10000 -5000 (net A))"#)
+        .wiring(r#"(via "Via[0-1]_600:400_um" 10000 -8000 (net A))"#)
+        .parse()
+}
+
+/// A session whose `library_out` lists one via twice, as KiCad does, next
+/// to an identical via under another name and one with a larger drill.
+const SESSION: &str = r#"(session normalize
+  (base_design normalize)
+  (routes
+    (resolution um 10)
+    (library_out
+      (padstack "Via[0-1]_600:300_um" (shape (circle F.Cu 6000 0 0)) (shape (circle B.Cu 6000 0 0)) (attach off))
+      (padstack "Via[0-1]_600:400_um" (shape (circle F.Cu 6000 0 0)) (shape (circle B.Cu 6000 0 0)) (attach off))
+      (padstack "Via[0-1]_600:300_um" (shape (circle F.Cu 6000 0 0)) (shape (circle B.Cu 6000 0 0)) (attach off))
+      (padstack "Via[0-1]_0.6:0.3_mm" (shape (circle F.Cu 6000 0 0)) (shape (circle B.Cu 6000 0 0)) (attach off))
+    )
+    (network_out
+      (net A
+        (via "Via[0-1]_600:300_um" 100000 -50000)
+        (via "Via[0-1]_600:400_um" 100000 -80000)
+        (via "Via[0-1]_0.6:0.3_mm" 100000 -90000)
+      )
+    )
+  )
+)"#;
+
+fn session() -> SessionStruct {
+    parse_s_expr_to_session(&parse_dsn_to_s_expr(SESSION).unwrap()).unwrap()
+}
+
+fn sorted_keys<T>(map: &std::collections::HashMap<String, T>) -> Vec<&str> {
+    let mut keys: Vec<&str> = map.keys().map(String::as_str).collect();
+    keys.sort();
+    keys
+}
+
+#[test]
+fn identical_padstacks_and_images_are_merged() {
+    let mut dsn = board();
+    let merges = normalize_library(&mut dsn);
+    let report: Vec<String> = merges.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        report,
+        [
+            "padstack Round_A replaces Round_B",
+            "padstack Via[0-1]_0.6:0.3_mm replaces Via[0-1]_600:300_um",
+            "image R_a replaces R_b",
+        ]
+    );
+    assert_eq!(
+        sorted_keys(&dsn.library.pad_stacks),
+        ["Round_A", "Via[0-1]_0.6:0.3_mm", "Via[0-1]_600:400_um"]
+    );
+    assert_eq!(sorted_keys(&dsn.library.images), ["R_a"]);
+}
+
+#[test]
+fn references_follow_the_merged_entries() {
+    let mut dsn = board();
+    normalize_library(&mut dsn);
+
+    assert_eq!(
+        dsn.structure.vias,
+        ["Via[0-1]_0.6:0.3_mm", "Via[0-1]_600:400_um"]
+    );
+    assert_eq!(
+        dsn.network.netclasses["default"].via_name,
+        "Via[0-1]_0.6:0.3_mm"
+    );
+    let wiring_vias: Vec<&str> = dsn
+        .wiring
+        .as_ref()
+        .unwrap()
+        .vias
+        .iter()
+        .map(|via| via.padstack_name.as_str())
+        .collect();
+    assert_eq!(wiring_vias, ["Via[0-1]_0.6:0.3_mm", "Via[0-1]_600:400_um"]);
+
+    // R2 joins R1 under the image kept
+    assert_eq!(dsn.placement.components.len(), 1);
+    let component = &dsn.placement.components[0];
+    assert_eq!(component.name, "R_a");
+    let references: Vec<&str> = component
+        .instances
+        .iter()
+        .map(|instance| instance.reference.as_str())
+        .collect();
+    assert_eq!(references, ["R1", "R2"]);
+    let pads: Vec<&str> = dsn.library.images["R_a"]
+        .pins
+        .values()
+        .map(|pin| pin.pad_stack_name.as_str())
+        .collect();
+    assert_eq!(pads, ["Round_A", "Round_A"]);
+}
+
+#[test]
+fn same_shape_vias_with_different_drills_stay_apart() {
+    let mut dsn = board();
+    dsn.library.pad_stacks.remove("Via[0-1]_0.6:0.3_mm");
+    dsn.structure
+        .vias
+        .retain(|via| via != "Via[0-1]_0.6:0.3_mm");
+    let merges = normalize_library(&mut dsn);
+    assert!(
+        merges.iter().all(|merge| !merge.kept.starts_with("Via")),
+        "{:?}",
+        merges
+    );
+    assert!(dsn.library.pad_stacks.contains_key("Via[0-1]_600:300_um"));
+    assert!(dsn.library.pad_stacks.contains_key("Via[0-1]_600:400_um"));
+}
+
+#[test]
+fn session_library_out_is_merged_and_vias_renamed() {
+    let mut session = session();
+    let merges = normalize_session_library(&mut session);
+    let report: Vec<String> = merges.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        report,
+        ["padstack Via[0-1]_0.6:0.3_mm replaces Via[0-1]_600:300_um and drops 1 duplicate"]
+    );
+
+    let routes = session.routes.as_ref().unwrap();
+    let library_out: Vec<&str> = routes
+        .library_out
+        .iter()
+        .map(|padstack| padstack.name.as_str())
+        .collect();
+    assert_eq!(library_out, ["Via[0-1]_0.6:0.3_mm", "Via[0-1]_600:400_um"]);
+    let vias: Vec<&str> = routes.network_out[0]
+        .vias
+        .iter()
+        .map(|via| via.padstack_name.as_str())
+        .collect();
+    assert_eq!(
+        vias,
+        [
+            "Via[0-1]_0.6:0.3_mm",
+            "Via[0-1]_600:400_um",
+            "Via[0-1]_0.6:0.3_mm"
+        ]
+    );
+}
+
+#[test]
+fn repeated_session_padstack_replaces_nothing() {
+    let mut session = session();
+    let routes = session.routes.as_mut().unwrap();
+    routes.library_out.truncate(3);
+    routes.network_out[0].vias.truncate(2);
+    let merges = normalize_session_library(&mut session);
+    assert_eq!(merges[0].replaced, Vec::<String>::new());
+    assert_eq!(merges[0].duplicates, 1);
+    assert_eq!(
+        merges[0].to_string(),
+        "padstack Via[0-1]_600:300_um drops 1 duplicate"
+    );
+    let routes = session.routes.as_ref().unwrap();
+    assert_eq!(routes.library_out.len(), 2);
+    assert_eq!(
+        routes.network_out[0].vias[0].padstack_name,
+        "Via[0-1]_600:300_um"
+    );
+}
+
+#[test]
+fn session_without_duplicates_is_unchanged() {
+    let mut session = session();
+    let routes = session.routes.as_mut().unwrap();
+    routes.library_out.truncate(2);
+    routes.network_out[0].vias.truncate(2);
+    let before = session.clone();
+    assert!(normalize_session_library(&mut session).is_empty());
+    assert_eq!(session, before);
+}
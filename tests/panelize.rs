@@ -0,0 +1,249 @@
+//! Every copy of a panel must keep its own outline, cutouts and keepouts at
+//! its own place.
+
+use pcb_parsing::dsn_error::DsnErrorKind;
+use pcb_parsing::dsn_struct::{Boundary, DsnStruct, Shape};
+use pcb_parsing::geometry::Point;
+use pcb_parsing::panelize::{PanelLayout, Tab, panelize};
+use pcb_parsing::units::Length;
+
+mod common;
+use common::Board;
+
+/// A house-shaped board with a square hole, a keepout, and one routed net.
+fn house() -> Board {
+    Board::new("panel")
+        .layers(&["F.Cu"])
+        .outline("0 0 10000 0 10000 -6000 5000 -10000 0 -6000 0 0")
+        .structure("(boundary (path pcb 0 4000 -4000 6000 -4000 6000 -6000 4000 -6000 4000 -4000))")
+        .via("Via_600", "(shape (circle F.Cu 600))")
+        .structure(r#"(keepout "" (rect F.Cu 1000 -2000 2000 -1000))"#)
+        .place("R", "R1 3000 -3000 front 0")
+        .library("(image R (pin Round 1 -800 0) (pin Round 2 800 0))")
+        .library("(padstack Round (shape (circle F.Cu 800)))")
+        .network("(net A (pins R1-1 R1-2))")
+        .network("(class default A (circuit (use_via Via_600)))")
+        .wiring("(wire (path F.Cu 250 2200 -3000 3800 -3000) (net A))")
+        .wiring("(via Via_600 3000 -8000 (net A))")
+}
+
+fn board() -> DsnStruct {
+    house().parse()
+}
+
+fn point(x: f64, y: f64) -> Point {
+    Point::new(Length::from_um(x), Length::from_um(y))
+}
+
+fn boundary(layer: &str, points: &[(f64, f64)]) -> Boundary {
+    Boundary {
+        layer: layer.to_string(),
+        width: Length::ZERO,
+        points: points.iter().map(|&(x, y)| point(x, y)).collect(),
+    }
+}
+
+/// Two copies side by side, 2 mm apart, with a 1 mm tab between them.
+fn two_copies() -> (DsnStruct, PanelLayout) {
+    let dsn = board();
+    let layout = PanelLayout::grid(&dsn, 2, 1, Length::from_mm(2.0), Some(Length::from_mm(1.0)));
+    (dsn, layout)
+}
+
+#[test]
+fn grid_places_copies_one_outline_and_spacing_apart() {
+    let (_, layout) = two_copies();
+    assert_eq!(layout.offsets, [point(0.0, 0.0), point(12000.0, 0.0)]);
+    assert_eq!(
+        layout.tabs,
+        [Tab {
+            center: point(11000.0, -5000.0),
+            width: Length::from_mm(2.0),
+            height: Length::from_mm(1.0),
+        }]
+    );
+}
+
+#[test]
+fn each_copy_keeps_its_outline_and_cutouts() {
+    let (dsn, layout) = two_copies();
+    let panel = panelize(&dsn, &layout).unwrap().0;
+    let outline = |dx: f64| {
+        boundary(
+            "signal",
+            &[
+                (dx, 0.0),
+                (dx + 10000.0, 0.0),
+                (dx + 10000.0, -6000.0),
+                (dx + 5000.0, -10000.0),
+                (dx, -6000.0),
+                (dx, 0.0),
+            ],
+        )
+    };
+    let hole = |dx: f64| {
+        boundary(
+            "pcb",
+            &[
+                (dx + 4000.0, -4000.0),
+                (dx + 6000.0, -4000.0),
+                (dx + 6000.0, -6000.0),
+                (dx + 4000.0, -6000.0),
+                (dx + 4000.0, -4000.0),
+            ],
+        )
+    };
+    let panel_outline = boundary(
+        "pcb",
+        &[
+            (0.0, -10000.0),
+            (22000.0, -10000.0),
+            (22000.0, 0.0),
+            (0.0, 0.0),
+            (0.0, -10000.0),
+        ],
+    );
+    assert_eq!(
+        panel.structure.boundaries,
+        [
+            panel_outline,
+            outline(0.0),
+            hole(0.0),
+            outline(12000.0),
+            hole(12000.0)
+        ]
+    );
+}
+
+#[test]
+fn outline_is_the_first_pcb_boundary() {
+    let mut dsn = board();
+    let routing_area = boundary(
+        "signal",
+        &[
+            (500.0, -500.0),
+            (9500.0, -500.0),
+            (9500.0, -5500.0),
+            (500.0, -500.0),
+        ],
+    );
+    dsn.structure.boundaries.insert(0, routing_area.clone());
+    let layout = PanelLayout::grid(&dsn, 2, 1, Length::from_mm(2.0), None);
+    assert_eq!(layout.offsets[1], point(12000.0, 0.0));
+
+    let panel = panelize(&dsn, &layout).unwrap().0;
+    let layers: Vec<&str> = panel
+        .structure
+        .boundaries
+        .iter()
+        .map(|boundary| boundary.layer.as_str())
+        .collect();
+    assert_eq!(
+        layers,
+        ["pcb", "signal", "signal", "pcb", "signal", "signal", "pcb"]
+    );
+    assert_eq!(panel.structure.boundaries[1], routing_area);
+    assert_eq!(
+        panel.structure.boundaries[2].points,
+        dsn.structure.boundaries[1].points
+    );
+}
+
+#[test]
+fn keepouts_move_with_their_copy_and_tabs_are_added() {
+    let (dsn, layout) = two_copies();
+    let panel = panelize(&dsn, &layout).unwrap().0;
+    let keepouts: Vec<(&str, &Shape)> = panel
+        .structure
+        .keepouts
+        .iter()
+        .map(|keepout| (keepout.shape.layer.as_str(), &keepout.shape.shape))
+        .collect();
+    let rect = |x_min: f64, y_min: f64, x_max: f64, y_max: f64| Shape::Rect {
+        x_min: Length::from_um(x_min),
+        y_min: Length::from_um(y_min),
+        x_max: Length::from_um(x_max),
+        y_max: Length::from_um(y_max),
+    };
+    assert_eq!(
+        keepouts,
+        [
+            ("F.Cu", &rect(1000.0, -2000.0, 2000.0, -1000.0)),
+            ("F.Cu", &rect(13000.0, -2000.0, 14000.0, -1000.0)),
+            ("signal", &rect(10000.0, -5500.0, 12000.0, -4500.0)),
+        ]
+    );
+    assert_eq!(panel.structure.keepouts[2].name, None);
+}
+
+#[test]
+fn references_nets_and_wiring_are_copied() {
+    let (dsn, layout) = two_copies();
+    let panel = panelize(&dsn, &layout).unwrap().0;
+
+    let instances: Vec<(&str, Point)> = panel.placement.components[0]
+        .instances
+        .iter()
+        .map(|instance| (instance.reference.as_str(), instance.position))
+        .collect();
+    assert_eq!(
+        instances,
+        [
+            ("R1_1", point(3000.0, -3000.0)),
+            ("R1_2", point(15000.0, -3000.0))
+        ]
+    );
+    let nets: Vec<&str> = panel
+        .network
+        .nets
+        .iter()
+        .map(|net| net.name.as_str())
+        .collect();
+    assert_eq!(nets, ["A_1", "A_2"]);
+    assert_eq!(panel.network.nets[1].pins[0].component_name, "R1_2");
+    assert_eq!(
+        panel.network.netclasses["default"].net_names,
+        ["A_1", "A_2"]
+    );
+
+    let wiring = panel.wiring.as_ref().unwrap();
+    assert_eq!(
+        wiring.wires[1].points,
+        [point(14200.0, -3000.0), point(15800.0, -3000.0)]
+    );
+    assert_eq!(wiring.wires[1].net.as_deref(), Some("A_2"));
+    assert_eq!(wiring.vias[1].position, point(15000.0, -8000.0));
+    assert_eq!(wiring.vias[1].net.as_deref(), Some("A_2"));
+}
+
+#[test]
+fn copper_the_model_lacks_is_reported_once_copied() {
+    let dsn = house()
+        .structure(r#"(keepout "" (circle F.Cu 500 5000 -2000))"#)
+        .wiring("(wire (polygon F.Cu 0 4000 -7000 6000 -7000 5000 -8000) (net A))")
+        .parse();
+    let (_, layout) = two_copies();
+    let (panel, warnings) = panelize(&dsn, &layout).unwrap();
+    let warnings: Vec<String> = warnings.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        warnings,
+        [
+            "warning: pcb > structure > keepout \"\": not modelled, so kept once at its original place",
+            "warning: pcb > wiring > wire: not modelled, so kept once at its original place",
+        ]
+    );
+    assert_eq!(panel.structure.unknown, dsn.structure.unknown);
+
+    let one_copy = PanelLayout::grid(&dsn, 1, 1, Length::ZERO, None);
+    assert!(panelize(&dsn, &one_copy).unwrap().1.is_empty());
+}
+
+#[test]
+fn empty_layout_is_rejected() {
+    let layout = PanelLayout {
+        offsets: Vec::new(),
+        tabs: Vec::new(),
+    };
+    let error = panelize(&board(), &layout).unwrap_err();
+    assert!(matches!(error.kind, DsnErrorKind::MissingField { .. }));
+}
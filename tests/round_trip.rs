@@ -0,0 +1,356 @@
+//! Parsing what the writer produced must give back the same design.
+//!
+//! Hand-written edge cases live in `tests/round_trip`; every file there goes
+//! through parse → write → parse.
+
+use std::fs;
+use std::path::Path;
+
+use pcb_parsing::apply_session::apply_session;
+use pcb_parsing::dsn_error::DsnErrorKind;
+use pcb_parsing::dsn_struct::{DsnStruct, Shape, Side};
+use pcb_parsing::geometry::Point;
+use pcb_parsing::parse_to_s_expr::parse_dsn_to_s_expr;
+use pcb_parsing::parse_to_session::{parse_s_expr_to_session, parse_s_expr_to_session_with_mode};
+use pcb_parsing::parse_to_struct::{ParseMode, parse_s_expr_to_struct_with_mode};
+use pcb_parsing::s_expr::SExpr;
+use pcb_parsing::session_struct::{PinSwap, SessionNet};
+use pcb_parsing::units::Length;
+use pcb_parsing::write_dsn::write_dsn;
+use pcb_parsing::write_s_expr::write_s_expr;
+use pcb_parsing::write_session::{routed_session, write_session};
+
+const SAMPLE: &str = include_str!("../specctra_test.dsn");
+const SESSION: &str = include_str!("../MyBoard.ses");
+
+fn parse(input: &str) -> DsnStruct {
+    let s_expr = parse_dsn_to_s_expr(input).unwrap();
+    let (dsn, _) = parse_s_expr_to_struct_with_mode(&s_expr, ParseMode::Lenient).unwrap();
+    dsn
+}
+
+/// The atoms of `expr` in sorted order, with every number in one spelling so
+/// that `63500.000000` and `63500` compare equal.
+fn atoms(expr: &SExpr) -> Vec<String> {
+    fn collect(expr: &SExpr, atoms: &mut Vec<String>) {
+        match expr {
+            SExpr::Atom(atom) => atoms.push(match atom.parse::<f64>() {
+                Ok(number) => number.to_string(),
+                Err(_) => atom.clone(),
+            }),
+            SExpr::List(items) => items.iter().for_each(|item| collect(item, atoms)),
+        }
+    }
+    let mut atoms = Vec::new();
+    collect(expr, &mut atoms);
+    atoms.sort();
+    atoms
+}
+
+/// Checks that the written text holds everything the input held, that it
+/// parses back to an equal design and that writing that design again
+/// changes nothing.
+fn assert_round_trip(input: &str) -> DsnStruct {
+    let dsn = parse(input);
+    let written = write_dsn(&dsn);
+    assert_eq!(
+        atoms(&parse_dsn_to_s_expr(input).unwrap()),
+        atoms(&parse_dsn_to_s_expr(&written).unwrap()),
+        "writer lost or added atoms:\n{}",
+        written
+    );
+    let reparsed = parse(&written);
+    assert_eq!(dsn, reparsed, "design changed on round trip:\n{}", written);
+    assert_eq!(write_dsn(&reparsed), written, "writer output is not stable");
+    reparsed
+}
+
+fn read_case(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/round_trip")
+        .join(name);
+    fs::read_to_string(path).unwrap()
+}
+
+#[test]
+fn sample_round_trips() {
+    let dsn = assert_round_trip(SAMPLE);
+    assert_eq!(dsn.design_name(), "specctra_test");
+}
+
+#[test]
+fn every_case_round_trips() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/round_trip");
+    let mut count = 0;
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let input = fs::read_to_string(&path).unwrap();
+        assert_round_trip(&input);
+        count += 1;
+    }
+    assert!(count > 0, "round trip corpus is empty");
+}
+
+#[test]
+fn session_round_trips_as_s_expr() {
+    let s_expr = parse_dsn_to_s_expr(SESSION).unwrap();
+    let written = write_s_expr(&s_expr, '"');
+    assert_eq!(parse_dsn_to_s_expr(&written).unwrap(), s_expr);
+}
+
+#[test]
+fn session_round_trips() {
+    let session = parse_s_expr_to_session(&parse_dsn_to_s_expr(SESSION).unwrap()).unwrap();
+    let written = write_session(&session);
+    let reparsed = parse_s_expr_to_session(&parse_dsn_to_s_expr(&written).unwrap()).unwrap();
+    assert_eq!(
+        session, reparsed,
+        "session changed on round trip:\n{}",
+        written
+    );
+    assert_eq!(
+        write_session(&reparsed),
+        written,
+        "writer output is not stable"
+    );
+}
+
+#[test]
+fn routed_session_lists_via_padstacks() {
+    let dsn = parse(&read_case("quoted_names.dsn"));
+    let wiring = dsn.wiring.clone().unwrap();
+    let nets = vec![SessionNet {
+        name: "Net (R1 pad 1)".to_string(),
+        wires: wiring.wires,
+        vias: wiring.vias,
+        unknown: Vec::new(),
+    }];
+    let session = routed_session(&dsn, nets).unwrap();
+    let written = write_session(&session);
+    let reparsed = parse_s_expr_to_session(&parse_dsn_to_s_expr(&written).unwrap()).unwrap();
+    let routes = reparsed.routes.unwrap();
+    assert_eq!(routes.library_out.len(), 1);
+    assert_eq!(routes.library_out[0].name, "Via[0-1]_600:300_um");
+    let session_routes = session.routes.unwrap();
+    assert_eq!(routes.network_out, session_routes.network_out);
+}
+
+#[test]
+fn routed_design_round_trips() {
+    let mut dsn = parse(SAMPLE);
+    let session = parse_s_expr_to_session(&parse_dsn_to_s_expr(SESSION).unwrap()).unwrap();
+    let mut renamed = session.clone();
+    renamed.routes.as_mut().unwrap().network_out[0].name = "renamed".to_string();
+    let error = apply_session(&mut dsn, &renamed).unwrap_err();
+    assert!(matches!(error.kind, DsnErrorKind::DanglingReference { .. }));
+    assert_eq!(dsn, parse(SAMPLE), "a rejected session changed the design");
+
+    apply_session(&mut dsn, &session).unwrap();
+    let routed = assert_round_trip(&write_dsn(&dsn));
+    let wiring = routed.wiring.as_ref().unwrap();
+    assert_eq!(wiring.wires.len(), 9);
+    assert!(wiring.wires.iter().all(|wire| wire.net.is_some()));
+}
+
+#[test]
+fn session_copper_the_model_lacks_is_reported_and_design_copper_kept() {
+    let mut dsn = parse(&read_case("back_side_multiple_boundaries.dsn"));
+    let polygon = dsn.wiring.as_ref().unwrap().unknown.clone();
+    assert_eq!(polygon.len(), 1);
+
+    let session = r#"(session routed
+  (base_design routed)
+  (routes
+    (resolution um 10)
+    (network_out
+      (net GND
+        (wire (path F.Cu 2000 0 0 100000 0))
+        (wire (polygon F.Cu 0 0 0 1000 0 1000 -1000))
+      )
+    )
+  )
+)"#;
+    let s_expr = parse_dsn_to_s_expr(session).unwrap();
+    let (session, _) = parse_s_expr_to_session_with_mode(&s_expr, ParseMode::Lenient).unwrap();
+    let warnings = apply_session(&mut dsn, &session).unwrap();
+
+    let warnings: Vec<String> = warnings.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        warnings,
+        ["warning: session > routes > network_out > net GND: polygon wire on F.Cu not taken over"]
+    );
+    let wiring = dsn.wiring.as_ref().unwrap();
+    assert_eq!(wiring.wires.len(), 1);
+    assert_eq!(wiring.wires[0].width, Length::from_um(200.0));
+    assert!(wiring.vias.is_empty());
+    assert_eq!(wiring.unknown, polygon);
+    let written = write_dsn(&dsn);
+    assert!(written.contains("(polygon F.Cu 0 0 0 100 0 100 -100)"));
+}
+
+#[test]
+fn was_is_swaps_pins_between_nets() {
+    let mut dsn = parse(SAMPLE);
+    let first = dsn.network.nets[0].pins[0].clone();
+    let second = dsn.network.nets[1].pins[0].clone();
+    let mut session = routed_session(&dsn, Vec::new()).unwrap();
+    let was_is = session.was_is.as_mut().unwrap();
+    was_is.pin_swaps = vec![
+        PinSwap {
+            was: first.clone(),
+            is: second.clone(),
+        },
+        PinSwap {
+            was: second.clone(),
+            is: first.clone(),
+        },
+    ];
+    let written = write_session(&session);
+    let reparsed = parse_s_expr_to_session(&parse_dsn_to_s_expr(&written).unwrap()).unwrap();
+    assert_eq!(session, reparsed, "was_is changed on round trip");
+
+    apply_session(&mut dsn, &reparsed).unwrap();
+    assert_eq!(dsn.network.nets[0].pins[0], second);
+    assert_eq!(dsn.network.nets[1].pins[0], first);
+
+    // moving a connection onto a pin that keeps its own leaves it on two nets
+    let mut one_way = reparsed.clone();
+    one_way.was_is.as_mut().unwrap().pin_swaps.pop();
+    let before = dsn.clone();
+    let error = apply_session(&mut dsn, &one_way).unwrap_err();
+    assert!(matches!(error.kind, DsnErrorKind::AlreadyConnected { .. }));
+    assert_eq!(dsn, before, "a rejected session changed the design");
+}
+
+#[test]
+fn quoted_names_survive() {
+    let dsn = assert_round_trip(&read_case("quoted_names.dsn"));
+    assert_eq!(dsn.design, r"C:\My Projects\board v2.dsn");
+    assert!(
+        dsn.library
+            .images
+            .contains_key("Resistor SMD:R 0603 (1608 metric)")
+    );
+    assert!(dsn.network.netclasses.contains_key("Power Class"));
+    let net = &dsn.network.nets[0];
+    assert_eq!(net.name, "Net (R1 pad 1)");
+    let wiring = dsn.wiring.as_ref().unwrap();
+    assert_eq!(wiring.wires[0].net.as_deref(), Some("Net (R1 pad 1)"));
+    assert_eq!(wiring.vias[0].padstack_name, "Via[0-1]_600:300_um");
+}
+
+#[test]
+fn bga_pin_ids_survive() {
+    let dsn = assert_round_trip(&read_case("bga.dsn"));
+    let image = &dsn.library.images["BGA-4x10_0.8mm"];
+    for pin_id in ["1", "A2", "A10", "B1", "AA1"] {
+        assert!(image.pins.contains_key(pin_id), "pin {} is missing", pin_id);
+    }
+    assert_eq!(
+        image.pins["B1"].clearance_class.as_deref(),
+        Some("BGA_fine")
+    );
+    let pins: Vec<&str> = dsn.network.nets[0]
+        .pins
+        .iter()
+        .map(|pin| pin.pin_id.as_str())
+        .collect();
+    assert_eq!(pins, ["1", "A10", "AA1"]);
+}
+
+#[test]
+fn dashed_component_names_and_pin_ids_survive() {
+    let mut dsn = assert_round_trip(&read_case("dashed_pin_ids.dsn"));
+    let pins: Vec<(&str, &str)> = dsn
+        .network
+        .nets
+        .iter()
+        .flat_map(|net| &net.pins)
+        .map(|pin| (pin.component_name.as_str(), pin.pin_id.as_str()))
+        .collect();
+    assert_eq!(
+        pins,
+        [
+            ("SW-1", "A-1"),
+            ("J1", "Pin-1"),
+            ("SW-1", "B-1"),
+            ("J1", "Pin-2")
+        ]
+    );
+
+    // a session names its pins the same way
+    let mut session = routed_session(&dsn, Vec::new()).unwrap();
+    let first = dsn.network.nets[0].pins[1].clone();
+    let second = dsn.network.nets[1].pins[1].clone();
+    session.was_is.as_mut().unwrap().pin_swaps = vec![
+        PinSwap {
+            was: first.clone(),
+            is: second.clone(),
+        },
+        PinSwap {
+            was: second.clone(),
+            is: first.clone(),
+        },
+    ];
+    let written = write_session(&session);
+    let reparsed = parse_s_expr_to_session(&parse_dsn_to_s_expr(&written).unwrap()).unwrap();
+    assert_eq!(session, reparsed, "was_is changed on round trip");
+    apply_session(&mut dsn, &reparsed).unwrap();
+    assert_eq!(dsn.network.nets[0].pins[1], second);
+}
+
+#[test]
+fn back_side_parts_and_boundaries_survive() {
+    let dsn = assert_round_trip(&read_case("back_side_multiple_boundaries.dsn"));
+    assert_eq!(dsn.structure.boundaries.len(), 2);
+    let instances = &dsn.placement.components[0].instances;
+    let sides: Vec<Side> = instances.iter().map(|instance| instance.side).collect();
+    assert_eq!(sides, [Side::Back, Side::Back, Side::Front]);
+    assert_eq!(instances[1].rotation, 45.5);
+    let image = &dsn.library.images["Package_TO_SOT_SMD:SOT-23"];
+    assert_eq!(image.pins["1"].rotation, 90.0);
+    assert_eq!(image.pins["3"].rotation, 0.0);
+    // the polygon wire is not modelled and comes back verbatim
+    assert_eq!(dsn.wiring.as_ref().unwrap().unknown.len(), 1);
+}
+
+#[test]
+fn class_scopes_survive() {
+    let input = read_case("class_scopes.dsn");
+    let dsn = assert_round_trip(&input);
+    let power = &dsn.network.netclasses["Power"];
+    assert_eq!(power.via_name, "Via_600");
+    assert_eq!(power.circuit_unknown.len(), 1);
+    assert_eq!(power.unknown.len(), 1);
+    assert_eq!(dsn.network.class_classes[0].unknown.len(), 1);
+
+    let written = write_dsn(&dsn);
+    assert!(written.contains("(use_layer F.Cu)"));
+    assert!(written.contains("(vendor_hint (priority 2))"));
+    assert!(written.contains("(layer_rule F.Cu (rule (clearance 500)))"));
+}
+
+#[test]
+fn signal_boundaries_and_pad_offsets_survive() {
+    let dsn = assert_round_trip(&read_case("signal_boundary_offset_pads.dsn"));
+    let boundaries = &dsn.structure.boundaries;
+    assert_eq!(boundaries[1].layer, "signal");
+    assert_eq!(boundaries[1].width, Length::from_um(50.0));
+    let offset_round = &dsn.library.pad_stacks["Offset_Round"];
+    assert_eq!(
+        offset_round.shape(),
+        Some(&Shape::Circle {
+            diameter: Length::from_um(1200.0),
+            center: Point::new(Length::from_um(300.0), Length::from_um(-150.0)),
+        })
+    );
+    // oval pads are not modelled and come back verbatim
+    let oval = &dsn.library.pad_stacks["Oval"];
+    assert!(oval.shapes.is_empty());
+    assert_eq!(oval.unknown.len(), 2);
+
+    let written = write_dsn(&dsn);
+    assert!(written.contains("(path signal 50 500 -500 19500 -500"));
+    assert!(written.contains("(circle F.Cu 1200 300 -150)"));
+    assert!(written.contains("(shape (path B.Cu 1000 -250 0 250 0))"));
+}
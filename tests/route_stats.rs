@@ -0,0 +1,90 @@
+//! Routing figures of a small board, checked against values worked out by
+//! hand.
+
+use pcb_parsing::dsn_struct::DsnStruct;
+use pcb_parsing::route_stats::route_stats;
+use pcb_parsing::units::Length;
+
+mod common;
+use common::Board;
+
+/// Net A goes down 5 mm on F.Cu along a 3-4-5 diagonal, through a via, and
+/// on 3 mm across and 4 mm down on B.Cu. Net B runs 6 mm straight on F.Cu
+/// with a point in the middle, net C is not routed, and a 1 mm wire and a
+/// via belong to no net.
+fn board() -> DsnStruct {
+    Board::new("stats")
+        .via("V", "(shape (circle signal 600))")
+        .place("Through", "J1 2000 -2000 front 0")
+        .place("Through", "J2 8000 -10000 front 0")
+        .place("Through", "J3 12000 -2000 front 0")
+        .place("Through", "J4 18000 -2000 front 0")
+        .place("Through", "J5 2000 -18000 front 0")
+        .place("Through", "J6 8000 -18000 front 0")
+        .library("(image Through (pin ThroughPad 1 0 0))")
+        .library("(padstack ThroughPad (shape (circle signal 1000)))")
+        .network("(net A (pins J1-1 J2-1))")
+        .network("(net B (pins J3-1 J4-1))")
+        .network("(net C (pins J5-1 J6-1))")
+        .network("(class default A B C (circuit (use_via V)))")
+        .wiring("(wire (path F.Cu 250 2000 -2000 5000 -6000 5000 -6000) (net A))")
+        .wiring("(via V 5000 -6000 (net A))")
+        .wiring("(wire (path B.Cu 250 5000 -6000 8000 -6000 8000 -10000) (net A))")
+        .wiring("(wire (path F.Cu 250 12000 -2000 15000 -2000 18000 -2000) (net B))")
+        .wiring("(wire (path F.Cu 250 12000 -15000 13000 -15000))")
+        .wiring("(via V 15000 -15000)")
+        .parse()
+}
+
+fn mm(value: f64) -> Length {
+    Length::from_mm(value)
+}
+
+#[test]
+fn per_net_figures() {
+    let stats = route_stats(&board()).unwrap();
+    let a = &stats.nets[0];
+    assert_eq!(a.net, "A");
+    assert_eq!(a.length, mm(12.0));
+    // the repeated point of the first wire is no segment
+    assert_eq!((a.segments, a.bends, a.vias), (3, 1, 1));
+    assert_eq!(
+        a.layer_lengths.iter().collect::<Vec<_>>(),
+        [
+            (&"B.Cu".to_string(), &mm(7.0)),
+            (&"F.Cu".to_string(), &mm(5.0))
+        ]
+    );
+
+    let b = &stats.nets[1];
+    assert_eq!(b.length, mm(6.0));
+    // a point in the middle of a straight line is no bend
+    assert_eq!((b.segments, b.bends, b.vias), (2, 0, 0));
+
+    let c = &stats.nets[2];
+    assert_eq!((c.length, c.segments, c.vias), (Length::ZERO, 0, 0));
+    assert!(c.layer_lengths.is_empty());
+}
+
+#[test]
+fn board_totals_include_copper_without_a_net() {
+    let stats = route_stats(&board()).unwrap();
+    assert_eq!(stats.vias, 2);
+    assert_eq!(stats.layer_lengths["F.Cu"], mm(12.0));
+    assert_eq!(stats.layer_lengths["B.Cu"], mm(7.0));
+    assert_eq!(stats.length(), mm(19.0));
+    // A and B are routed, C is not
+    assert!((stats.completion - 200.0 / 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn report() {
+    let stats = route_stats(&board()).unwrap();
+    assert_eq!(
+        stats.to_string(),
+        "net A: 12mm, 3 segments, 1 bends, 1 vias (B.Cu 7mm, F.Cu 5mm)\n\
+         net B: 6mm, 2 segments, 0 bends, 0 vias (F.Cu 6mm)\n\
+         net C: 0mm, 0 segments, 0 bends, 0 vias\n\
+         board: 19mm, 2 vias, 66.7% routed (B.Cu 7mm, F.Cu 12mm)"
+    );
+}
@@ -0,0 +1,197 @@
+//! Each level of the rule hierarchy overrides the more general ones, and
+//! two objects on one level get the larger of their values.
+
+use pcb_parsing::dsn_index::DsnIndex;
+use pcb_parsing::rules::{RuleObject, RuleResolver};
+use pcb_parsing::units::Length;
+
+mod common;
+use common::Board;
+
+/// Rules for each level; an empty string leaves the level unset.
+#[derive(Default)]
+struct Rules {
+    pcb: &'static str,
+    power: &'static str,
+    signal: &'static str,
+    class_class: &'static str,
+    net_a: &'static str,
+}
+
+/// Net A is in class Power, nets B and C are in class Signal.
+fn board(rules: Rules) -> Board {
+    let mut board = Board::new("rules")
+        .via("V", "(shape (circle signal 600))")
+        .place("R", "R1 5000 -5000 front 0")
+        .place("R", "R2 15000 -5000 front 0")
+        .library("(image R (pin Round 1 -800 0) (pin Round 2 800 0))")
+        .library("(padstack Round (shape (circle F.Cu 800)))")
+        .network(&format!("(net A (pins R1-1) {})", rules.net_a))
+        .network("(net B (pins R1-2))")
+        .network("(net C (pins R2-1))")
+        .network(&format!(
+            "(class Power A (circuit (use_via V)) {})",
+            rules.power
+        ))
+        .network(&format!(
+            "(class Signal B C (circuit (use_via V)) {})",
+            rules.signal
+        ));
+    if !rules.pcb.is_empty() {
+        board = board.structure(rules.pcb);
+    }
+    if !rules.class_class.is_empty() {
+        board = board.network(rules.class_class);
+    }
+    board
+}
+
+fn object(net: &'static str) -> RuleObject<'static> {
+    RuleObject {
+        net: Some(net),
+        clearance_class: None,
+    }
+}
+
+fn pin(net: &'static str, clearance_class: &'static str) -> RuleObject<'static> {
+    RuleObject {
+        net: Some(net),
+        clearance_class: Some(clearance_class),
+    }
+}
+
+/// Clearance in um between `a` and `b` under `rules`.
+fn clearance(rules: Rules, a: RuleObject, b: RuleObject) -> f64 {
+    let dsn = board(rules).parse();
+    let index = DsnIndex::new(&dsn).unwrap();
+    RuleResolver::new(&index).clearance(a, b).as_mm() * 1000.0
+}
+
+fn width(rules: Rules, net: &str) -> Length {
+    let dsn = board(rules).parse();
+    let index = DsnIndex::new(&dsn).unwrap();
+    RuleResolver::new(&index).width(Some(net))
+}
+
+const PCB: &str = "(rule (width 200) (clearance 200))";
+
+#[test]
+fn no_rule_means_no_clearance() {
+    let rules = Rules::default();
+    assert_eq!(clearance(rules, object("A"), object("B")), 0.0);
+}
+
+#[test]
+fn pcb_rule_applies_to_everything() {
+    let rules = Rules {
+        pcb: PCB,
+        ..Rules::default()
+    };
+    assert_eq!(clearance(rules, object("A"), RuleObject::default()), 200.0);
+}
+
+#[test]
+fn class_overrides_pcb() {
+    let rules = Rules {
+        pcb: PCB,
+        power: "(rule (clearance 150))",
+        ..Rules::default()
+    };
+    assert_eq!(clearance(rules, object("A"), object("B")), 150.0);
+}
+
+#[test]
+fn two_classes_take_the_larger_clearance() {
+    let rules = || Rules {
+        pcb: PCB,
+        power: "(rule (clearance 300))",
+        signal: "(rule (clearance 400))",
+        ..Rules::default()
+    };
+    assert_eq!(clearance(rules(), object("A"), object("B")), 400.0);
+    assert_eq!(clearance(rules(), object("B"), object("A")), 400.0);
+    // an object without a class only brings the pcb level
+    assert_eq!(
+        clearance(rules(), object("A"), RuleObject::default()),
+        300.0
+    );
+}
+
+#[test]
+fn class_class_overrides_the_classes() {
+    let rules = || Rules {
+        pcb: PCB,
+        power: "(rule (clearance 300))",
+        signal: "(rule (clearance 400))",
+        class_class: "(class_class (classes Signal Power) (rule (clearance 250)))",
+        ..Rules::default()
+    };
+    assert_eq!(clearance(rules(), object("A"), object("B")), 250.0);
+    // B and C are both Signal, which the class_class does not pair
+    assert_eq!(clearance(rules(), object("B"), object("C")), 400.0);
+}
+
+#[test]
+fn net_overrides_class_class() {
+    let rules = Rules {
+        pcb: PCB,
+        power: "(rule (clearance 300))",
+        class_class: "(class_class (classes Signal Power) (rule (clearance 250)))",
+        net_a: "(rule (clearance 120))",
+        ..Rules::default()
+    };
+    assert_eq!(clearance(rules, object("A"), object("B")), 120.0);
+}
+
+#[test]
+fn pin_type_overrides_net() {
+    let rules = || Rules {
+        pcb: "(rule (clearance 200) (clearance 80 (type smd_smd)) (clearance 90 (type default_smd)))",
+        net_a: "(rule (clearance 120))",
+        ..Rules::default()
+    };
+    assert_eq!(clearance(rules(), pin("A", "smd"), pin("B", "smd")), 80.0);
+    // only one side has a class: KiCad's `default_<class>` type
+    assert_eq!(clearance(rules(), pin("A", "smd"), object("B")), 90.0);
+    assert_eq!(clearance(rules(), object("B"), pin("A", "smd")), 90.0);
+    // a class no type names falls back to the untyped levels
+    assert_eq!(clearance(rules(), pin("A", "bga"), pin("B", "bga")), 120.0);
+}
+
+#[test]
+fn typed_clearances_are_found_on_every_level() {
+    let rules = || Rules {
+        pcb: "(rule (clearance 200) (clearance 80 (type smd_smd)))",
+        signal: "(rule (clearance 60 (type smd_smd)))",
+        class_class: "(class_class (classes Signal Power) (rule (clearance 70 (type smd_smd))))",
+        ..Rules::default()
+    };
+    // the class_class is the most specific level typing A and B
+    assert_eq!(clearance(rules(), pin("A", "smd"), pin("B", "smd")), 70.0);
+    // B and C only reach the Signal class
+    assert_eq!(clearance(rules(), pin("B", "smd"), pin("C", "smd")), 60.0);
+}
+
+#[test]
+fn width_follows_pcb_class_and_net() {
+    let um = Length::from_um;
+    let pcb = Rules {
+        pcb: PCB,
+        ..Rules::default()
+    };
+    assert_eq!(width(pcb, "A"), um(200.0));
+    let class = Rules {
+        pcb: PCB,
+        power: "(rule (width 300))",
+        ..Rules::default()
+    };
+    assert_eq!(width(class, "A"), um(300.0));
+    let net = Rules {
+        pcb: PCB,
+        power: "(rule (width 300))",
+        net_a: "(rule (width 350))",
+        ..Rules::default()
+    };
+    assert_eq!(width(net, "A"), um(350.0));
+    assert_eq!(width(Rules::default(), "A"), Length::ZERO);
+}
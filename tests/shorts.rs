@@ -0,0 +1,79 @@
+//! Copper of two nets shorts only where it touches on one layer, which a
+//! via can bring about on a layer its wire never reaches.
+
+use pcb_parsing::shorts::find_shorts;
+
+mod common;
+use common::Board;
+
+/// VCC runs left to right between two top pads and GND top to bottom
+/// between two through-hole pads; the wiring is added by each test.
+fn board() -> Board {
+    Board::new("shorts")
+        .via("V", "(shape (circle signal 600))")
+        .place("Top", "J1 2000 -5000 front 0")
+        .place("Top", "J2 18000 -5000 front 0")
+        .place("Through", "J3 10000 -1000 front 0")
+        .place("Through", "J4 10000 -9000 front 0")
+        .library("(image Top (pin TopPad 1 0 0))")
+        .library("(image Through (pin ThroughPad 1 0 0))")
+        .library("(padstack TopPad (shape (rect F.Cu -500 -500 500 500)))")
+        .library("(padstack ThroughPad (shape (circle signal 1000)))")
+        .network("(net VCC (pins J1-1 J2-1))")
+        .network("(net GND (pins J3-1 J4-1))")
+        .network("(class default VCC GND (circuit (use_via V)))")
+}
+
+fn shorts(wiring: &[&str]) -> Vec<String> {
+    let board = wiring
+        .iter()
+        .fold(board(), |board, item| board.wiring(item));
+    find_shorts(&board.parse())
+        .unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn crossing_wires_on_one_layer_short() {
+    let shorts = shorts(&[
+        "(wire (path F.Cu 250 2000 -5000 18000 -5000) (net VCC))",
+        "(wire (path F.Cu 250 10000 -1000 10000 -9000) (net GND))",
+    ]);
+    assert_eq!(
+        shorts,
+        ["short between VCC and GND on F.Cu at (10mm, -5mm): wire #0 touches wire #1"]
+    );
+}
+
+#[test]
+fn crossing_wires_on_different_layers_do_not_short() {
+    let shorts = shorts(&[
+        "(wire (path F.Cu 250 2000 -5000 18000 -5000) (net VCC))",
+        "(wire (path B.Cu 250 10000 -1000 10000 -9000) (net GND))",
+    ]);
+    assert!(shorts.is_empty(), "{:?}", shorts);
+}
+
+#[test]
+fn via_shorts_a_wire_on_its_other_layer() {
+    let shorts = shorts(&[
+        "(wire (path F.Cu 250 2000 -5000 10000 -5000) (net VCC))",
+        "(wire (path B.Cu 250 10000 -1000 10000 -9000) (net GND))",
+        "(via V 10000 -5000 (net VCC))",
+    ]);
+    assert_eq!(
+        shorts,
+        ["short between GND and VCC on B.Cu at (10mm, -5mm): wire #1 touches via #0"]
+    );
+}
+
+#[test]
+fn copper_without_a_net_shorts_nothing() {
+    let shorts = shorts(&[
+        "(wire (path F.Cu 250 2000 -5000 18000 -5000) (net VCC))",
+        "(wire (path F.Cu 250 10000 -1000 10000 -9000))",
+    ]);
+    assert!(shorts.is_empty(), "{:?}", shorts);
+}
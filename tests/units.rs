@@ -0,0 +1,120 @@
+//! The unit a file is written in must not change the board it describes.
+
+use pcb_parsing::dsn_struct::DsnStruct;
+use pcb_parsing::parse_to_display::dsn_to_display;
+use pcb_parsing::parse_to_s_expr::parse_dsn_to_s_expr;
+use pcb_parsing::parse_to_struct::{ParseMode, parse_s_expr_to_struct_with_mode};
+use pcb_parsing::pcb_problem::NetName;
+
+/// One board, written in mil.
+const MIL_BOARD: &str = r#"(pcb units
+  (resolution mil 1000)
+  (unit mil)
+  (structure
+    (layer F.Cu (type signal))
+    (layer B.Cu (type signal))
+    (boundary (path pcb 0 0 0 1000 0 1000 -800 0 -800 0 0))
+    (via Via_24)
+    (rule (width 10) (clearance 8) (clearance 12 (type default_smd)))
+  )
+  (placement
+    (component R
+      (place R1 500 -400 front 90)
+      (place R2 250 -400 back 0)
+    )
+  )
+  (library
+    (image R
+      (pin Round 1 -30 0)
+      (pin Square 2 30 0 (clearance_class smd))
+    )
+    (padstack Round (shape (circle F.Cu 32)))
+    (padstack Square (shape (rect F.Cu -20 -10 20 10)))
+    (padstack Via_24 (shape (circle F.Cu 24)) (shape (circle B.Cu 24)))
+  )
+  (network
+    (net A (pins R1-1 R2-2))
+    (net B (pins R1-2 R2-1))
+    (class default A B (circuit (use_via Via_24)))
+  )
+)"#;
+
+/// The same board in um, every length multiplied by 25.4.
+const UM_BOARD: &str = r#"(pcb units
+  (resolution um 10)
+  (unit um)
+  (structure
+    (layer F.Cu (type signal))
+    (layer B.Cu (type signal))
+    (boundary (path pcb 0 0 0 25400 0 25400 -20320 0 -20320 0 0))
+    (via Via_24)
+    (rule (width 254) (clearance 203.2) (clearance 304.8 (type default_smd)))
+  )
+  (placement
+    (component R
+      (place R1 12700 -10160 front 90)
+      (place R2 6350 -10160 back 0)
+    )
+  )
+  (library
+    (image R
+      (pin Round 1 -762 0)
+      (pin Square 2 762 0 (clearance_class smd))
+    )
+    (padstack Round (shape (circle F.Cu 812.8)))
+    (padstack Square (shape (rect F.Cu -508 -254 508 254)))
+    (padstack Via_24 (shape (circle F.Cu 609.6)) (shape (circle B.Cu 609.6)))
+  )
+  (network
+    (net A (pins R1-1 R2-2))
+    (net B (pins R1-2 R2-1))
+    (class default A B (circuit (use_via Via_24)))
+  )
+)"#;
+
+fn parse(input: &str) -> DsnStruct {
+    let s_expr = parse_dsn_to_s_expr(input).unwrap();
+    let (dsn, _) = parse_s_expr_to_struct_with_mode(&s_expr, ParseMode::Strict).unwrap();
+    dsn
+}
+
+#[test]
+fn mil_and_um_files_give_the_same_geometry() {
+    let (mil, mil_extra) = dsn_to_display(parse(MIL_BOARD)).unwrap();
+    let (um, um_extra) = dsn_to_display(parse(UM_BOARD)).unwrap();
+
+    assert_eq!(
+        (mil.width, mil.height, mil.center),
+        (um.width, um.height, um.center)
+    );
+    for name in ["A", "B"] {
+        let mil_net = &mil.nets[&NetName(name.to_string())];
+        let um_net = &um.nets[&NetName(name.to_string())];
+        assert_eq!(mil_net.default_trace_width, um_net.default_trace_width);
+        assert_eq!(
+            mil_net.default_trace_clearance,
+            um_net.default_trace_clearance
+        );
+        assert_eq!(mil_net.via_diameter, um_net.via_diameter);
+        assert_eq!(mil_net.pads.len(), um_net.pads.len());
+        for (mil_pad, um_pad) in mil_net.pads.iter().zip(&um_net.pads) {
+            assert_eq!(mil_pad.name, um_pad.name);
+            assert_eq!(mil_pad.position, um_pad.position);
+            assert_eq!(mil_pad.rotation, um_pad.rotation);
+            assert_eq!(mil_pad.clearance, um_pad.clearance);
+            assert_eq!(
+                format!("{:?}", mil_pad.shape),
+                format!("{:?}", um_pad.shape)
+            );
+        }
+    }
+
+    // only the pins of clearance class `smd` differ from their net
+    let mut overrides: Vec<_> = mil_extra.pad_name_to_trace_clearance.into_iter().collect();
+    overrides.sort();
+    let mut um_overrides: Vec<_> = um_extra.pad_name_to_trace_clearance.into_iter().collect();
+    um_overrides.sort();
+    assert_eq!(overrides, um_overrides);
+    let names: Vec<&str> = overrides.iter().map(|(name, _)| name.0.as_str()).collect();
+    assert_eq!(names, ["R1-2", "R2-2"]);
+}
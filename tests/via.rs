@@ -0,0 +1,119 @@
+//! Vias are resolved from their padstack shapes; a KiCad via name only adds
+//! the drill, and is not trusted when it disagrees with the shapes.
+
+use pcb_parsing::dsn_index::DsnIndex;
+use pcb_parsing::units::Length;
+use pcb_parsing::via::{Via, ViaKind, ViaName};
+
+mod common;
+use common::Board;
+
+fn um(value: f64) -> Length {
+    Length::from_um(value)
+}
+
+/// A four-layer board offering the via `name` with pads `shapes`.
+fn board(name: &str, shapes: &str) -> Board {
+    Board::new("via")
+        .layers(&["F.Cu", "In1.Cu", "In2.Cu", "B.Cu"])
+        .via(name, shapes)
+}
+
+/// The via and the warnings of indexing `board`.
+fn resolve(board: Board, name: &str) -> (Via, Vec<String>) {
+    let dsn = board.parse();
+    let index = DsnIndex::new(&dsn).unwrap();
+    let warnings = index.warnings().iter().map(ToString::to_string).collect();
+    (index.via(name).unwrap().clone(), warnings)
+}
+
+#[test]
+fn kicad_via_names_are_parsed() {
+    assert_eq!(
+        ViaName::parse("Via[0-3]_600:300_um"),
+        Some(ViaName {
+            start_layer: 0,
+            end_layer: 3,
+            diameter: um(600.0),
+            drill: um(300.0),
+        })
+    );
+    let mm = ViaName::parse("Via[1-2]_0.45:0.2_mm").unwrap();
+    assert_eq!((mm.start_layer, mm.end_layer), (1, 2));
+    assert_eq!((mm.diameter, mm.drill), (um(450.0), um(200.0)));
+}
+
+#[test]
+fn other_via_names_are_not_parsed() {
+    for name in [
+        "V",
+        "Via_600:300_um",
+        "Via[0-1]_600_um",
+        "Via[0-1]_600:300",
+        "Via[0-1]_600:300_furlong",
+        "Via[a-1]_600:300_um",
+        "Via[0-1]_big:300_um",
+    ] {
+        assert_eq!(ViaName::parse(name), None, "{}", name);
+    }
+}
+
+#[test]
+fn span_gives_the_kind() {
+    let through = board("T", "(shape (circle signal 600))");
+    assert_eq!(resolve(through, "T").0.kind, ViaKind::Through);
+
+    let blind = board("B", "(shape (circle F.Cu 600)) (shape (circle In2.Cu 600))");
+    let (via, _) = resolve(blind, "B");
+    assert_eq!((via.start_layer, via.end_layer), (0, 2));
+    assert_eq!(via.kind, ViaKind::Blind);
+
+    let buried = board(
+        "I",
+        "(shape (circle In1.Cu 600)) (shape (circle In2.Cu 600))",
+    );
+    let (via, _) = resolve(buried, "I");
+    assert_eq!((via.start_layer, via.end_layer), (1, 2));
+    assert_eq!(via.kind, ViaKind::Buried);
+}
+
+#[test]
+fn matching_kicad_name_adds_the_drill() {
+    let name = "Via[0-3]_600:300_um";
+    let (via, warnings) = resolve(board(name, "(shape (circle signal 600))"), name);
+    assert_eq!(via.drill, Some(um(300.0)));
+    assert_eq!(via.diameter(), um(600.0));
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+#[test]
+fn rect_pad_is_sized_by_its_bounding_box() {
+    let rect = board("R", "(shape (rect signal -300 -400 300 400))");
+    let (via, warnings) = resolve(rect, "R");
+    assert_eq!(via.kind, ViaKind::Through);
+    assert_eq!(via.diameter(), um(800.0));
+    assert!(warnings.is_empty(), "{:?}", warnings);
+
+    let polygon = board("P", "(shape (polygon signal 100 -250 0 0 250 250 0))");
+    assert_eq!(resolve(polygon, "P").0.diameter(), um(600.0));
+}
+
+#[test]
+fn name_disagreeing_with_the_shapes_is_a_warning() {
+    // named for all four layers, but reaching only two
+    let name = "Via[0-3]_600:300_um";
+    let blind = board(
+        name,
+        "(shape (circle F.Cu 600)) (shape (circle In1.Cu 600))",
+    );
+    let (via, warnings) = resolve(blind, name);
+    assert_eq!(via.kind, ViaKind::Blind);
+    assert_eq!(via.drill, None);
+    assert_eq!(
+        warnings,
+        [
+            "warning: pcb > library > padstack \"Via[0-3]_600:300_um\": \
+             via name Via[0-3]_600:300_um disagrees with the padstack shapes, which are used"
+        ]
+    );
+}